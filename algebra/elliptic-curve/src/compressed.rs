@@ -0,0 +1,241 @@
+use crate::Affine;
+use std::fmt;
+use zkp_primefield::FieldElement;
+use zkp_u256::{Binary, U256};
+
+/// Prefix of the compressed point at infinity.
+const PREFIX_ZERO: u8 = 0x00;
+
+/// Prefix of a compressed point with even `y`. Odd `y` is one higher.
+const PREFIX_EVEN: u8 = 0x02;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    InvalidPrefix(u8),
+    CoordinateOutOfRange,
+    NotOnCurve,
+    NotInSubgroup,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+        match *self {
+            InvalidPrefix(prefix) => write!(f, "Invalid compressed point prefix {:#04x}", prefix),
+            CoordinateOutOfRange => write!(f, "Coordinate is not a reduced field element"),
+            NotOnCurve => write!(f, "The coordinates are not of a point on the curve"),
+            NotInSubgroup => write!(f, "The point is not in the prime order subgroup"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl Affine {
+    /// Encode as a prefix byte followed by the big-endian x-coordinate.
+    ///
+    /// The prefix is `0x02` for even `y` and `0x03` for odd `y`. The point at
+    /// infinity is encoded as 33 zero bytes.
+    #[must_use]
+    pub fn to_compressed(&self) -> [u8; 33] {
+        let mut result = [0_u8; 33];
+        if let Self::Point { x, y } = self {
+            result[0] = PREFIX_EVEN + u8::from(y.to_uint().bit(0));
+            result[1..].copy_from_slice(&x.to_uint().to_bytes_be());
+        }
+        result
+    }
+
+    /// Decode a point encoded with [`Affine::to_compressed`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the prefix is invalid, if the x-coordinate is not smaller than
+    /// the field modulus or if there is no point with this x-coordinate.
+    pub fn from_compressed(bytes: &[u8; 33]) -> Result<Self, Error> {
+        let mut coordinate = [0_u8; 32];
+        coordinate.copy_from_slice(&bytes[1..]);
+        let coordinate = U256::from_bytes_be(&coordinate);
+        match bytes[0] {
+            PREFIX_ZERO if coordinate == U256::ZERO => Ok(Self::Zero),
+            prefix @ PREFIX_EVEN..=0x03 => {
+                if coordinate >= FieldElement::MODULUS {
+                    return Err(Error::CoordinateOutOfRange);
+                }
+                let (a, b) =
+                    Self::from_x(&FieldElement::from_uint(&coordinate)).ok_or(Error::NotOnCurve)?;
+                let odd = prefix == PREFIX_EVEN + 1;
+                if a.y().map(|y| y.to_uint().bit(0)) == Some(odd) {
+                    Ok(a)
+                } else {
+                    Ok(b)
+                }
+            }
+            prefix => Err(Error::InvalidPrefix(prefix)),
+        }
+    }
+
+    /// Decode like [`Affine::from_compressed`] and check the point is in the
+    /// prime order subgroup.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`Affine::from_compressed`], or with `NotInSubgroup` if the
+    /// point fails [`Affine::is_in_subgroup`].
+    pub fn from_compressed_checked(bytes: &[u8; 33]) -> Result<Self, Error> {
+        Self::from_compressed(bytes).and_then(Self::check_subgroup)
+    }
+
+    /// Encode as the big-endian x-coordinate followed by the big-endian
+    /// y-coordinate.
+    ///
+    /// The point at infinity is encoded as 64 zero bytes. This is the format
+    /// Ethereum contracts expect.
+    #[must_use]
+    pub fn to_uncompressed(&self) -> [u8; 64] {
+        let mut result = [0_u8; 64];
+        if let Self::Point { x, y } = self {
+            result[..32].copy_from_slice(&x.to_uint().to_bytes_be());
+            result[32..].copy_from_slice(&y.to_uint().to_bytes_be());
+        }
+        result
+    }
+
+    /// Decode a point encoded with [`Affine::to_uncompressed`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if a coordinate is not smaller than the field modulus or if the
+    /// coordinates are not of a point on the curve.
+    pub fn from_uncompressed(bytes: &[u8; 64]) -> Result<Self, Error> {
+        if bytes.iter().all(|&byte| byte == 0) {
+            return Ok(Self::Zero);
+        }
+        let coordinate = |bytes: &[u8]| {
+            let mut coordinate = [0_u8; 32];
+            coordinate.copy_from_slice(bytes);
+            let coordinate = U256::from_bytes_be(&coordinate);
+            if coordinate >= FieldElement::MODULUS {
+                Err(Error::CoordinateOutOfRange)
+            } else {
+                Ok(FieldElement::from_uint(&coordinate))
+            }
+        };
+        let point = Self::new(coordinate(&bytes[..32])?, coordinate(&bytes[32..])?);
+        if point.is_on_curve() {
+            Ok(point)
+        } else {
+            Err(Error::NotOnCurve)
+        }
+    }
+
+    /// Decode like [`Affine::from_uncompressed`] and check the point is in the
+    /// prime order subgroup.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`Affine::from_uncompressed`], or with `NotInSubgroup` if
+    /// the point fails [`Affine::is_in_subgroup`].
+    pub fn from_uncompressed_checked(bytes: &[u8; 64]) -> Result<Self, Error> {
+        Self::from_uncompressed(bytes).and_then(Self::check_subgroup)
+    }
+
+    fn check_subgroup(self) -> Result<Self, Error> {
+        if self.is_in_subgroup() {
+            Ok(self)
+        } else {
+            Err(Error::NotInSubgroup)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GENERATOR;
+    use proptest::prelude::*;
+    use zkp_primefield::Zero;
+
+    #[test]
+    fn test_zero() {
+        assert_eq!(Affine::Zero.to_compressed(), [0; 33]);
+        assert_eq!(Affine::from_compressed(&[0; 33]), Ok(Affine::Zero));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut bytes = GENERATOR.to_compressed();
+        bytes[0] = 0x04;
+        assert_eq!(
+            Affine::from_compressed(&bytes),
+            Err(Error::InvalidPrefix(4))
+        );
+        bytes[0] = PREFIX_ZERO;
+        assert_eq!(
+            Affine::from_compressed(&bytes),
+            Err(Error::InvalidPrefix(0))
+        );
+
+        let mut bytes = [0xff; 33];
+        bytes[0] = PREFIX_EVEN;
+        assert_eq!(
+            Affine::from_compressed(&bytes),
+            Err(Error::CoordinateOutOfRange)
+        );
+
+        // There is no point with x = 0
+        assert_eq!(Affine::from_x(&FieldElement::zero()), None);
+        let mut bytes = [0; 33];
+        bytes[0] = PREFIX_EVEN;
+        assert_eq!(Affine::from_compressed(&bytes), Err(Error::NotOnCurve));
+    }
+
+    #[test]
+    fn test_uncompressed_zero() {
+        assert_eq!(Affine::Zero.to_uncompressed(), [0; 64]);
+        assert_eq!(Affine::from_uncompressed(&[0; 64]), Ok(Affine::Zero));
+    }
+
+    #[test]
+    fn test_uncompressed_invalid() {
+        let mut bytes = GENERATOR.to_uncompressed();
+        bytes[63] ^= 1;
+        assert_eq!(Affine::from_uncompressed(&bytes), Err(Error::NotOnCurve));
+
+        let mut bytes = GENERATOR.to_uncompressed();
+        for byte in &mut bytes[32..] {
+            *byte = 0xff;
+        }
+        assert_eq!(
+            Affine::from_uncompressed(&bytes),
+            Err(Error::CoordinateOutOfRange)
+        );
+    }
+
+    proptest!(
+        #[test]
+        fn roundtrip(point: Affine) {
+            let bytes = point.to_compressed();
+            prop_assert_eq!(Affine::from_compressed(&bytes), Ok(point));
+        }
+
+        #[test]
+        fn roundtrip_uncompressed(point: Affine) {
+            let bytes = point.to_uncompressed();
+            prop_assert_eq!(Affine::from_uncompressed(&bytes), Ok(point));
+        }
+
+        #[test]
+        fn roundtrip_checked(point: Affine) {
+            prop_assert_eq!(
+                Affine::from_compressed_checked(&point.to_compressed()),
+                Ok(point.clone())
+            );
+            prop_assert_eq!(
+                Affine::from_uncompressed_checked(&point.to_uncompressed()),
+                Ok(point)
+            );
+        }
+    );
+}
@@ -4,13 +4,13 @@
 #[allow(clippy::wildcard_imports)]
 use std::prelude::v1::*;
 
-use crate::{ScalarFieldElement, BETA};
+use crate::{Jacobian, ScalarFieldElement, BETA};
 #[cfg(feature = "parity_codec")]
 use parity_scale_codec::{Decode, Encode};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
-use zkp_primefield::{FieldElement, NegInline, One, Zero};
+use zkp_primefield::{FieldElement, NegInline, One, SquareRoot, Zero};
 use zkp_u256::{commutative_binop, noncommutative_binop};
 
 #[derive(PartialEq, Eq, Clone, Debug)]
@@ -29,6 +29,28 @@ impl Affine {
         Self::Point { x, y }
     }
 
+    /// The two points with x-coordinate `x`, or `None` if there are none.
+    ///
+    /// The points are `(x, y)` and `(x, -y)` where `y` is the square root
+    /// returned by [`SquareRoot::square_root`]. If `y` is zero both points are
+    /// the same.
+    #[must_use]
+    pub fn from_x(x: &FieldElement) -> Option<(Self, Self)> {
+        (x * x * x + x + BETA).square_root().map(|y| {
+            let negated = -&y;
+            (Self::new(x.clone(), y), Self::new(x.clone(), negated))
+        })
+    }
+
+    /// Convert many points to Jacobian coordinates.
+    ///
+    /// This needs no inversions, it is the counterpart of
+    /// [`Jacobian::batch_to_affine`].
+    #[must_use]
+    pub fn batch_to_jacobian(points: &[Self]) -> Vec<Jacobian> {
+        points.iter().map(Jacobian::from).collect()
+    }
+
     #[must_use]
     pub fn x(&self) -> Option<&FieldElement> {
         self.as_coordinates().map(|(x, _)| x)
@@ -63,6 +85,22 @@ impl Affine {
         }
     }
 
+    /// Whether the point is on the curve and in the subgroup generated by
+    /// [`GENERATOR`](crate::GENERATOR).
+    ///
+    /// This multiplies by the group order and checks for the identity. The
+    /// curve has prime order, so with a cofactor of one every point on the
+    /// curve passes. The check is still useful for externally supplied points
+    /// that have not been decoded with a validating method.
+    #[must_use]
+    pub fn is_in_subgroup(&self) -> bool {
+        self.is_on_curve()
+            && Jacobian::from(self)
+                .mul_windowed(&ScalarFieldElement::MODULUS, 4)
+                .z
+                .is_zero()
+    }
+
     pub fn double_assign(&mut self) {
         *self = self.double();
     }
@@ -229,6 +267,23 @@ mod tests {
     use zkp_macros_decl::{field_element, u256h};
     use zkp_u256::U256;
 
+    #[test]
+    fn test_from_x_off_curve() {
+        // 0^3 + 0 + BETA is not a square
+        assert!(!BETA.is_quadratic_residue());
+        assert_eq!(Affine::from_x(&FieldElement::zero()), None);
+    }
+
+    #[test]
+    fn test_not_in_subgroup() {
+        // (0, 0) is off the curve. The curve has prime order, so there are no
+        // low-order points to test with.
+        let point = Affine::new(FieldElement::zero(), FieldElement::zero());
+        assert!(!point.is_in_subgroup());
+        assert!(Affine::Zero.is_in_subgroup());
+        assert!(crate::GENERATOR.is_in_subgroup());
+    }
+
     #[test]
     fn test_add() {
         let a = Affine::new(
@@ -277,6 +332,34 @@ mod tests {
     }
 
     proptest!(
+        #[test]
+        fn from_x(x: FieldElement) {
+            match Affine::from_x(&x) {
+                Some((a, b)) => {
+                    prop_assert!(a.is_on_curve());
+                    prop_assert!(b.is_on_curve());
+                    prop_assert_eq!(a.x(), Some(&x));
+                    prop_assert_eq!(-&a, b);
+                }
+                None => {
+                    prop_assert!(!(&x * &x * &x + &x + BETA).is_quadratic_residue());
+                }
+            }
+        }
+
+        #[test]
+        fn from_x_point(p: Affine) {
+            if let Some(x) = p.x() {
+                let (a, b) = Affine::from_x(x).unwrap();
+                prop_assert!(p == a || p == b);
+            }
+        }
+
+        #[test]
+        fn in_subgroup(p: Affine) {
+            prop_assert!(p.is_in_subgroup());
+        }
+
         #[test]
         fn add_commutative(a: Affine, b: Affine) {
             prop_assert_eq!(&a + &b, b + a)
@@ -0,0 +1,128 @@
+// False positive: attribute has a use
+#[allow(clippy::useless_attribute)]
+// False positive: Importing preludes is allowed
+#[allow(clippy::wildcard_imports)]
+use std::prelude::v1::*;
+
+use crate::{Affine, Jacobian};
+use zkp_u256::{Binary, U256};
+
+/// Precomputed multiples of a fixed base point.
+///
+/// The scalar is split in windows of `window` bits. For every window
+/// position `j` and every non-zero digit `d` the table holds
+/// `d * 2^(window * j) * base`, so a multiplication is one addition per
+/// non-zero window and no doublings.
+///
+/// The table holds `ceil(256 / window) * (2^window - 1)` affine points of
+/// about 64 bytes each. For `window = 4` that is 960 points (about 60 KiB)
+/// and at most 64 additions per multiplication. For `window = 8` it is 8160
+/// points (about 510 KiB) and at most 32 additions, but the table takes
+/// proportionally longer to compute. Larger windows only pay off for bases
+/// that are used very often.
+#[derive(Clone)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct FixedBaseTable {
+    window: usize,
+    table:  Vec<Affine>,
+}
+
+impl FixedBaseTable {
+    /// # Panics
+    ///
+    /// Panics if `window` is not between 1 and 8.
+    #[must_use]
+    pub fn new(base: &Affine, window: usize) -> Self {
+        assert!(
+            (1..=8).contains(&window),
+            "Window size must be between 1 and 8"
+        );
+        let num_windows = (256 + window - 1) / window;
+        let row_size = (1 << window) - 1;
+        let mut multiples = Vec::with_capacity(num_windows * row_size);
+        let mut row_base = Jacobian::from(base);
+        for _ in 0..num_windows {
+            let mut multiple = row_base.clone();
+            for _ in 0..row_size {
+                multiples.push(multiple.clone());
+                multiple += &row_base;
+            }
+            // `multiple` is now `2^window * row_base`
+            row_base = multiple;
+        }
+        Self {
+            window,
+            table: Jacobian::batch_to_affine(&multiples),
+        }
+    }
+
+    #[must_use]
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    #[must_use]
+    pub fn mul(&self, scalar: &U256) -> Affine {
+        let row_size = (1 << self.window) - 1;
+        let mut r = Jacobian::ZERO;
+        for (j, row) in self.table.chunks(row_size).enumerate() {
+            let digit = (0..self.window).rev().fold(0, |digit, bit| {
+                digit << 1 | usize::from(scalar.bit(j * self.window + bit))
+            });
+            if digit != 0 {
+                r += &row[digit - 1];
+            }
+        }
+        Affine::from(&r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ScalarFieldElement, GENERATOR};
+    use proptest::prelude::*;
+
+    fn generic_mul(base: &Affine, scalar: &U256) -> Affine {
+        Affine::from(&(Jacobian::from(base) * ScalarFieldElement::from(scalar.clone())))
+    }
+
+    #[test]
+    fn fixed_scalars() {
+        let base = GENERATOR.double();
+        let scalars = [
+            U256::ZERO,
+            U256::ONE,
+            U256::from(0x0f00_dbab_e0ca_feba_u64),
+            U256::MAX,
+        ];
+        for window in &[1, 4, 8] {
+            let table = FixedBaseTable::new(&base, *window);
+            for scalar in &scalars {
+                assert_eq!(table.mul(scalar), generic_mul(&base, scalar));
+            }
+        }
+    }
+
+    #[test]
+    fn zero_base() {
+        let table = FixedBaseTable::new(&Affine::ZERO, 4);
+        assert_eq!(table.mul(&U256::from(12345)), Affine::ZERO);
+    }
+
+    proptest!(
+        #[test]
+        fn table_mul(base: Affine, scalar: U256, window in 1_usize..=5) {
+            let table = FixedBaseTable::new(&base, window);
+            prop_assert_eq!(table.mul(&scalar), generic_mul(&base, &scalar));
+        }
+    );
+
+    #[test]
+    fn table_entries_on_curve() {
+        let table = FixedBaseTable::new(&GENERATOR, 2);
+        assert_eq!(table.table.len(), 128 * 3);
+        assert_eq!(table.table[0], GENERATOR);
+        assert!(table.table.iter().all(Affine::is_on_curve));
+    }
+}
@@ -7,8 +7,8 @@ use std::prelude::v1::*;
 use crate::{curve_operations, Affine, ScalarFieldElement};
 use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 use zkp_macros_decl::field_element;
-use zkp_primefield::{FieldElement, Inv, NegInline, One, SquareInline, Zero};
-use zkp_u256::{commutative_binop, noncommutative_binop, U256};
+use zkp_primefield::{invert_batch, FieldElement, Inv, NegInline, One, SquareInline, Zero};
+use zkp_u256::{commutative_binop, noncommutative_binop, Binary, U256};
 
 // See http://www.hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html
 
@@ -55,12 +55,165 @@ impl Jacobian {
         self.y.neg_assign();
     }
 
+    /// Negate the point if `choice` is set, without branching on `choice`.
+    pub fn conditional_negate(&mut self, choice: bool) {
+        let negated = -&self.y;
+        self.y = select_field(&self.y, &negated, choice);
+    }
+
+    /// Returns `b` if `choice` is set and `a` otherwise, without branching on
+    /// `choice`.
+    #[must_use]
+    pub fn conditional_select(a: &Self, b: &Self, choice: bool) -> Self {
+        Self {
+            x: select_field(&a.x, &b.x, choice),
+            y: select_field(&a.y, &b.y, choice),
+            z: select_field(&a.z, &b.z, choice),
+        }
+    }
+
     #[must_use]
     pub fn double(&self) -> Self {
         let mut r = self.clone();
         r.double_assign();
         r
     }
+
+    /// Scalar multiplication using fixed windows of `window` bits.
+    ///
+    /// A table of the multiples `0, P, 2P, ..., (2^window - 1) P` is computed
+    /// first. The scalar is then processed `window` bits at a time from the
+    /// most significant end, doing `window` doublings and at most one addition
+    /// per window. For `window = 4` this needs about a quarter of the
+    /// additions of double-and-add, at the cost of a 16 point table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is not between 1 and 8.
+    #[must_use]
+    pub fn mul_windowed(&self, scalar: &U256, window: usize) -> Self {
+        assert!(
+            (1..=8).contains(&window),
+            "Window size must be between 1 and 8"
+        );
+        let mut table = Vec::with_capacity(1 << window);
+        table.push(Self::ZERO);
+        for i in 1..(1 << window) {
+            let next: Self = &table[i - 1] + self;
+            table.push(next);
+        }
+
+        let num_windows = scalar
+            .most_significant_bit()
+            .map_or(0, |msb| msb / window + 1);
+        let mut r = Self::ZERO;
+        for i in (0..num_windows).rev() {
+            for _ in 0..window {
+                r.double_assign();
+            }
+            let digit = (0..window).rev().fold(0, |digit, bit| {
+                digit << 1 | usize::from(scalar.bit(i * window + bit))
+            });
+            if digit != 0 {
+                r += &table[digit];
+            }
+        }
+        r
+    }
+
+    /// Multi-scalar multiplication `sum_i scalars[i] * points[i]`.
+    ///
+    /// Uses Pippenger's bucket method. The scalars are split in windows of
+    /// about `log2(points.len())` bits. For each window, every point is added
+    /// to the bucket of its digit and the buckets are combined with a running
+    /// sum, so a window costs about one addition per point plus two per
+    /// bucket instead of a full scalar multiplication per point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `points` and `scalars` have different lengths.
+    #[must_use]
+    pub fn msm(points: &[Affine], scalars: &[U256]) -> Self {
+        assert_eq!(
+            points.len(),
+            scalars.len(),
+            "Number of points and scalars must be equal"
+        );
+        let window = match points.len().next_power_of_two().trailing_zeros() as usize {
+            0 => 1,
+            log_len => log_len.min(16),
+        };
+        let num_windows = scalars
+            .iter()
+            .filter_map(U256::most_significant_bit)
+            .max()
+            .map_or(0, |msb| msb / window + 1);
+
+        let mut result = Self::ZERO;
+        for i in (0..num_windows).rev() {
+            for _ in 0..window {
+                result.double_assign();
+            }
+            let mut buckets = vec![Self::ZERO; (1 << window) - 1];
+            for (point, scalar) in points.iter().zip(scalars) {
+                let digit = (0..window).rev().fold(0, |digit, bit| {
+                    digit << 1 | usize::from(scalar.bit(i * window + bit))
+                });
+                if digit != 0 {
+                    buckets[digit - 1] += point;
+                }
+            }
+            // The bucket of digit `d` gets added `d` times
+            let mut running = Self::ZERO;
+            for bucket in buckets.iter().rev() {
+                running += bucket;
+                result += &running;
+            }
+        }
+        result
+    }
+
+    /// Convert many points to affine coordinates with a single inversion.
+    ///
+    /// Points at infinity are skipped in the batch inversion and map to
+    /// `Affine::Zero`.
+    #[must_use]
+    pub fn batch_to_affine(points: &[Self]) -> Vec<Affine> {
+        let z_values: Vec<FieldElement> = points
+            .iter()
+            .filter(|point| !point.z.is_zero())
+            .map(|point| point.z.clone())
+            .collect();
+        let mut inverses = invert_batch(&z_values).into_iter();
+        points
+            .iter()
+            .map(|point| {
+                if point.z.is_zero() {
+                    return Affine::ZERO;
+                }
+                let zi = inverses.next().unwrap();
+                let zi2 = zi.square();
+                let zi3 = zi * &zi2;
+                Affine::Point {
+                    x: &point.x * zi2,
+                    y: &point.y * zi3,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Select `b` if `choice` is set and `a` otherwise by masking the limbs of
+/// the Montgomery representations.
+fn select_field(a: &FieldElement, b: &FieldElement, choice: bool) -> FieldElement {
+    let mask = 0_u64.wrapping_sub(u64::from(choice));
+    let a = a.as_montgomery().as_limbs();
+    let b = b.as_montgomery().as_limbs();
+    let mut limbs = [0_u64; 4];
+    for (limb, (a, b)) in limbs.iter_mut().zip(a.iter().zip(b.iter())) {
+        *limb = a ^ (mask & (a ^ b));
+    }
+    FieldElement::from_montgomery(U256::from_limbs(limbs))
 }
 
 impl PartialEq for Jacobian {
@@ -237,7 +390,7 @@ noncommutative_binop!(Jacobian, Sub, sub, SubAssign, sub_assign);
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ScalarFieldElement;
+    use crate::{ScalarFieldElement, GENERATOR};
     use proptest::prelude::*;
     use zkp_macros_decl::u256h;
 
@@ -271,6 +424,12 @@ mod tests {
         assert_eq!(a.double(), b);
     }
 
+    #[test]
+    #[should_panic(expected = "Number of points and scalars must be equal")]
+    fn test_msm_mismatched() {
+        let _ = Jacobian::msm(&[GENERATOR], &[]);
+    }
+
     #[test]
     fn test_mul() {
         let a = Jacobian::from(Affine::new(
@@ -298,6 +457,72 @@ mod tests {
             prop_assert_eq!(&p * &a + &p * &b, p * (a + b));
         }
 
+        #[test]
+        fn mul_windowed(p: Jacobian, scalar: ScalarFieldElement, window in 1_usize..=8) {
+            prop_assert_eq!(p.mul_windowed(&scalar.to_uint(), window), &p * &scalar);
+            prop_assert_eq!(p.mul_windowed(&U256::ZERO, window), Jacobian::ZERO);
+            prop_assert_eq!(p.mul_windowed(&U256::ONE, window), p);
+        }
+
+        #[test]
+        fn msm(terms in proptest::collection::vec(any::<(Affine, ScalarFieldElement)>(), 0..40)) {
+            let (points, scalars): (Vec<_>, Vec<_>) = terms.into_iter().unzip();
+            let expected = points
+                .iter()
+                .zip(&scalars)
+                .fold(Jacobian::ZERO, |sum, (point, scalar)| &sum + &(point * scalar));
+            let scalars = scalars.iter().map(ScalarFieldElement::to_uint).collect::<Vec<_>>();
+            prop_assert_eq!(Jacobian::msm(&points, &scalars), expected);
+        }
+
+        #[test]
+        fn msm_zero(points: Vec<Affine>) {
+            let scalars = vec![U256::ZERO; points.len()];
+            prop_assert_eq!(Jacobian::msm(&points, &scalars), Jacobian::ZERO);
+            let points = vec![Affine::Zero; points.len()];
+            let scalars = vec![U256::ONE; points.len()];
+            prop_assert_eq!(Jacobian::msm(&points, &scalars), Jacobian::ZERO);
+        }
+
+        #[test]
+        fn batch_to_affine(points: Vec<Jacobian>) {
+            let expected: Vec<Affine> = points.iter().map(Affine::from).collect();
+            prop_assert_eq!(Jacobian::batch_to_affine(&points), expected);
+        }
+
+        #[test]
+        fn batch_to_jacobian(points: Vec<Affine>) {
+            let jacobian = Affine::batch_to_jacobian(&points);
+            prop_assert_eq!(Jacobian::batch_to_affine(&jacobian), points);
+        }
+
+        #[test]
+        fn conditional_negate(p: Jacobian) {
+            for &choice in &[false, true] {
+                let mut masked = p.clone();
+                masked.conditional_negate(choice);
+                let expected = if choice { -&p } else { p.clone() };
+                prop_assert_eq!(masked.x, expected.x);
+                prop_assert_eq!(masked.y, expected.y);
+                prop_assert_eq!(masked.z, expected.z);
+            }
+        }
+
+        #[test]
+        fn conditional_select(a: Jacobian, b: Jacobian) {
+            for &negate in &[false, true] {
+                for &choice in &[false, true] {
+                    let mut b = b.clone();
+                    b.conditional_negate(negate);
+                    let masked = Jacobian::conditional_select(&a, &b, choice);
+                    let expected = if choice { b.clone() } else { a.clone() };
+                    prop_assert_eq!(&masked.x, &expected.x);
+                    prop_assert_eq!(&masked.y, &expected.y);
+                    prop_assert_eq!(&masked.z, &expected.z);
+                }
+            }
+        }
+
         #[test]
         fn affine_jacobian(j: Jacobian) {
             prop_assert_eq!(Jacobian::from(Affine::from(&j)), j);
@@ -43,7 +43,9 @@
 // TODO: Add `must_use` where relevant
 #![allow(clippy::must_use_candidate)]
 
+mod compressed;
 mod curve;
+mod fixed_base;
 mod jacobian;
 #[cfg(any(test, feature = "proptest"))]
 mod proptest;
@@ -53,7 +55,9 @@ mod wnaf;
 #[cfg(not(feature = "std"))]
 extern crate no_std_compat as std;
 
+pub use compressed::Error as CompressionError;
 pub use curve::Affine;
+pub use fixed_base::FixedBaseTable;
 pub use jacobian::Jacobian;
 pub use scalar_field::{Element as ScalarFieldElement, Order};
 pub use wnaf::{base_mul, double_base_mul, double_mul, mul, window_table_affine};
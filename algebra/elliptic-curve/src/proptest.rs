@@ -1,6 +1,6 @@
-use crate::{Affine, Jacobian, BETA};
+use crate::{Affine, Jacobian};
 use proptest::prelude::*;
-use zkp_primefield::{FieldElement, Pow, SquareInline, SquareRoot, Zero};
+use zkp_primefield::{FieldElement, SquareInline, Zero};
 
 impl Arbitrary for Affine {
     type Parameters = ();
@@ -10,9 +10,7 @@ impl Arbitrary for Affine {
         prop_oneof![
             Just(Affine::Zero),
             <(bool, FieldElement)>::arbitrary().prop_filter_map("x not on curve", |(sign, x)| {
-                (x.pow(3_usize) + &x + BETA)
-                    .square_root()
-                    .map(|y| Affine::new(x, if sign { y } else { -y }))
+                Affine::from_x(&x).map(|(a, b)| if sign { a } else { b })
             })
         ]
         .boxed()
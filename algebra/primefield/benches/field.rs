@@ -1,7 +1,10 @@
 #![warn(clippy::all)]
 use criterion::{black_box, Criterion};
 use rand::prelude::*;
-use zkp_primefield::{FieldElement, Inv, SquareInline, SquareRoot};
+use zkp_primefield::{
+    invert_batch, AdditionChain, FieldElement, Inv, Pow, SquareInline, SquareRoot,
+};
+use zkp_u256::U256;
 
 fn field_add(crit: &mut Criterion) {
     crit.bench_function("Field add", move |bench| {
@@ -54,6 +57,26 @@ fn field_inv(crit: &mut Criterion) {
     });
 }
 
+// Fermat inversion `a^(p - 2)`, for comparison with the GCD based `inv`.
+fn field_inv_fermat(crit: &mut Criterion) {
+    crit.bench_function("Field inv Fermat", move |bench| {
+        let a: &FieldElement = &random();
+        let exponent = FieldElement::order() - U256::ONE;
+        bench.iter(|| {
+            black_box(black_box(a).pow(&exponent));
+        })
+    });
+}
+
+fn field_invert_batch_1(crit: &mut Criterion) {
+    crit.bench_function("Field invert_batch of one", move |bench| {
+        let a: [FieldElement; 1] = [random()];
+        bench.iter(|| {
+            black_box(invert_batch(black_box(&a)));
+        })
+    });
+}
+
 fn field_sqrt(crit: &mut Criterion) {
     crit.bench_function("Field square root", move |bench| {
         let a: &FieldElement = &random();
@@ -64,6 +87,30 @@ fn field_sqrt(crit: &mut Criterion) {
     });
 }
 
+fn field_pow(crit: &mut Criterion) {
+    crit.bench_function("Field pow 10k fixed exponent", move |bench| {
+        let bases: Vec<FieldElement> = (0..10_000).map(|_| random()).collect();
+        let exponent = (1_usize << 20) - 1;
+        bench.iter(|| {
+            for base in &bases {
+                black_box(base.pow(black_box(exponent)));
+            }
+        })
+    });
+}
+
+fn field_pow_precomputed(crit: &mut Criterion) {
+    crit.bench_function("Field pow_precomputed 10k fixed exponent", move |bench| {
+        let bases: Vec<FieldElement> = (0..10_000).map(|_| random()).collect();
+        let chain = AdditionChain::for_exponent(&((1_usize << 20) - 1));
+        bench.iter(|| {
+            for base in &bases {
+                black_box(base.pow_precomputed(black_box(&chain)));
+            }
+        })
+    });
+}
+
 pub fn group(crit: &mut Criterion) {
     field_add(crit);
     field_sub(crit);
@@ -71,5 +118,9 @@ pub fn group(crit: &mut Criterion) {
     field_mul(crit);
     field_square(crit);
     field_inv(crit);
+    field_inv_fermat(crit);
+    field_invert_batch_1(crit);
     field_sqrt(crit);
+    field_pow(crit);
+    field_pow_precomputed(crit);
 }
@@ -0,0 +1,146 @@
+// False positive: attribute has a use
+#[allow(clippy::useless_attribute)]
+// False positive: Importing preludes is allowed
+#[allow(clippy::wildcard_imports)]
+use std::prelude::v1::*;
+
+use zkp_u256::Binary;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub(crate) enum Step {
+    Square,
+    /// Multiply by the odd power `2 * i + 1` of the base.
+    Multiply(usize),
+}
+
+/// Precomputed sequence of operations for raising to a fixed exponent.
+///
+/// This is a sliding window exponentiation where the window positions are
+/// computed once. It pays off when the same exponent is used many times,
+/// see [`PrimeField::pow_precomputed`](crate::PrimeField::pow_precomputed).
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct AdditionChain {
+    /// Number of odd powers of the base that are precomputed.
+    pub(crate) table_size: usize,
+    /// The odd power to start with, or `None` if the exponent is zero.
+    pub(crate) start:      Option<usize>,
+    pub(crate) steps:      Vec<Step>,
+}
+
+impl AdditionChain {
+    pub fn for_exponent<Exponent: Binary>(exponent: &Exponent) -> Self {
+        let num_bits = match exponent.most_significant_bit() {
+            None => {
+                return Self {
+                    table_size: 0,
+                    start:      None,
+                    steps:      Vec::new(),
+                }
+            }
+            Some(msb) => msb + 1,
+        };
+        // Larger windows need a larger table but fewer multiplications.
+        let window = match num_bits {
+            0..=23 => 1,
+            24..=79 => 3,
+            80..=239 => 4,
+            _ => 5,
+        };
+
+        let mut start = None;
+        let mut steps = Vec::with_capacity(num_bits + num_bits / window);
+        let mut table_size = 1;
+        let mut i = num_bits;
+        while i > 0 {
+            if !exponent.bit(i - 1) {
+                if start.is_some() {
+                    steps.push(Step::Square);
+                }
+                i -= 1;
+                continue;
+            }
+            // Find the longest window starting at bit i - 1 that ends in a one
+            let mut low = i.saturating_sub(window);
+            while !exponent.bit(low) {
+                low += 1;
+            }
+            let mut value = 0;
+            for bit in (low..i).rev() {
+                value = value << 1 | usize::from(exponent.bit(bit));
+            }
+            let index = value >> 1;
+            table_size = std::cmp::max(table_size, index + 1);
+            if start.is_some() {
+                steps.extend(std::iter::repeat(Step::Square).take(i - low));
+                steps.push(Step::Multiply(index));
+            } else {
+                start = Some(index);
+            }
+            i = low;
+        }
+        Self {
+            table_size,
+            start,
+            steps,
+        }
+    }
+
+    /// The number of multiplications and squarings needed, including those
+    /// to build the table of odd powers.
+    pub fn len(&self) -> usize {
+        let table = if self.table_size > 1 {
+            self.table_size
+        } else {
+            0
+        };
+        table + self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldElement, Pow};
+    use proptest::prelude::*;
+    use zkp_u256::U256;
+
+    #[test]
+    fn zero_exponent() {
+        let chain = AdditionChain::for_exponent(&0_usize);
+        assert!(chain.is_empty());
+        assert_eq!(
+            FieldElement::from(5).pow_precomputed(&chain),
+            FieldElement::from(1)
+        );
+    }
+
+    #[test]
+    fn fixed_exponents() {
+        let base = FieldElement::from(0x0f00_dbab_e0ca_feba_u64);
+        for exponent in &[1_usize, 2, 3, 7, 8, 255, 1023, 65537, 0xdead_beef] {
+            let chain = AdditionChain::for_exponent(exponent);
+            assert_eq!(base.pow_precomputed(&chain), base.pow(*exponent));
+        }
+    }
+
+    proptest!(
+        #[test]
+        fn pow_precomputed_matches_pow(base: FieldElement, exponent: U256) {
+            let chain = AdditionChain::for_exponent(&exponent);
+            prop_assert_eq!(base.pow_precomputed(&chain), base.pow(&exponent));
+        }
+
+        #[test]
+        fn pow_precomputed_small(base: FieldElement, exponent: u32) {
+            let exponent = exponent as usize;
+            let chain = AdditionChain::for_exponent(&exponent);
+            prop_assert_eq!(base.pow_precomputed(&chain), base.pow(exponent));
+        }
+    );
+}
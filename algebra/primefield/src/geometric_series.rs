@@ -90,6 +90,18 @@ where
     }
 }
 
+/// The first `count` powers of `base`, `[1, base, base^2, ...]`.
+///
+/// Each power is computed from the previous one with a single
+/// multiplication.
+pub fn powers<Field>(base: &Field, count: usize) -> Vec<Field>
+where
+    Field: FieldLike,
+    for<'a> &'a Field: RefFieldLike<Field>,
+{
+    geometric_series(&Field::one(), base).take(count).collect()
+}
+
 pub fn root_series<Field>(order: usize) -> GeometricIter<Field>
 where
     Field: FieldLike,
@@ -102,7 +114,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::FieldElement;
+    use crate::{FieldElement, One};
+    use proptest::prelude::*;
     use zkp_macros_decl::field_element;
     use zkp_u256::U256;
 
@@ -119,4 +132,27 @@ mod tests {
         assert_eq!(domain.next(), Some(&base * &step * &step));
         assert_eq!(domain.next(), Some(&base * &step * &step * &step));
     }
+
+    #[test]
+    fn powers_small() {
+        let base = FieldElement::from(7);
+        assert_eq!(powers(&base, 0), vec![]);
+        assert_eq!(powers(&base, 1), vec![FieldElement::one()]);
+        assert_eq!(powers(&base, 3), vec![
+            FieldElement::one(),
+            FieldElement::from(7),
+            FieldElement::from(49)
+        ]);
+    }
+
+    proptest!(
+        #[test]
+        fn powers_match_pow(base: FieldElement, count in 0_usize..100) {
+            let result = powers(&base, count);
+            prop_assert_eq!(result.len(), count);
+            for (i, power) in result.iter().enumerate() {
+                prop_assert_eq!(power, &base.pow(i));
+            }
+        }
+    );
 }
@@ -11,6 +11,16 @@ use std::prelude::v1::*;
 
 use crate::{FieldLike, Inv, RefFieldLike};
 
+/// Invert all of `source` into `destination` using a single inversion.
+///
+/// This is Montgomery's trick: it costs one [`Inv::inv`] and three
+/// multiplications per element. The single inversion is the field's own, for
+/// `PrimeField` a Lehmer extended GCD, so a batch of one costs one inversion
+/// plus a few multiplications.
+///
+/// # Panics
+///
+/// Panics if the lengths differ or any element of `source` is zero.
 pub fn invert_batch_src_dst<Field>(source: &[Field], destination: &mut [Field])
 where
     Field: FieldLike + From<usize> + std::fmt::Debug,
@@ -30,6 +40,7 @@ where
     // OPT: We can avoid the last multiplication
 }
 
+/// Invert all elements of `source`, see [`invert_batch_src_dst`].
 pub fn invert_batch<Field>(source: &[Field]) -> Vec<Field>
 where
     Field: FieldLike + From<usize> + std::fmt::Debug,
@@ -43,10 +54,24 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{FieldElement, Zero};
+    use crate::{FieldElement, One, Zero};
     use proptest::prelude::*;
 
+    #[test]
+    #[should_panic(expected = "Division by zero in batch invert")]
+    fn test_batch_inv_zero() {
+        let _ = invert_batch(&[FieldElement::one(), FieldElement::zero()]);
+    }
+
     proptest!(
+        #[test]
+        fn test_batch_inv_one(x: FieldElement) {
+            prop_assume!(!x.is_zero());
+            let inverse = x.inv().unwrap();
+            prop_assert!((&x * &inverse).is_one());
+            prop_assert_eq!(invert_batch(&[x]), vec![inverse]);
+        }
+
         #[test]
         fn test_batch_inv(x: Vec<FieldElement>) {
             prop_assume!(!x.iter().any(FieldElement::is_zero));
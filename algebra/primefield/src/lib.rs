@@ -45,6 +45,7 @@
 // TODO: Provide two versions of hot functions `_inlined` and plain.
 #![allow(clippy::inline_always)]
 
+mod addition_chain;
 mod convert;
 pub mod fft;
 pub mod geometric_series;
@@ -64,12 +65,13 @@ mod traits;
 mod uint;
 
 // Generic field implementation
-pub use prime_field::{Parameters, PrimeField};
+pub use prime_field::{Parameters, ParseError, PrimeField};
 pub use zkp_u256::MontgomeryParameters;
 
 // The smallest 252-bit Proth field
 pub use proth_field::FieldElement;
 
+pub use addition_chain::AdditionChain;
 pub use invert_batch::{invert_batch, invert_batch_src_dst};
 
 // Re-exports dependencies that are part of the public interface
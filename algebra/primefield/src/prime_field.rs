@@ -4,11 +4,15 @@
 #[allow(clippy::wildcard_imports)]
 use std::{fmt, prelude::v1::*};
 
-use crate::{Root, SquareRoot, UInt as FieldUInt};
+use crate::{
+    addition_chain::{AdditionChain, Step},
+    Root, SquareRoot, UInt as FieldUInt,
+};
 use std::{
     hash::{Hash, Hasher},
     marker::PhantomData,
     ops::Shr,
+    str::FromStr,
 };
 use zkp_u256::{
     AddInline, Binary, DivRem, Inv, Montgomery as _, MontgomeryParameters, MulInline, NegInline,
@@ -123,12 +127,6 @@ impl<P: Parameters> PrimeField<P> {
         }
     }
 
-    // TODO: from_radix_str
-    // #[cfg(feature = "std")]
-    // pub fn from_hex_str(s: &str) -> Self {
-    // Self::from(UInt::from_hex_str(s))
-    // }
-
     /// Convert to `UInt`.
     #[inline(always)] // Simple wrapper for `from_montgomery`
     pub fn to_uint(&self) -> P::UInt {
@@ -164,6 +162,68 @@ impl<P: Parameters> PrimeField<P> {
         // TODO: Optimize
         self.clone() + self + self
     }
+
+    /// Raise to the exponent of a precomputed [`AdditionChain`].
+    ///
+    /// This gives the same result as `pow` but is faster when the same
+    /// exponent is used many times.
+    pub fn pow_precomputed(&self, chain: &AdditionChain) -> Self {
+        let start = match chain.start {
+            None => return Self::one(),
+            Some(start) => start,
+        };
+        // Table of odd powers self^1, self^3, self^5, ...
+        let mut table = Vec::with_capacity(chain.table_size);
+        table.push(self.clone());
+        if chain.table_size > 1 {
+            let square = self.square();
+            for i in 1..chain.table_size {
+                let next = &table[i - 1] * &square;
+                table.push(next);
+            }
+        }
+        let mut result = table[start].clone();
+        for step in &chain.steps {
+            match step {
+                Step::Square => result.square_assign(),
+                Step::Multiply(index) => result *= &table[*index],
+            }
+        }
+        result
+    }
+
+    /// Raise to each of the `exponents`.
+    ///
+    /// The repeated squares of `self` are computed once and shared between
+    /// the exponents, so every exponent only adds the multiplications for its
+    /// set bits.
+    pub fn pow_vec<Exponent: Binary>(&self, exponents: &[Exponent]) -> Vec<Self> {
+        let num_bits = exponents
+            .iter()
+            .filter_map(Binary::most_significant_bit)
+            .max()
+            .map_or(0, |msb| msb + 1);
+        let mut squares = Vec::with_capacity(num_bits);
+        if num_bits > 0 {
+            squares.push(self.clone());
+            for i in 1..num_bits {
+                let next = squares[i - 1].square();
+                squares.push(next);
+            }
+        }
+        exponents
+            .iter()
+            .map(|exponent| {
+                let mut result = Self::one();
+                for (i, square) in squares.iter().enumerate() {
+                    if exponent.bit(i) {
+                        result *= square;
+                    }
+                }
+                result
+            })
+            .collect()
+    }
 }
 
 impl<P: Parameters> Clone for PrimeField<P> {
@@ -430,12 +490,231 @@ where
     }
 }
 
+impl<U, P> PrimeField<P>
+where
+    U: FieldUInt + Binary + Shr<usize, Output = U>,
+    P: Parameters<UInt = U>,
+{
+    /// The Legendre symbol: `1` for non-zero squares, `-1` for non-squares
+    /// and `0` for zero.
+    ///
+    /// Zero is a quadratic residue, so this is non-negative exactly when
+    /// [`SquareRoot::square_root`] succeeds.
+    pub fn legendre(&self) -> i8 {
+        if self.is_zero() {
+            0
+        } else if self.is_quadratic_residue() {
+            1
+        } else {
+            -1
+        }
+    }
+
+    /// Square roots of many elements, with a single batch inversion.
+    ///
+    /// Gives the same results as calling [`SquareRoot::square_root`] on each
+    /// element. Only the inversion is batched: the Legendre symbol and the
+    /// Tonelli-Shanks exponentiations are still computed per element. The
+    /// addition chains for the fixed exponents and the powers of the
+    /// non-residue are computed once for the whole batch.
+    pub fn batch_square_root(xs: &[Self]) -> Vec<Option<Self>>
+    where
+        Self: crate::FieldLike + From<usize> + fmt::Debug,
+        for<'a> &'a Self: crate::RefFieldLike<Self>,
+    {
+        let minus_one = -Self::one();
+        let legendre = AdditionChain::for_exponent(&(Self::MODULUS >> 1_usize));
+        let residues: Vec<bool> = xs
+            .iter()
+            .map(|x| x.is_zero() || x.pow_precomputed(&legendre) != minus_one)
+            .collect();
+        let nonzero_residues: Vec<Self> = xs
+            .iter()
+            .zip(residues.iter())
+            .filter(|(x, &residue)| residue && !x.is_zero())
+            .map(|(x, _)| x.clone())
+            .collect();
+        let mut inverses = crate::invert_batch(&nonzero_residues).into_iter();
+
+        // See `square_root` for the algorithm
+        let trailing_zeros = Self::order().trailing_zeros();
+        let signifcant = Self::order() >> trailing_zeros;
+        let mut c_powers = Vec::with_capacity(trailing_zeros);
+        c_powers.push(<&Self as Pow<&U>>::pow(&Self::generator(), &signifcant));
+        for i in 1..trailing_zeros {
+            let next = c_powers[i - 1].square();
+            c_powers.push(next);
+        }
+        let start = AdditionChain::for_exponent(&((signifcant + P::UInt::one()) >> 1));
+
+        xs.iter()
+            .zip(residues)
+            .map(|(x, residue)| {
+                if !residue {
+                    return None;
+                }
+                if x.is_zero() {
+                    return Some(Self::zero());
+                }
+                let inverse = inverses.next().unwrap();
+                let mut root = x.pow_precomputed(&start);
+                for i in 1..trailing_zeros {
+                    let exponent = P::UInt::one() << (trailing_zeros - i - 1);
+                    if <&Self as Pow<&U>>::pow(&(root.square() * &inverse), &exponent) == minus_one
+                    {
+                        root *= &c_powers[i - 1];
+                    }
+                }
+                Some(root)
+            })
+            .collect()
+    }
+}
+
+/// Error returned when parsing a [`PrimeField`] from a string fails.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum ParseError {
+    Empty,
+    InvalidRadix(u32),
+    InvalidDigit { position: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParseError::Empty => write!(f, "Cannot parse field element from empty string"),
+            ParseError::InvalidRadix(radix) => {
+                write!(f, "Radix {} is not in the range 2 to 36", radix)
+            }
+            ParseError::InvalidDigit { position } => {
+                write!(f, "Invalid digit at position {}", position)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+impl<U, P> PrimeField<P>
+where
+    U: FieldUInt + From<u32>,
+    P: Parameters<UInt = U>,
+{
+    /// Parse digits in the given radix, reducing the value modulo the prime.
+    ///
+    /// Accepts radices from 2 to 36, with digits as in
+    /// [`u64::from_str_radix`]. Signs and prefixes are not accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `s` is empty, contains a character that is not a
+    /// digit in `radix`, or if `radix` is out of range.
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseError> {
+        if !(2..=36).contains(&radix) {
+            return Err(ParseError::InvalidRadix(radix));
+        }
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let radix_element = Self::from(radix);
+        let mut result = Self::zero();
+        for (position, c) in s.char_indices() {
+            let digit = c
+                .to_digit(radix)
+                .ok_or(ParseError::InvalidDigit { position })?;
+            result *= &radix_element;
+            result += Self::from(digit);
+        }
+        Ok(result)
+    }
+}
+
+/// Parse a `0x` prefixed hexadecimal or otherwise decimal number, reduced
+/// modulo the prime.
+impl<U, P> FromStr for PrimeField<P>
+where
+    U: FieldUInt + From<u32>,
+    P: Parameters<UInt = U>,
+{
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        if let Some(hex) = s.strip_prefix("0x") {
+            Self::from_str_radix(hex, 16).map_err(|error| {
+                match error {
+                    ParseError::InvalidDigit { position } => {
+                        ParseError::InvalidDigit {
+                            position: position + 2,
+                        }
+                    }
+                    error => error,
+                }
+            })
+        } else {
+            Self::from_str_radix(s, 10)
+        }
+    }
+}
+
 impl<P: Parameters> Default for PrimeField<P> {
     fn default() -> Self {
         Self::zero()
     }
 }
 
+impl<P: Parameters<UInt = U256>> PrimeField<P> {
+    /// Constant time equality comparison.
+    ///
+    /// Unlike `==`, this compares all limbs without an early exit, so the
+    /// running time does not depend on the values.
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        let difference = self
+            .as_montgomery()
+            .as_limbs()
+            .iter()
+            .zip(other.as_montgomery().as_limbs())
+            .fold(0, |accumulator, (a, b)| accumulator | (a ^ b));
+        difference == 0
+    }
+
+    /// Serialize elements as concatenated 32-byte big-endian integers.
+    ///
+    /// Gives the same bytes as `x.to_uint().to_bytes_be()` for each element.
+    /// Every element is still converted on its own, so this is a convenience
+    /// rather than a faster path.
+    pub fn to_bytes_be_batch(xs: &[Self]) -> Vec<u8> {
+        let mut result = Vec::with_capacity(32 * xs.len());
+        for x in xs {
+            result.extend_from_slice(&x.to_uint().to_bytes_be());
+        }
+        result
+    }
+
+    /// Deserialize elements written by [`Self::to_bytes_be_batch`].
+    ///
+    /// Returns `None` if the length is not a multiple of 32 bytes or if any
+    /// of the values is not smaller than the modulus.
+    pub fn from_bytes_be_batch(bytes: &[u8]) -> Option<Vec<Self>> {
+        let chunks = bytes.chunks_exact(32);
+        if !chunks.remainder().is_empty() {
+            return None;
+        }
+        let mut result = Vec::with_capacity(chunks.len());
+        let mut buffer = [0_u8; 32];
+        for chunk in chunks {
+            buffer.copy_from_slice(chunk);
+            let uint = U256::from_bytes_be(&buffer);
+            if uint >= Self::modulus() {
+                return None;
+            }
+            result.push(Self::from_uint(&uint));
+        }
+        Some(result)
+    }
+}
+
 // TODO: Find a way to create generic implementations of these
 impl<P: Parameters<UInt = U256>> From<PrimeField<P>> for U256 {
     #[inline(always)]
@@ -473,6 +752,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_str() {
+        assert_eq!("0".parse(), Ok(FieldElement::zero()));
+        assert_eq!(
+            "1325123410".parse(),
+            Ok(FieldElement::from(1_325_123_410_u64))
+        );
+        assert_eq!(
+            "0x0548c135e26faa9c977fb2eda057b54b2e0baa9a77a0be7c80278f4f03462d4c".parse(),
+            Ok(field_element!(
+                "0548c135e26faa9c977fb2eda057b54b2e0baa9a77a0be7c80278f4f03462d4c"
+            ))
+        );
+        assert_eq!("0xff".parse(), Ok(FieldElement::from(255)));
+        assert_eq!(
+            FieldElement::from_str_radix("-101", 2),
+            Err(ParseError::InvalidDigit { position: 0 })
+        );
+        assert_eq!(
+            FieldElement::from_str_radix("z", 36),
+            Ok(FieldElement::from(35))
+        );
+    }
+
+    #[test]
+    fn test_from_str_reduces() {
+        let modulus = FieldElement::modulus();
+        assert_eq!(
+            modulus.to_decimal_string().parse(),
+            Ok(FieldElement::zero())
+        );
+        assert_eq!(
+            (modulus + U256::ONE).to_decimal_string().parse(),
+            Ok(FieldElement::one())
+        );
+        assert_eq!(
+            "0x10000000000000000000000000000000000000000000000000000000000000000".parse(),
+            Ok(FieldElement::from(U256::MAX) + FieldElement::one())
+        );
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        assert_eq!("".parse::<FieldElement>(), Err(ParseError::Empty));
+        assert_eq!("0x".parse::<FieldElement>(), Err(ParseError::Empty));
+        assert_eq!(
+            "12a".parse::<FieldElement>(),
+            Err(ParseError::InvalidDigit { position: 2 })
+        );
+        assert_eq!(
+            "0x12g".parse::<FieldElement>(),
+            Err(ParseError::InvalidDigit { position: 4 })
+        );
+        assert_eq!(
+            " 1".parse::<FieldElement>(),
+            Err(ParseError::InvalidDigit { position: 0 })
+        );
+        assert_eq!(
+            FieldElement::from_str_radix("1", 37),
+            Err(ParseError::InvalidRadix(37))
+        );
+    }
+
     #[test]
     fn minus_zero_equals_zero() {
         assert!(FieldElement::zero().is_zero());
@@ -536,6 +878,33 @@ mod tests {
             prop_assert_eq!(FieldElement::from(n).to_i128().unwrap(), n);
         }
 
+        #[test]
+        fn ct_eq_matches_eq(a: FieldElement, b: FieldElement) {
+            prop_assert!(a.ct_eq(&a.clone()));
+            prop_assert_eq!(a.ct_eq(&b), a == b);
+        }
+
+        #[test]
+        fn from_str_roundtrip(x: FieldElement) {
+            let uint = x.to_uint();
+            prop_assert_eq!(uint.to_decimal_string().parse(), Ok(x.clone()));
+            prop_assert_eq!(uint.to_hex_string().parse(), Ok(x));
+        }
+
+        #[test]
+        fn bytes_be_batch(xs: Vec<FieldElement>) {
+            let bytes = FieldElement::to_bytes_be_batch(&xs);
+            let expected: Vec<u8> = xs
+                .iter()
+                .flat_map(|x| x.to_uint().to_bytes_be().to_vec())
+                .collect();
+            prop_assert_eq!(&bytes, &expected);
+            prop_assert_eq!(FieldElement::from_bytes_be_batch(&bytes), Some(xs));
+            if !bytes.is_empty() {
+                prop_assert_eq!(FieldElement::from_bytes_be_batch(&bytes[1..]), None);
+            }
+        }
+
         #[test]
         fn add_identity(a: FieldElement) {
             prop_assert_eq!(&a + FieldElement::zero(), a);
@@ -622,8 +991,52 @@ mod tests {
             let r = s.square_root().unwrap();
             prop_assert!(r == a || r == -a);
         }
+
+        #[test]
+        fn pow_vec(a: FieldElement, exponents: Vec<U256>) {
+            let mut exponents = exponents;
+            exponents.push(U256::ZERO);
+            exponents.push(U256::from(1_u64 << 40));
+            let powers = a.pow_vec(&exponents);
+            prop_assert_eq!(powers.len(), exponents.len());
+            for (power, exponent) in powers.iter().zip(exponents.iter()) {
+                prop_assert_eq!(power, &a.pow(exponent));
+            }
+        }
+
+        #[test]
+        fn legendre(a: FieldElement) {
+            for x in &[a.clone(), a.square(), FieldElement::zero()] {
+                let legendre = x.legendre();
+                prop_assert_eq!(legendre == 0, x.is_zero());
+                prop_assert_eq!(legendre >= 0, x.is_quadratic_residue());
+                prop_assert_eq!(legendre >= 0, x.square_root().is_some());
+            }
+            prop_assert!(a.square().legendre() >= 0);
+            prop_assert_eq!(FieldElement::generator().legendre(), -1);
+        }
+
+        #[test]
+        fn batch_square_root(xs: Vec<FieldElement>) {
+            let mut xs = xs;
+            xs.push(FieldElement::zero());
+            let roots = FieldElement::batch_square_root(&xs);
+            prop_assert_eq!(roots.len(), xs.len());
+            for (x, root) in xs.iter().zip(roots.iter()) {
+                match root {
+                    Some(y) => prop_assert_eq!(&y.square(), x),
+                    None => prop_assert!(!x.is_quadratic_residue()),
+                }
+                prop_assert_eq!(root, &x.square_root());
+            }
+        }
     );
 
+    #[test]
+    fn zero_has_no_inverse() {
+        assert_eq!(FieldElement::zero().inv(), None);
+    }
+
     #[test]
     fn zeroth_root_of_unity() {
         assert_eq!(FieldElement::root(0).unwrap(), FieldElement::one());
@@ -1,5 +1,29 @@
 #[cfg(feature = "std")]
-use std::fmt;
+use std::{fmt, str::FromStr};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input does not have exactly 64 hexadecimal digits.
+    InvalidLength(usize),
+    InvalidCharacter {
+        c:     char,
+        index: usize,
+    },
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParseError::InvalidLength(length) => {
+                write!(f, "Expected 64 hex digits, found {}", length)
+            }
+            ParseError::InvalidCharacter { c, index } => {
+                write!(f, "Invalid hex character {:?} at position {}", c, index)
+            }
+        }
+    }
+}
 
 #[derive(Clone, Default, PartialEq, Eq)]
 pub struct Hash([u8; 32]);
@@ -14,6 +38,69 @@ impl Hash {
     pub fn as_bytes(&self) -> &[u8] {
         &self.0
     }
+
+    /// Constant time equality comparison.
+    ///
+    /// Unlike `==`, this compares all bytes without an early exit.
+    #[must_use]
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        let difference = self
+            .0
+            .iter()
+            .zip(other.0.iter())
+            .fold(0, |accumulator, (a, b)| accumulator | (a ^ b));
+        difference == 0
+    }
+}
+
+#[cfg(feature = "std")]
+impl Hash {
+    /// Parse 64 hexadecimal digits, optionally prefixed by `0x`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input has the wrong length or contains a
+    /// character that is not a hexadecimal digit.
+    pub fn from_hex(s: &str) -> Result<Self, ParseError> {
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        let offset = s.len() - digits.len();
+        if digits.len() != 64 {
+            return Err(ParseError::InvalidLength(digits.len()));
+        }
+        let mut bytes = [0_u8; 32];
+        hex::decode_to_slice(digits, &mut bytes).map_err(|error| {
+            match error {
+                hex::FromHexError::InvalidHexCharacter { c, index } => {
+                    ParseError::InvalidCharacter {
+                        c,
+                        index: offset + index,
+                    }
+                }
+                _ => ParseError::InvalidLength(digits.len()),
+            }
+        })?;
+        Ok(Self(bytes))
+    }
+
+    pub fn to_hex(&self) -> String {
+        "0x".to_owned() + &hex::encode(self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for Hash {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
 }
 
 #[cfg(feature = "std")]
@@ -22,3 +109,65 @@ impl fmt::Debug for Hash {
         write!(f, "Hash(0x{:})", hex::encode(self.0))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ct_eq_matches_eq() {
+        let a = Hash::new([0x5a; 32]);
+        let mut bytes = [0x5a; 32];
+        assert!(a.ct_eq(&Hash::new(bytes)));
+        for i in 0..32 {
+            bytes[i] ^= 1;
+            let b = Hash::new(bytes);
+            assert_eq!(a.ct_eq(&b), a == b);
+            assert!(!a.ct_eq(&b));
+            bytes[i] ^= 1;
+        }
+    }
+
+    #[test]
+    fn hex_roundtrip() {
+        let hex = "fd112f44bc944f33e2567f86eea202350913b11c000000000000000000000000";
+        let hash = Hash::from_hex(hex).unwrap();
+        assert_eq!(hash.as_bytes()[..2], [0xfd, 0x11]);
+        assert_eq!(hash.to_hex(), format!("0x{}", hex));
+        assert_eq!(hash.to_string().parse::<Hash>(), Ok(hash));
+
+        let zero = Hash::default();
+        assert_eq!(zero.to_hex(), format!("0x{}", "0".repeat(64)));
+        assert_eq!(zero.to_string().parse::<Hash>(), Ok(zero));
+    }
+
+    #[test]
+    fn hex_invalid() {
+        assert_eq!(Hash::from_hex("0x123"), Err(ParseError::InvalidLength(3)));
+        assert_eq!(
+            Hash::from_hex(&"0".repeat(66)),
+            Err(ParseError::InvalidLength(66))
+        );
+        assert_eq!(
+            Hash::from_hex(&format!("{}g", "0".repeat(63))),
+            Err(ParseError::InvalidCharacter {
+                c:     'g',
+                index: 63,
+            })
+        );
+        assert_eq!(
+            Hash::from_hex(&format!("0x{}g", "0".repeat(63))),
+            Err(ParseError::InvalidCharacter {
+                c:     'g',
+                index: 65,
+            })
+        );
+        assert_eq!(
+            Hash::from_hex(&format!("0x0x{}", "0".repeat(62))),
+            Err(ParseError::InvalidCharacter {
+                c:     'x',
+                index: 3,
+            })
+        );
+    }
+}
@@ -4,12 +4,20 @@
 #[allow(clippy::wildcard_imports)]
 use std::prelude::v1::*;
 
-use crate::{hash::Hash, masked_keccak::MaskedKeccak};
+use crate::{hash::Hash, hasher::Hasher, masked_keccak::MaskedKeccak};
 use zkp_primefield::FieldElement;
 use zkp_u256::U256;
 
 pub trait Hashable {
     fn hash(&self) -> Hash;
+
+    /// Like [`Hashable::hash`], but using `H` wherever values are combined.
+    ///
+    /// Values that are passed as-is, like [`U256`], hash the same for every
+    /// `H`.
+    fn hash_with<H: Hasher>(&self) -> Hash {
+        self.hash()
+    }
 }
 
 impl Hashable for Hash {
@@ -21,6 +29,7 @@ impl Hashable for Hash {
     }
 }
 
+/// The 32 big-endian bytes of the value, without hashing.
 impl Hashable for U256 {
     fn hash(&self) -> Hash {
         // U256 values are passed as-is
@@ -29,6 +38,11 @@ impl Hashable for U256 {
     }
 }
 
+/// The big-endian bytes of the Montgomery form, without hashing.
+///
+/// This is the encoding the Solidity verifier reads from proofs and hashes
+/// into its Merkle trees. It is not the canonical encoding, so it differs from
+/// `element.to_uint().hash()`.
 impl Hashable for FieldElement {
     fn hash(&self) -> Hash {
         // We hash as U256 in Montgomery form (which is identity-hashed)
@@ -40,18 +54,26 @@ impl<T: Hashable> Hashable for &T {
     fn hash(&self) -> Hash {
         (*self).hash()
     }
+
+    fn hash_with<H: Hasher>(&self) -> Hash {
+        (*self).hash_with::<H>()
+    }
 }
 
 impl<T: Hashable> Hashable for &[T] {
     fn hash(&self) -> Hash {
+        self.hash_with::<MaskedKeccak>()
+    }
+
+    fn hash_with<H: Hasher>(&self) -> Hash {
         if self.len() == 1 {
             // For a single element, return its hash.
-            self[0].hash()
+            self[0].hash_with::<H>()
         } else {
             // Concatenate the element hashes and hash the result.
-            let mut hasher = MaskedKeccak::new();
+            let mut hasher = H::default();
             for value in self.iter() {
-                hasher.update(value.hash().as_bytes());
+                hasher.update(value.hash_with::<H>().as_bytes());
             }
             hasher.hash()
         }
@@ -62,4 +84,48 @@ impl<T: Hashable> Hashable for Vec<T> {
     fn hash(&self) -> Hash {
         self.as_slice().hash()
     }
+
+    fn hash_with<H: Hasher>(&self) -> Hash {
+        self.as_slice().hash_with::<H>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::Keccak256;
+    use zkp_macros_decl::hex;
+    use zkp_primefield::One;
+
+    #[test]
+    fn field_element_montgomery() {
+        let one = FieldElement::one();
+        assert_eq!(one.hash(), one.as_montgomery().hash());
+        assert_eq!(
+            one.hash(),
+            Hash::new(hex!(
+                "07fffffffffffdf0ffffffffffffffffffffffffffffffffffffffffffffffe1"
+            ))
+        );
+    }
+
+    #[test]
+    fn field_element_slice() {
+        let values = [FieldElement::one(), FieldElement::from(2)];
+        assert_eq!((&values[..1]).hash(), values[0].hash());
+        let mut hasher = MaskedKeccak::new();
+        hasher.update_u256(values[0].as_montgomery());
+        hasher.update_u256(values[1].as_montgomery());
+        assert_eq!((&values[..]).hash(), hasher.hash());
+    }
+
+    #[test]
+    fn field_element_slice_with() {
+        let values = vec![FieldElement::one(), FieldElement::from(2)];
+        assert_eq!(values.hash_with::<MaskedKeccak>(), values.hash());
+        let mut hasher = Keccak256::default();
+        hasher.update(values[0].hash().as_bytes());
+        hasher.update(values[1].hash().as_bytes());
+        assert_eq!(values.hash_with::<Keccak256>(), hasher.hash());
+    }
 }
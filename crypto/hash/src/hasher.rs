@@ -0,0 +1,68 @@
+use crate::{hash::Hash, masked_keccak::MaskedKeccak};
+use tiny_keccak::{Hasher as _, Keccak};
+
+/// Hash function used to combine hashes, for example in Merkle tree nodes.
+///
+/// [`MaskedKeccak`] is the default. Other hash functions, such as ones that are
+/// cheaper to verify in a recursive proof, can be used by implementing this
+/// trait.
+pub trait Hasher: Default {
+    fn update(&mut self, input: &[u8]);
+
+    fn hash(self) -> Hash;
+}
+
+impl Hasher for MaskedKeccak {
+    fn update(&mut self, input: &[u8]) {
+        Self::update(self, input)
+    }
+
+    fn hash(self) -> Hash {
+        Self::hash(self)
+    }
+}
+
+/// Keccak-256 without masking.
+///
+/// This is the hash of the Fiat-Shamir channel in `zkp-stark`.
+#[derive(Clone)]
+pub struct Keccak256(Keccak);
+
+impl Default for Keccak256 {
+    fn default() -> Self {
+        Self(Keccak::v256())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Debug for Keccak256 {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(fmt, "Keccak256(...)")
+    }
+}
+
+impl Hasher for Keccak256 {
+    fn update(&mut self, input: &[u8]) {
+        self.0.update(input)
+    }
+
+    fn hash(self) -> Hash {
+        let mut result = [0; 32];
+        self.0.finalize(&mut result);
+        Hash::new(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keccak256_is_unmasked() {
+        let mut hasher = Keccak256::default();
+        hasher.update(b"unmasked");
+        let mut masked = MaskedKeccak::with_mask(32);
+        masked.update(b"unmasked");
+        assert_eq!(hasher.hash(), masked.hash());
+    }
+}
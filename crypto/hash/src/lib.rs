@@ -46,6 +46,12 @@ extern crate no_std_compat as std;
 
 mod hash;
 mod hashable;
+mod hasher;
 mod masked_keccak;
 
-pub use crate::{hash::Hash, hashable::Hashable, masked_keccak::MaskedKeccak};
+pub use crate::{
+    hash::{Hash, ParseError as HashParseError},
+    hashable::Hashable,
+    hasher::{Hasher, Keccak256},
+    masked_keccak::MaskedKeccak,
+};
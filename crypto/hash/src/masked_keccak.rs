@@ -1,25 +1,60 @@
 use crate::hash::Hash;
 use tiny_keccak::{Hasher, Keccak};
+use zkp_primefield::FieldElement;
+use zkp_u256::U256;
 
-pub struct MaskedKeccak(Keccak);
+pub struct MaskedKeccak {
+    keccak:      Keccak,
+    mask_length: usize,
+}
 
 impl MaskedKeccak {
-    const MASK_LENGTH: usize = 20;
+    /// Number of leading bytes kept by default, matching the 160 bit hashes
+    /// of the on-chain verifier.
+    pub const MASK_LENGTH: usize = 20;
 
     #[must_use]
     pub fn new() -> Self {
-        Self(Keccak::v256())
+        Self::with_mask(Self::MASK_LENGTH)
+    }
+
+    /// Hasher that keeps the first `bytes` bytes of the Keccak output and
+    /// sets the rest to zero. With `bytes = 32` this is plain Keccak-256.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is larger than 32.
+    #[must_use]
+    pub fn with_mask(bytes: usize) -> Self {
+        assert!(bytes <= 32, "Mask length can be at most 32 bytes");
+        Self {
+            keccak:      Keccak::v256(),
+            mask_length: bytes,
+        }
     }
 
     pub fn update(&mut self, input: &[u8]) {
-        self.0.update(input)
+        self.keccak.update(input)
+    }
+
+    /// Absorb the 32 big-endian bytes of `value`.
+    pub fn update_u256(&mut self, value: &U256) {
+        self.update(&value.to_bytes_be());
+    }
+
+    /// Absorb the 32 big-endian bytes of the canonical value of `element`.
+    ///
+    /// Note that this differs from the [`Hashable`](crate::Hashable)
+    /// implementation of `FieldElement`, which hashes the Montgomery form.
+    pub fn update_field(&mut self, element: &FieldElement) {
+        self.update_u256(&element.to_uint());
     }
 
     #[must_use]
     pub fn hash(self) -> Hash {
         let mut result: [u8; 32] = [0; 32];
-        self.0.finalize(&mut result);
-        for byte in result[Self::MASK_LENGTH..].iter_mut() {
+        self.keccak.finalize(&mut result);
+        for byte in result[self.mask_length..].iter_mut() {
             *byte = 0;
         }
         Hash::new(result)
@@ -38,3 +73,62 @@ impl std::fmt::Debug for MaskedKeccak {
         write!(fmt, "MaskedKeccak(...)")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkp_macros_decl::hex;
+
+    fn keccak(input: &[u8]) -> [u8; 32] {
+        let mut keccak = Keccak::v256();
+        keccak.update(input);
+        let mut result = [0; 32];
+        keccak.finalize(&mut result);
+        result
+    }
+
+    #[test]
+    fn default_mask() {
+        let mut hasher = MaskedKeccak::new();
+        hasher.update(b"masked");
+        let mut expected = keccak(b"masked");
+        for byte in &mut expected[20..] {
+            *byte = 0;
+        }
+        assert_eq!(hasher.hash(), Hash::new(expected));
+    }
+
+    #[test]
+    fn update_field() {
+        let element = FieldElement::from(0xcafe_babe_u64);
+        let mut hasher = MaskedKeccak::new();
+        hasher.update_field(&element);
+        let mut manual = MaskedKeccak::new();
+        manual.update(&element.to_uint().to_bytes_be());
+        let hash = hasher.hash();
+        assert_eq!(hash, manual.hash());
+        assert_eq!(
+            hash,
+            Hash::new(hex!(
+                "788ff1594590ac6811398a2fa500ae737578fc87000000000000000000000000"
+            ))
+        );
+    }
+
+    #[test]
+    fn update_u256() {
+        let value = U256::from(0xcafe_babe_u64);
+        let mut hasher = MaskedKeccak::new();
+        hasher.update_u256(&value);
+        let mut manual = MaskedKeccak::new();
+        manual.update(&value.to_bytes_be());
+        assert_eq!(hasher.hash(), manual.hash());
+    }
+
+    #[test]
+    fn unmasked() {
+        let mut hasher = MaskedKeccak::with_mask(32);
+        hasher.update(b"unmasked");
+        assert_eq!(hasher.hash(), Hash::new(keccak(b"unmasked")));
+    }
+}
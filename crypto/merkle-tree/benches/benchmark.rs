@@ -1,5 +1,6 @@
 #![warn(clippy::all)]
 use criterion::{black_box, Criterion};
+use rayon::ThreadPoolBuilder;
 use zkp_criterion_utils::{log_size_bench, log_thread_bench};
 use zkp_merkle_tree::Tree;
 use zkp_u256::U256;
@@ -10,6 +11,12 @@ const SIZES: [usize; 6] = [64, 256, 1024, 4096, 16384, 65536];
 #[cfg(test)]
 const SIZES: [usize; 1] = [64];
 
+#[cfg(not(test))]
+const CROSSOVER_SIZES: [usize; 7] = [1 << 8, 1 << 10, 1 << 12, 1 << 14, 1 << 16, 1 << 18, 1 << 20];
+
+#[cfg(test)]
+const CROSSOVER_SIZES: [usize; 1] = [1 << 8];
+
 fn merkle_tree_size(crit: &mut Criterion) {
     log_size_bench(crit, "Merkle tree size", &SIZES, move |bench, size| {
         let leaves: Vec<_> = (0..size).map(U256::from).collect();
@@ -17,6 +24,35 @@ fn merkle_tree_size(crit: &mut Criterion) {
     });
 }
 
+// Compare with `Merkle tree crossover` to find where threading pays off.
+fn merkle_tree_sequential(crit: &mut Criterion) {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .expect("Building benchmark thread pool failed.");
+    log_size_bench(
+        crit,
+        "Merkle tree sequential",
+        &CROSSOVER_SIZES,
+        move |bench, size| {
+            let leaves: Vec<_> = (0..size).map(U256::from).collect();
+            pool.install(|| bench.iter(|| black_box(Tree::from_leaves(black_box(leaves.clone())))))
+        },
+    );
+}
+
+fn merkle_tree_crossover(crit: &mut Criterion) {
+    log_size_bench(
+        crit,
+        "Merkle tree crossover",
+        &CROSSOVER_SIZES,
+        move |bench, size| {
+            let leaves: Vec<_> = (0..size).map(U256::from).collect();
+            bench.iter(|| black_box(Tree::from_leaves(black_box(leaves.clone()))))
+        },
+    );
+}
+
 fn merkle_tree_threads(crit: &mut Criterion) {
     let size: usize = *SIZES.last().unwrap();
     log_thread_bench(crit, "Merkle tree threads", size, move |bench| {
@@ -29,5 +65,7 @@ fn main() {
     let crit = &mut Criterion::default().configure_from_args();
     merkle_tree_size(crit);
     merkle_tree_threads(crit);
+    merkle_tree_sequential(crit);
+    merkle_tree_crossover(crit);
     crit.final_summary();
 }
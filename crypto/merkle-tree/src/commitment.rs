@@ -4,10 +4,11 @@
 #[allow(clippy::wildcard_imports)]
 use std::prelude::v1::*;
 
-use crate::{Error, Index, Result};
+use crate::{Error, Index, Node, Result};
 use itertools::Itertools;
+use std::collections::VecDeque;
 use zkp_error_utils::require;
-use zkp_hash::Hash;
+use zkp_hash::{Hash, Hashable, Hasher, MaskedKeccak};
 
 #[derive(Clone, Default)]
 #[cfg_attr(feature = "std", derive(Debug))]
@@ -63,4 +64,113 @@ impl Commitment {
         }
         Ok(size)
     }
+
+    /// The number of values and hashes required to open the given values
+    /// when each leaf of the tree holds `2^log_arity` consecutive values.
+    ///
+    /// Value index `i` is in leaf `i >> log_arity`. For every opened leaf the
+    /// values that are not in `indices` are included in the decommitment,
+    /// followed by the proof for the opened leaves. For `log_arity == 0` this
+    /// is the same as `proof_size`.
+    pub fn decommitment_size(&self, indices: &[usize], log_arity: usize) -> Result<usize> {
+        let arity = 1_usize << log_arity;
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        let leaves: Vec<usize> = indices.iter().map(|i| i / arity).dedup().collect();
+        let values = leaves.len() * arity - indices.len();
+        Ok(values + self.proof_size(&leaves)?)
+    }
+
+    /// Verify the leafs against this commitment, pulling the proof hashes
+    /// from an iterator.
+    ///
+    /// Hashes are consumed one at a time in the order the reconstruction
+    /// needs them, so they can be read from a stream without buffering the
+    /// whole proof. Any hashes left in the iterator are not consumed.
+    pub fn verify_streaming<Leaf, Hashes>(
+        &self,
+        leafs: &[(usize, Leaf)],
+        hashes: Hashes,
+    ) -> Result<()>
+    where
+        Leaf: Hashable,
+        Hashes: IntoIterator<Item = Hash>,
+    {
+        self.verify_streaming_with::<MaskedKeccak, _, _>(leafs, hashes)
+    }
+
+    /// Like [`Commitment::verify_streaming`], but for a tree whose nodes are
+    /// hashed with `H`.
+    pub fn verify_streaming_with<H, Leaf, Hashes>(
+        &self,
+        leafs: &[(usize, Leaf)],
+        hashes: Hashes,
+    ) -> Result<()>
+    where
+        H: Hasher,
+        Leaf: Hashable,
+        Hashes: IntoIterator<Item = Hash>,
+    {
+        // TODO: Pass leafs by reference?
+        // TODO: Check if the indices line up.
+
+        // Construct the leaf nodes
+        let mut nodes = leafs
+            .iter()
+            .map(|(index, leaf)| {
+                Index::from_size_offset(self.size, *index)
+                    .map(|index| (index, leaf.hash_with::<H>()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        nodes.sort_unstable_by_key(|(index, _)| *index);
+        // OPT: `tuple_windows` copies the hashes
+        require!(
+            nodes
+                .iter()
+                .tuple_windows()
+                .all(|(a, b)| a.0 != b.0 || a.1 == b.1),
+            Error::DuplicateLeafMismatch
+        );
+        nodes.dedup_by_key(|(index, _)| *index);
+        let mut nodes: VecDeque<(Index, Hash)> = nodes.into_iter().collect();
+
+        // Create a mutable closure to pop hashes from the stream
+        let mut hashes_iter = hashes.into_iter();
+        let mut pop = move || hashes_iter.next().ok_or(Error::NotEnoughHashes);
+
+        // Reconstruct the root
+        while let Some((current, hash)) = nodes.pop_front() {
+            if let Some(parent) = current.parent() {
+                // Reconstruct the parent node
+                let node = if current.is_left() {
+                    if let Some((next, next_hash)) = nodes.front() {
+                        // TODO: Find a better way to satisfy the borrow checker.
+                        let next_hash = next_hash.clone();
+                        if current.sibling().unwrap() == *next {
+                            // Merge left with next
+                            let _ = nodes.pop_front();
+                            Node(&hash, &next_hash).hash_with::<H>()
+                        } else {
+                            // Left not merged with next
+                            // TODO: Find a way to merge this branch with the next.
+                            Node(&hash, &pop()?).hash_with::<H>()
+                        }
+                    } else {
+                        // Left not merged with next
+                        Node(&hash, &pop()?).hash_with::<H>()
+                    }
+                } else {
+                    // Right not merged with previous (or we would have skipped)
+                    Node(&pop()?, &hash).hash_with::<H>()
+                };
+                // Queue the new parent node for the next iteration
+                nodes.push_back((parent, node))
+            } else {
+                // Root node has no parent, we are done
+                require!(hash.ct_eq(&self.hash), Error::RootHashMismatch);
+            }
+        }
+        Ok(())
+    }
 }
@@ -51,12 +51,12 @@ mod commitment;
 ///
 /// <https://eprint.iacr.org/2011/495.pdf>
 // TODO: Spin of to it's own crate.
-// TODO: Implement sparse Merkle trees.
 // TODO: Generalize over hash implementations.
 mod index;
 mod node;
 mod proof;
 mod result;
+mod sparse_tree;
 
 #[cfg(feature = "prover")]
 mod tree;
@@ -65,8 +65,9 @@ mod tree;
 mod vector_commitment;
 
 pub use commitment::Commitment;
-pub use proof::Proof;
+pub use proof::{verify_batch, Proof};
 pub use result::{Error, Result};
+pub use sparse_tree::SparseMerkleTree;
 
 #[cfg(feature = "prover")]
 pub use tree::Tree;
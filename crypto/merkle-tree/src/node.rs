@@ -1,14 +1,24 @@
-use zkp_hash::{Hash, Hashable, MaskedKeccak};
+use zkp_hash::{Hash, Hashable, Hasher, MaskedKeccak};
 
 #[derive(Clone)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub(crate) struct Node<'a>(pub(crate) &'a Hash, pub(crate) &'a Hash);
 
-impl Hashable for Node<'_> {
-    fn hash(&self) -> Hash {
-        let mut hasher = MaskedKeccak::new();
+impl Node<'_> {
+    pub(crate) fn hash_with<H: Hasher>(&self) -> Hash {
+        let mut hasher = H::default();
         hasher.update(self.0.as_bytes());
         hasher.update(self.1.as_bytes());
         hasher.hash()
     }
 }
+
+impl Hashable for Node<'_> {
+    fn hash(&self) -> Hash {
+        Node::hash_with::<MaskedKeccak>(self)
+    }
+
+    fn hash_with<H: Hasher>(&self) -> Hash {
+        Node::hash_with::<H>(self)
+    }
+}
@@ -4,11 +4,9 @@
 #[allow(clippy::wildcard_imports)]
 use std::prelude::v1::*;
 
-use crate::{Commitment, Error, Index, Node, Result};
-use itertools::Itertools;
-use std::collections::VecDeque;
+use crate::{Commitment, Error, Result};
 use zkp_error_utils::require;
-use zkp_hash::{Hash, Hashable};
+use zkp_hash::{Hash, Hashable, Hasher, MaskedKeccak};
 
 // Note: we can merge and split proofs. Based on indices we can
 // compute which values are redundant.
@@ -44,65 +42,27 @@ impl Proof {
     }
 
     pub fn verify<Leaf: Hashable>(&self, leafs: &[(usize, Leaf)]) -> Result<()> {
-        // TODO: Pass leafs by reference?
-        // TODO: Check if the indices line up.
-
-        // Construct the leaf nodes
-        let mut nodes = leafs
-            .iter()
-            .map(|(index, leaf)| {
-                Index::from_size_offset(self.commitment.size(), *index)
-                    .map(|index| (index, leaf.hash()))
-            })
-            .collect::<Result<Vec<_>>>()?;
-        nodes.sort_unstable_by_key(|(index, _)| *index);
-        // OPT: `tuple_windows` copies the hashes
-        require!(
-            nodes
-                .iter()
-                .tuple_windows()
-                .all(|(a, b)| a.0 != b.0 || a.1 == b.1),
-            Error::DuplicateLeafMismatch
-        );
-        nodes.dedup_by_key(|(index, _)| *index);
-        let mut nodes: VecDeque<(Index, Hash)> = nodes.into_iter().collect();
-
-        // Create a mutable closure to pop hashes from the list
-        let mut hashes_iter = self.hashes.iter();
-        let mut pop = move || hashes_iter.next().ok_or(Error::NotEnoughHashes);
+        self.verify_with::<MaskedKeccak, _>(leafs)
+    }
 
-        // Reconstruct the root
-        while let Some((current, hash)) = nodes.pop_front() {
-            if let Some(parent) = current.parent() {
-                // Reconstruct the parent node
-                let node = if current.is_left() {
-                    if let Some((next, next_hash)) = nodes.front() {
-                        // TODO: Find a better way to satisfy the borrow checker.
-                        let next_hash = next_hash.clone();
-                        if current.sibling().unwrap() == *next {
-                            // Merge left with next
-                            let _ = nodes.pop_front();
-                            Node(&hash, &next_hash).hash()
-                        } else {
-                            // Left not merged with next
-                            // TODO: Find a way to merge this branch with the next.
-                            Node(&hash, pop()?).hash()
-                        }
-                    } else {
-                        // Left not merged with next
-                        Node(&hash, pop()?).hash()
-                    }
-                } else {
-                    // Right not merged with previous (or we would have skipped)
-                    Node(pop()?, &hash).hash()
-                };
-                // Queue the new parent node for the next iteration
-                nodes.push_back((parent, node))
-            } else {
-                // Root node has no parent, we are done
-                require!(hash == *self.commitment.hash(), Error::RootHashMismatch);
-            }
-        }
-        Ok(())
+    /// Verify a proof from a tree hashed with `H`.
+    pub fn verify_with<H: Hasher, Leaf: Hashable>(&self, leafs: &[(usize, Leaf)]) -> Result<()> {
+        self.commitment
+            .verify_streaming_with::<H, _, _>(leafs, self.hashes.iter().cloned())
     }
 }
+
+/// Verify the proofs of several trees, for example the trace, constraint and
+/// FRI layer commitments opened at the same query indices.
+///
+/// Each entry pairs a proof with the leaves it opens. Verification stops at
+/// the first proof that fails and returns its position in `batch` together
+/// with the error.
+pub fn verify_batch<Leaf: Hashable>(
+    batch: &[(&Proof, &[(usize, Leaf)])],
+) -> std::result::Result<(), (usize, Error)> {
+    batch
+        .iter()
+        .enumerate()
+        .try_for_each(|(tree, (proof, leafs))| proof.verify(leafs).map_err(|error| (tree, error)))
+}
@@ -0,0 +1,185 @@
+// False positive: attribute has a use
+#[allow(clippy::useless_attribute)]
+// False positive: Importing preludes is allowed
+#[allow(clippy::wildcard_imports)]
+use std::prelude::v1::*;
+
+use crate::{Commitment, Error, Index, Node, Proof, Result};
+use std::collections::BTreeMap;
+use zkp_error_utils::require;
+use zkp_hash::{Hash, Hashable};
+
+/// Sparse Merkle tree
+///
+/// A Merkle tree where most leaves have a default value. Only leaves that
+/// differ from the default and the nodes above them are stored. All other
+/// nodes are roots of empty subtrees, their hashes are computed once per
+/// depth.
+///
+/// The roots and proofs are identical to those of a [`Tree`](crate::Tree)
+/// with all the unset leaves equal to the default.
+#[derive(Clone)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct SparseMerkleTree<Leaf: Hashable> {
+    size:    usize,
+    default: Leaf,
+    leaves:  BTreeMap<usize, Leaf>,
+    nodes:   BTreeMap<Index, Hash>,
+    // Hash of an empty subtree rooted at each depth
+    empty:   Vec<Hash>,
+}
+
+impl<Leaf: Hashable + Clone> SparseMerkleTree<Leaf> {
+    /// Create a tree of `size` leaves all equal to `default`.
+    pub fn new(size: usize, default: Leaf) -> Result<Self> {
+        require!(size.is_power_of_two(), Error::NumLeavesNotPowerOfTwo);
+        require!(size <= Index::max_size(), Error::TreeToLarge);
+        let leaf_depth = Index::depth_for_size(size);
+        let mut empty = vec![default.hash()];
+        for _ in 0..leaf_depth {
+            let below = empty.last().unwrap();
+            let hash = Node(below, below).hash();
+            empty.push(hash);
+        }
+        empty.reverse();
+        Ok(Self {
+            size,
+            default,
+            leaves: BTreeMap::new(),
+            nodes: BTreeMap::new(),
+            empty,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn leaf_depth(&self) -> usize {
+        Index::depth_for_size(self.size)
+    }
+
+    pub fn root(&self) -> Hash {
+        self.node_hash(Index::root())
+    }
+
+    pub fn commitment(&self) -> Commitment {
+        Commitment::from_size_hash(self.size, &self.root()).unwrap()
+    }
+
+    pub fn leaf(&self, index: usize) -> &Leaf {
+        self.leaves.get(&index).unwrap_or(&self.default)
+    }
+
+    /// Iterate over the leaves that differ from the default.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Leaf)> {
+        self.leaves.iter().map(|(index, leaf)| (*index, leaf))
+    }
+
+    pub fn node_hash(&self, index: Index) -> Hash {
+        self.nodes
+            .get(&index)
+            .unwrap_or_else(|| &self.empty[index.depth()])
+            .clone()
+    }
+
+    pub fn update(&mut self, index: usize, leaf: Leaf) -> Result<()> {
+        let mut current = Index::from_size_offset(self.size, index)?;
+        let hash = leaf.hash();
+        if hash == self.empty[current.depth()] {
+            let _ = self.leaves.remove(&index);
+        } else {
+            let _ = self.leaves.insert(index, leaf);
+        }
+        self.set_node(current, hash);
+        while let Some(parent) = current.parent() {
+            let hash = Node(
+                &self.node_hash(parent.left_child()),
+                &self.node_hash(parent.right_child()),
+            )
+            .hash();
+            self.set_node(parent, hash);
+            current = parent;
+        }
+        Ok(())
+    }
+
+    pub fn proof(&self, index: usize) -> Result<Proof> {
+        let mut current = Index::from_size_offset(self.size, index)?;
+        let mut hashes = Vec::with_capacity(self.leaf_depth());
+        while let Some(sibling) = current.sibling() {
+            hashes.push(self.node_hash(sibling));
+            current = current.parent().unwrap();
+        }
+        Proof::from_hashes(&self.commitment(), &[index], &hashes)
+    }
+
+    fn set_node(&mut self, index: Index, hash: Hash) {
+        if hash == self.empty[index.depth()] {
+            let _ = self.nodes.remove(&index);
+        } else {
+            let _ = self.nodes.insert(index, hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tree;
+    use proptest::prelude::*;
+    use zkp_u256::U256;
+
+    proptest!(
+        #[test]
+        fn sparse_matches_dense(depth: usize, updates: Vec<(usize, u64)>) {
+            let depth = depth % 9;
+            let size = 1_usize << depth;
+            let default = U256::from(7);
+            let mut sparse = SparseMerkleTree::new(size, default.clone()).unwrap();
+            let mut dense = vec![default; size];
+            for (index, value) in updates {
+                let index = index % size;
+                sparse.update(index, U256::from(value)).unwrap();
+                dense[index] = U256::from(value);
+            }
+            let tree = Tree::from_leaves(dense).unwrap();
+            prop_assert_eq!(&sparse.root(), tree.commitment().hash());
+            for index in 0..size {
+                prop_assert_eq!(sparse.leaf(index), &tree.leaf(index));
+            }
+        }
+
+        #[test]
+        fn sparse_proof(depth: usize, updates: Vec<(usize, u64)>, index: usize) {
+            let depth = depth % 9;
+            let size = 1_usize << depth;
+            let index = index % size;
+            let mut sparse = SparseMerkleTree::new(size, U256::ZERO).unwrap();
+            for (index, value) in updates {
+                sparse.update(index % size, U256::from(value)).unwrap();
+            }
+            let proof = sparse.proof(index).unwrap();
+            prop_assert!(proof.verify(&[(index, sparse.leaf(index).clone())]).is_ok());
+        }
+    );
+
+    #[test]
+    fn reset_to_default() {
+        let mut sparse = SparseMerkleTree::new(1 << 20, U256::ZERO).unwrap();
+        let empty_root = sparse.root();
+        sparse.update(12345, U256::from(1)).unwrap();
+        sparse.update(999_999, U256::from(2)).unwrap();
+        assert_ne!(sparse.root(), empty_root);
+        assert_eq!(sparse.iter().count(), 2);
+        sparse.update(12345, U256::ZERO).unwrap();
+        sparse.update(999_999, U256::ZERO).unwrap();
+        assert_eq!(sparse.root(), empty_root);
+        assert_eq!(sparse.iter().count(), 0);
+        assert!(sparse.nodes.is_empty());
+        assert_eq!(
+            sparse.update(1 << 20, U256::ZERO),
+            Err(Error::IndexOutOfRange)
+        );
+    }
+}
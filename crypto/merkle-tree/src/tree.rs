@@ -1,37 +1,61 @@
 use crate::{Commitment, Error, Index, Node, Proof, Result, VectorCommitment};
 use log::{info, trace};
-use std::collections::VecDeque;
+use std::{collections::VecDeque, marker::PhantomData, prelude::v1::*};
 use zkp_error_utils::require;
-use zkp_hash::{Hash, Hashable};
+use zkp_hash::{Hash, Hasher, MaskedKeccak};
 use zkp_mmap_vec::MmapVec;
 
 #[cfg(feature = "std")]
 use rayon::prelude::*;
 
-// Utility function to parallelize iff on std
+/// Minimum number of nodes per thread for a layer to be hashed in parallel.
+///
+/// Below this the overhead of distributing the work over threads is larger
+/// than the time spent hashing. See the `Merkle tree sequential` benchmark.
+#[cfg(feature = "std")]
+const MIN_NODES_PER_THREAD: usize = 64;
+
+// Smallest layer size that is hashed in parallel
+#[cfg(feature = "std")]
+fn parallel_threshold() -> usize {
+    let num_threads = rayon::current_num_threads();
+    if num_threads <= 1 {
+        usize::max_value()
+    } else {
+        MIN_NODES_PER_THREAD * num_threads
+    }
+}
+
+// Utility function to parallelize iff on std and the slice is large enough
 fn for_each<F>(slice: &mut [Hash], f: F)
 where
     F: Fn((usize, &mut Hash)) + Sync + Send,
 {
     #[cfg(feature = "std")]
-    slice.par_iter_mut().enumerate().for_each(f);
+    {
+        if slice.len() >= parallel_threshold() {
+            slice.par_iter_mut().enumerate().for_each(f);
+        } else {
+            slice.iter_mut().enumerate().for_each(f);
+        }
+    }
 
     #[cfg(not(feature = "std"))]
     slice.iter_mut().enumerate().for_each(f);
 }
 
 // Utility function to compute the first layer of the tree from the leaves
-fn compute<C: VectorCommitment>(leaves: &C, index: Index) -> Hash {
+fn compute<C: VectorCommitment, H: Hasher>(leaves: &C, index: Index) -> Hash {
     let leaf_depth = Index::depth_for_size(leaves.len());
     assert!(index.depth() <= leaf_depth);
     if index.depth() == leaf_depth {
-        leaves.leaf_hash(index.offset())
+        leaves.leaf_hash_with::<H>(index.offset())
     } else {
         Node(
-            &compute(leaves, index.left_child()),
-            &compute(leaves, index.right_child()),
+            &compute::<C, H>(leaves, index.left_child()),
+            &compute::<C, H>(leaves, index.right_child()),
         )
-        .hash()
+        .hash_with::<H>()
     }
 }
 
@@ -40,14 +64,19 @@ fn compute<C: VectorCommitment>(leaves: &C, index: Index) -> Hash {
 /// The tree will become the owner of the `Container`. This is necessary because
 /// when low layer-omission is implemented we need immutable access to the
 /// leaves. If shared ownership is required the `Container` can be an `Rc<_>`.
+///
+/// The nodes, and leaves holding several values, are hashed with `H`, which
+/// defaults to [`MaskedKeccak`]. Proofs from a tree with a different hasher are
+/// verified with [`Proof::verify_with`].
 // OPT: Do not store leaf hashes but re-create.
 // OPT: Allow up to `n` lower layers to be skipped.
 // TODO: Make hash depend on type.
 #[cfg_attr(feature = "std", derive(Debug))]
-pub struct Tree<Container: VectorCommitment> {
+pub struct Tree<Container: VectorCommitment, H: Hasher = MaskedKeccak> {
     commitment: Commitment,
     nodes:      MmapVec<Hash>,
     leaves:     Container,
+    hasher:     PhantomData<H>,
 }
 
 impl<Container: VectorCommitment> Tree<Container> {
@@ -56,6 +85,13 @@ impl<Container: VectorCommitment> Tree<Container> {
     }
 
     pub fn from_leaves_skip_layers(leaves: Container, skip_layers: usize) -> Result<Self> {
+        Self::with_hasher(leaves, skip_layers)
+    }
+}
+
+impl<Container: VectorCommitment, H: Hasher> Tree<Container, H> {
+    /// Like [`Tree::from_leaves_skip_layers`], but hashing with `H`.
+    pub fn with_hasher(leaves: Container, skip_layers: usize) -> Result<Self> {
         info!(
             "Computing Merkle tree of size {} ({} skip layer)",
             leaves.len(),
@@ -70,6 +106,7 @@ impl<Container: VectorCommitment> Tree<Container> {
                 commitment: Commitment::from_size_hash(size, &Hash::default()).unwrap(),
                 nodes: MmapVec::with_capacity(0),
                 leaves,
+                hasher: PhantomData,
             });
         }
         // TODO: Support non power of two sizes
@@ -100,7 +137,7 @@ impl<Container: VectorCommitment> Tree<Container> {
             let leaf_layer = &mut nodes[Index::layer_range(depth)];
             // First layer
             for_each(leaf_layer, |(i, hash)| {
-                *hash = compute(&leaves, Index::from_depth_offset(depth, i).unwrap())
+                *hash = compute::<_, H>(&leaves, Index::from_depth_offset(depth, i).unwrap());
             });
             // Upper layers
             for depth in (0..depth).rev() {
@@ -109,13 +146,13 @@ impl<Container: VectorCommitment> Tree<Container> {
                     nodes.split_at_mut(Index::from_depth_offset(depth + 1, 0).unwrap().as_index());
                 let current = &mut tree[Index::layer_range(depth)];
                 for_each(current, |(i, hash)| {
-                    *hash = Node(&previous[i << 1], &previous[i << 1 | 1]).hash()
+                    *hash = Node(&previous[i << 1], &previous[i << 1 | 1]).hash_with::<H>();
                 });
             }
         }
 
         let root_hash = if nodes.is_empty() {
-            compute(&leaves, Index::root())
+            compute::<_, H>(&leaves, Index::root())
         } else {
             nodes[0].clone()
         };
@@ -125,6 +162,7 @@ impl<Container: VectorCommitment> Tree<Container> {
             commitment,
             nodes,
             leaves,
+            hasher: PhantomData,
         })
     }
 
@@ -150,17 +188,19 @@ impl<Container: VectorCommitment> Tree<Container> {
         } else {
             assert!(index.depth() <= self.leaf_depth());
             if index.depth() == self.leaf_depth() {
-                self.leaves.leaf_hash(index.offset())
+                self.leaves.leaf_hash_with::<H>(index.offset())
             } else {
                 Node(
                     &self.node_hash(index.left_child()),
                     &self.node_hash(index.right_child()),
                 )
-                .hash()
+                .hash_with::<H>()
             }
         }
     }
 
+    /// Open the leaves at `indices`, which can be in any order and contain
+    /// duplicates.
     pub fn open(&self, indices: &[usize]) -> Result<Proof> {
         let indices = self.commitment().sort_indices(indices)?;
         let proof_indices: Vec<usize> = indices.iter().map(|i| i.offset()).collect();
@@ -238,6 +278,14 @@ mod tests {
         let select_leaves: Vec<_> = indices.iter().map(|&i| (i, tree.leaf(i))).collect();
         proof.verify(select_leaves.as_slice()).unwrap();
 
+        // Verify proof from a stream of hashes
+        root.verify_streaming(&select_leaves, proof.hashes().iter().cloned())
+            .unwrap();
+        assert_eq!(
+            root.verify_streaming(&select_leaves, proof.hashes().iter().take(8).cloned()),
+            Err(Error::NotEnoughHashes)
+        );
+
         // Verify non-root
         let non_root = Hash::new(hex!(
             "ed112f44bc944f33e2567f86eea202350913b11c000000000000000000000000"
@@ -254,6 +302,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn sequential_matches_parallel() {
+        let sequential = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let parallel = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        for log_size in 0..14 {
+            let leaves: Vec<_> = (0..1_u64 << log_size).map(U256::from).collect();
+            let expected = sequential.install(|| Tree::from_leaves(leaves.clone()).unwrap());
+            let actual = parallel.install(|| Tree::from_leaves(leaves).unwrap());
+            assert_eq!(actual.commitment().hash(), expected.commitment().hash());
+        }
+    }
+
+    // Not a secure hash, but order dependent so misplaced nodes are caught.
+    #[derive(Default, Debug)]
+    struct AddHasher {
+        state:  [u8; 32],
+        offset: usize,
+    }
+
+    impl Hasher for AddHasher {
+        // Truncation is intended
+        #[allow(clippy::cast_possible_truncation)]
+        fn update(&mut self, input: &[u8]) {
+            for byte in input {
+                self.state[self.offset % 32] ^= byte.wrapping_add(self.offset as u8);
+                self.offset += 1;
+            }
+        }
+
+        fn hash(self) -> Hash {
+            Hash::new(self.state)
+        }
+    }
+
+    #[test]
+    fn test_custom_hasher() {
+        let leaves: Vec<_> = (0..64_u64).map(|i| U256::from((i + 10).pow(3))).collect();
+        let indices = vec![1, 11, 14, 63];
+        let select_leaves: Vec<_> = indices.iter().map(|&i| (i, leaves[i].clone())).collect();
+
+        let keccak_tree = Tree::from_leaves(leaves.clone()).unwrap();
+        let tree = Tree::<_, AddHasher>::with_hasher(leaves, 1).unwrap();
+        assert_ne!(tree.commitment().hash(), keccak_tree.commitment().hash());
+
+        let proof = tree.open(&indices).unwrap();
+        assert_eq!(proof.verify_with::<AddHasher, _>(&select_leaves), Ok(()));
+        assert_eq!(proof.verify(&select_leaves), Err(Error::RootHashMismatch));
+        assert_eq!(
+            keccak_tree
+                .open(&indices)
+                .unwrap()
+                .verify_with::<AddHasher, _>(&select_leaves),
+            Err(Error::RootHashMismatch)
+        );
+    }
+
+    #[test]
+    fn test_custom_hasher_leaves() {
+        let leaves: Vec<_> = (0..16_u64)
+            .map(|i| vec![U256::from(i), U256::from(i * i)])
+            .collect();
+        let indices = vec![2, 3, 9];
+        let select_leaves: Vec<_> = indices.iter().map(|&i| (i, leaves[i].clone())).collect();
+        assert_ne!(leaves.leaf_hash_with::<AddHasher>(2), leaves.leaf_hash(2));
+
+        let tree = Tree::<_, AddHasher>::with_hasher(leaves, 1).unwrap();
+        let proof = tree.open(&indices).unwrap();
+        assert_eq!(proof.verify_with::<AddHasher, _>(&select_leaves), Ok(()));
+    }
+
     #[test]
     fn test_empty_tree() {
         let indices: Vec<usize> = vec![];
@@ -271,7 +395,112 @@ mod tests {
         proof.verify(&select_leaves).unwrap();
     }
 
+    // Open `indices` in a tree of 64 leaves, check the proof size, that the
+    // proof verifies and that it fails against a different root.
+    fn check_open(indices: &[usize]) -> std::result::Result<(), TestCaseError> {
+        let leaves: Vec<_> = (0..64_u64).map(|i| U256::from(i * i + 7)).collect();
+        let tree = Tree::from_leaves(leaves).unwrap();
+        let root = tree.commitment();
+        let proof = tree.open(indices).unwrap();
+        prop_assert_eq!(proof.hashes().len(), root.proof_size(indices).unwrap());
+        let select_leaves: Vec<_> = indices.iter().map(|&i| (i, tree.leaf(i))).collect();
+        prop_assert_eq!(proof.verify(&select_leaves), Ok(()));
+
+        let mut flipped = [0_u8; 32];
+        flipped.copy_from_slice(root.hash().as_bytes());
+        flipped[0] ^= 1;
+        let flipped = Commitment::from_size_hash(root.size(), &Hash::new(flipped)).unwrap();
+        let non_proof = Proof::from_hashes(&flipped, indices, proof.hashes()).unwrap();
+        prop_assert_eq!(
+            non_proof.verify(&select_leaves),
+            Err(Error::RootHashMismatch)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_edge_cases() {
+        let all: Vec<usize> = (0..64).collect();
+        check_open(&all).unwrap();
+        let leaves: Vec<_> = (0..64_u64).map(U256::from).collect();
+        assert!(Tree::from_leaves(leaves)
+            .unwrap()
+            .open(&all)
+            .unwrap()
+            .hashes()
+            .is_empty());
+
+        for indices in &[vec![0], vec![63], vec![62, 63], vec![1, 62], vec![0, 63]] {
+            check_open(indices).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_unsorted_duplicate_indices() {
+        let leaves: Vec<_> = (0..64_u64).map(|i| U256::from((i + 10).pow(3))).collect();
+        let tree = Tree::from_leaves(leaves).unwrap();
+        let unsorted = [14, 1, 11, 11];
+        let sorted = [1, 11, 14];
+        let proof = tree.open(&unsorted).unwrap();
+        assert_eq!(proof.hashes(), tree.open(&sorted).unwrap().hashes());
+        assert_eq!(
+            tree.commitment().proof_size(&unsorted),
+            tree.commitment().proof_size(&sorted)
+        );
+
+        let select_leaves: Vec<_> = unsorted.iter().map(|&i| (i, tree.leaf(i))).collect();
+        proof.verify(&select_leaves).unwrap();
+
+        // Duplicates must agree on the value
+        let mut conflicting = select_leaves;
+        conflicting[3].1 += U256::from(1);
+        assert_eq!(
+            proof.verify(&conflicting),
+            Err(Error::DuplicateLeafMismatch)
+        );
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let trees: Vec<_> = (0..3_u64)
+            .map(|seed| {
+                let leaves: Vec<_> = (0..32_u64).map(|i| U256::from(i * i + seed)).collect();
+                Tree::from_leaves(leaves).unwrap()
+            })
+            .collect();
+        let indices = [3, 17, 18, 30];
+        let leaves: Vec<Vec<_>> = trees
+            .iter()
+            .map(|tree| indices.iter().map(|&i| (i, tree.leaf(i))).collect())
+            .collect();
+        let mut proofs: Vec<_> = trees
+            .iter()
+            .map(|tree| tree.open(&indices).unwrap())
+            .collect();
+        let batch = |proofs: &[Proof]| -> std::result::Result<(), (usize, Error)> {
+            let batch: Vec<_> = proofs
+                .iter()
+                .zip(&leaves)
+                .map(|(proof, leaves)| (proof, leaves.as_slice()))
+                .collect();
+            crate::verify_batch(&batch)
+        };
+        assert_eq!(batch(&proofs), Ok(()));
+
+        // Corrupt a decommitment hash of the second tree
+        let mut hashes = proofs[1].hashes().to_vec();
+        hashes[2] = Hash::new([0xff; 32]);
+        proofs[1] = Proof::from_hashes(trees[1].commitment(), &indices, &hashes).unwrap();
+        assert_eq!(batch(&proofs), Err((1, Error::RootHashMismatch)));
+    }
+
     proptest!(
+        #[test]
+        fn test_open_subsets(mask in 1_u64..) {
+            let indices: Vec<usize> = (0..64).filter(|i| mask >> i & 1 == 1).collect();
+            check_open(&indices)?;
+        }
+
         #[test]
         fn test_merkle_tree(depth: usize, skip: usize, indices: Vec<usize>, seed: usize) {
             // We want tests up to depth 8; adjust the input
@@ -295,6 +524,10 @@ mod tests {
             // Verify proof
             let select_leaves: Vec<_> = indices.iter().map(|&i| (i, tree.leaf(i))).collect();
             prop_assert!(proof.verify(&select_leaves).is_ok());
+            prop_assert!(tree
+                .commitment()
+                .verify_streaming(&select_leaves, proof.hashes().iter().cloned())
+                .is_ok());
         }
     );
 }
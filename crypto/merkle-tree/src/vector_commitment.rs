@@ -5,7 +5,7 @@
 use std::prelude::v1::*;
 
 use crate::{Commitment, Result, Tree};
-use zkp_hash::{Hash, Hashable};
+use zkp_hash::{Hash, Hashable, Hasher, MaskedKeccak};
 
 #[cfg(feature = "mmap")]
 use crate::mmap_vec::MmapVec;
@@ -26,11 +26,24 @@ where
     fn leaf(&self, index: usize) -> Self::Leaf;
 
     fn leaf_hash(&self, index: usize) -> Hash {
-        self.leaf(index).hash()
+        self.leaf_hash_with::<MaskedKeccak>(index)
+    }
+
+    /// Hash of a leaf in a tree whose nodes are hashed with `H`.
+    ///
+    /// Implementations that override the leaf hashing should override this
+    /// method rather than [`VectorCommitment::leaf_hash`].
+    fn leaf_hash_with<H: Hasher>(&self, index: usize) -> Hash {
+        self.leaf(index).hash_with::<H>()
     }
 
     fn commit(self) -> Result<(Commitment, Tree<Self>)> {
-        let tree = Tree::from_leaves(self)?;
+        self.commit_with()
+    }
+
+    /// Like [`VectorCommitment::commit`], but hashing the tree with `H`.
+    fn commit_with<H: Hasher>(self) -> Result<(Commitment, Tree<Self, H>)> {
+        let tree = Tree::with_hasher(self, 1)?;
         let commitment = tree.commitment().clone();
         Ok((commitment, tree))
     }
@@ -49,8 +62,8 @@ impl<Leaf: Hashable + Clone + Sync> VectorCommitment for Vec<Leaf> {
         self[index].clone()
     }
 
-    fn leaf_hash(&self, index: usize) -> Hash {
-        self[index].hash()
+    fn leaf_hash_with<H: Hasher>(&self, index: usize) -> Hash {
+        self[index].hash_with::<H>()
     }
 }
 
@@ -66,7 +79,7 @@ impl<Leaf: Hashable + Clone + Sync> VectorCommitment for MmapVec<Leaf> {
         self[index].clone()
     }
 
-    fn leaf_hash(&self, index: usize) -> Hash {
-        self[index].hash()
+    fn leaf_hash_with<H: Hasher>(&self, index: usize) -> Hash {
+        self[index].hash_with::<H>()
     }
 }
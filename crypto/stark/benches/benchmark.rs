@@ -1,8 +1,13 @@
 #![warn(clippy::all)]
 use criterion::{black_box, Criterion};
+use zkp_criterion_utils::log_thread_bench;
+use zkp_hash::Hash;
 use zkp_macros_decl::field_element;
-use zkp_primefield::{FieldElement, Root};
-use zkp_stark::{prove, verify, Constraints, Provable, RationalExpression, TraceTable, Verifiable};
+use zkp_primefield::{FieldElement, Pow, Root};
+use zkp_stark::{
+    check_constraints, grind_with_callback, prove, verify, Constraints, DensePolynomial, Provable,
+    RationalExpression, TraceLayout, TraceTable, Verifiable,
+};
 use zkp_u256::U256;
 
 #[derive(Clone, PartialEq, Debug)]
@@ -16,23 +21,33 @@ pub(crate) struct Witness {
     pub(crate) secret: FieldElement,
 }
 
-impl Verifiable for Claim {
-    fn constraints(&self) -> Constraints {
-        use RationalExpression::*;
+impl Claim {
+    /// The trace needs a row for `index` and at least two rows for the
+    /// recurrence.
+    fn trace_length(&self) -> usize {
+        std::cmp::max(self.index + 1, 2).next_power_of_two()
+    }
+}
 
-        // Seed
+impl Verifiable for Claim {
+    fn public_input_bytes(&self) -> Vec<u8> {
         let mut seed = self.index.to_be_bytes().to_vec();
         seed.extend_from_slice(&self.value.as_montgomery().to_bytes_be());
+        seed
+    }
+
+    fn constraints(&self) -> Constraints {
+        use RationalExpression::*;
 
         // Constraint repetitions
-        let trace_length = self.index.next_power_of_two();
+        let trace_length = self.trace_length();
         let trace_generator = FieldElement::root(trace_length).unwrap();
         let g = Constant(trace_generator);
         let on_row = |index| (X - g.pow(index)).inv();
         let every_row = || (X - g.pow(trace_length - 1)) / (X.pow(trace_length) - 1);
 
         // Constraints
-        Constraints::from_expressions((trace_length, 2), seed, vec![
+        Constraints::from_expressions((trace_length, 2), self.public_input_bytes(), vec![
             (Trace(0, 1) - Trace(1, 0)) * every_row(),
             (Trace(1, 1) - Trace(0, 0) - Trace(1, 0)) * every_row(),
             (Trace(0, 0) - 1) * on_row(0),
@@ -44,7 +59,7 @@ impl Verifiable for Claim {
 
 impl Provable<&Witness> for Claim {
     fn trace(&self, witness: &Witness) -> TraceTable {
-        let trace_length = self.index.next_power_of_two();
+        let trace_length = self.trace_length();
         let mut trace = TraceTable::new(trace_length, 2);
         trace[(0, 0)] = 1.into();
         trace[(0, 1)] = witness.secret.clone();
@@ -90,9 +105,62 @@ fn bench_verify(crit: &mut Criterion) {
     });
 }
 
+fn bench_trace_layout(crit: &mut Criterion) {
+    use RationalExpression::*;
+    let trace_length = 1024;
+    let num_columns = 64;
+    let g = Constant(FieldElement::root(trace_length).unwrap());
+    let every_row = || (X - g.pow(trace_length - 1)) / (X.pow(trace_length) - 1);
+    let expressions = (0..num_columns)
+        .map(|j| (Trace(j, 1) - Trace(j, 0) - Trace(j, -1)) * every_row())
+        .collect();
+    let constraints =
+        Constraints::from_expressions((trace_length, num_columns), vec![], expressions).unwrap();
+
+    for &(name, layout) in &[
+        ("row-major", TraceLayout::RowMajor),
+        ("column-major", TraceLayout::ColumnMajor),
+    ] {
+        let trace = TraceTable::with_layout(trace_length, num_columns, layout);
+        let constraints = constraints.clone();
+        crit.bench_function(
+            &format!("Checking constraints on a {} wide trace", name),
+            move |bench| bench.iter(|| black_box(check_constraints(&constraints, &trace))),
+        );
+    }
+}
+
+fn bench_mul(crit: &mut Criterion) {
+    let coefficients: Vec<FieldElement> = (0..1024)
+        .map(|i| FieldElement::from(i).pow(7_usize))
+        .collect();
+    let a = DensePolynomial::new(&coefficients);
+    let b = DensePolynomial::new(&coefficients[..512]);
+
+    crit.bench_function(
+        "Multiplying polynomials of degree 1023 and 511",
+        move |bench| bench.iter(|| black_box(&a * &b)),
+    );
+}
+
+fn bench_pow(crit: &mut Criterion) {
+    let challenge = Hash::new([0x5a; 32]);
+    log_thread_bench(
+        crit,
+        "Proof of work 16 bits threads",
+        1 << 16,
+        move |bench| {
+            bench.iter(|| black_box(grind_with_callback(black_box(&challenge), 16, |_| {})))
+        },
+    );
+}
+
 fn main() {
     let crit = &mut Criterion::default().configure_from_args();
     bench_verify(crit);
     bench_prove(crit);
+    bench_trace_layout(crit);
+    bench_mul(crit);
+    bench_pow(crit);
     crit.final_summary();
 }
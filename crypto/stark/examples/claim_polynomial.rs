@@ -1,8 +1,8 @@
 use zkp_macros_decl::field_element;
 use zkp_primefield::FieldElement;
 use zkp_stark::{
-    generate, proof_serialize, prove, Constraints, DensePolynomial, Provable, RationalExpression,
-    TraceTable, Verifiable,
+    generate, proof_serialize, prove, verify, Constraints, DensePolynomial, Provable,
+    RationalExpression, TraceTable, Verifiable,
 };
 use zkp_u256::U256;
 
@@ -13,9 +13,13 @@ struct Claim(FieldElement);
 struct Witness(FieldElement);
 
 impl Verifiable for Claim {
+    fn public_input_bytes(&self) -> Vec<u8> {
+        self.0.as_montgomery().to_bytes_be().to_vec()
+    }
+
     fn constraints(&self) -> Constraints {
         use RationalExpression::*;
-        Constraints::from_expressions((2, 1), self.0.as_montgomery().to_bytes_be().to_vec(), vec![
+        Constraints::from_expressions((2, 1), self.public_input_bytes(), vec![
             (Trace(0, 0) - ClaimPolynomial(0, 0, Box::new(X), Some("MyClaimPoly"))) / (X - 1),
         ])
         .unwrap()
@@ -32,8 +36,20 @@ impl Provable<&Witness> for Claim {
 }
 
 impl Claim {
+    fn claim_polynomials(&self) -> Vec<DensePolynomial> {
+        vec![DensePolynomial::new(&[self.0.clone()])]
+    }
+
+    /// The constraints with the claim polynomials attached but not yet
+    /// substituted, as the prover and verifier receive them.
+    fn claim_system(&self) -> Constraints {
+        let mut system = self.constraints();
+        system.add_claim_polynomials(self.claim_polynomials());
+        system
+    }
+
     fn concrete_system(&self) -> Constraints {
-        let claim_polynomials = vec![DensePolynomial::new(&[self.0.clone()])];
+        let claim_polynomials = self.claim_polynomials();
         let expressions = self
             .constraints()
             .expressions()
@@ -41,12 +57,7 @@ impl Claim {
             .map(|x| x.substitute_claim(&claim_polynomials))
             .collect();
 
-        Constraints::from_expressions(
-            (2, 1),
-            self.0.as_montgomery().to_bytes_be().to_vec(),
-            expressions,
-        )
-        .unwrap()
+        Constraints::from_expressions((2, 1), self.public_input_bytes(), expressions).unwrap()
     }
 }
 
@@ -59,6 +70,7 @@ fn main() {
     let concrete_system = claim.concrete_system();
     let trace = claim.trace(&witness);
     let proof = prove(&concrete_system, &trace).unwrap();
+    verify(&claim.claim_system(), &proof).expect("Verification failed");
 
     let mut proof_string = "".to_string();
     proof_serialize(&concrete_system, &proof, &mut proof_string).unwrap();
@@ -71,3 +83,30 @@ fn main() {
         "Claim",
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkp_primefield::One;
+
+    #[test]
+    fn prove_claim_polynomial() {
+        let claim = Claim(FieldElement::from(1234));
+        let witness = Witness(claim.0.clone());
+        let trace = claim.trace(&witness);
+        let proof = prove(&claim.claim_system(), &trace).unwrap();
+        verify(&claim.claim_system(), &proof).unwrap();
+        verify(&claim.concrete_system(), &proof).unwrap();
+
+        // Same seed, different claim polynomial
+        let mut wrong = claim.claim_system();
+        wrong.add_claim_polynomials(vec![DensePolynomial::new(&[
+            claim.0.clone() + FieldElement::one()
+        ])]);
+        assert!(verify(&wrong, &proof).is_err());
+
+        // Different claim, and therefore a different seed
+        let other = Claim(claim.0.clone() + FieldElement::one());
+        assert!(verify(&other.claim_system(), &proof).is_err());
+    }
+}
@@ -13,9 +13,13 @@ struct Claim(FieldElement);
 struct Witness(FieldElement);
 
 impl Verifiable for Claim {
+    fn public_input_bytes(&self) -> Vec<u8> {
+        self.0.as_montgomery().to_bytes_be().to_vec()
+    }
+
     fn constraints(&self) -> Constraints {
         use RationalExpression::*;
-        Constraints::from_expressions((2, 1), self.0.as_montgomery().to_bytes_be().to_vec(), vec![
+        Constraints::from_expressions((2, 1), self.public_input_bytes(), vec![
             (Trace(0, 0) - Constant(self.0.clone())) / (X - 1),
         ])
         .unwrap()
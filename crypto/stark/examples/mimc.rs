@@ -0,0 +1,219 @@
+#![warn(clippy::all)]
+use log::info;
+use std::{fmt, time::Instant};
+use zkp_macros_decl::field_element;
+use zkp_primefield::{fft::permute, Fft, FieldElement, Pow, Root, Zero};
+use zkp_stark::{
+    Constraints, DensePolynomial, Provable, RationalExpression, TraceTable, Verifiable,
+};
+use zkp_u256::U256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Error {
+    RoundConstantsLength { rounds: usize, constants: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+        match *self {
+            RoundConstantsLength { rounds, constants } => {
+                write!(f, "Expected {} round constants, got {}", rounds, constants)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Clone, Debug)]
+struct MimcParams {
+    rounds:          usize,
+    round_constants: Vec<FieldElement>,
+    exponent:        usize,
+}
+
+/// The MiMC permutation $x_{i+1} = (x_i + c_i)^e$.
+#[derive(Clone, Debug)]
+struct Mimc {
+    params: MimcParams,
+}
+
+impl Mimc {
+    fn new(params: MimcParams) -> Result<Self, Error> {
+        if params.round_constants.len() != params.rounds {
+            return Err(Error::RoundConstantsLength {
+                rounds:    params.rounds,
+                constants: params.round_constants.len(),
+            });
+        }
+        Ok(Self { params })
+    }
+
+    fn round(&self, x: &FieldElement, constant: &FieldElement) -> FieldElement {
+        (x + constant).pow(self.params.exponent)
+    }
+
+    fn permute(&self, x: &FieldElement) -> FieldElement {
+        self.params
+            .round_constants
+            .iter()
+            .fold(x.clone(), |x, constant| self.round(&x, constant))
+    }
+
+    /// One row per round plus a row for the output. The rows after the
+    /// output continue the permutation with zero round constants.
+    fn trace_length(&self) -> usize {
+        std::cmp::max(self.params.rounds + 1, 2).next_power_of_two()
+    }
+
+    /// Round constants padded with zeros to the trace length.
+    fn padded_constants(&self) -> Vec<FieldElement> {
+        let mut constants = self.params.round_constants.clone();
+        constants.resize(self.trace_length(), FieldElement::zero());
+        constants
+    }
+}
+
+// Proves that `after` is the MiMC permutation applied to `before`.
+#[derive(Clone, Debug)]
+struct Claim {
+    mimc:   Mimc,
+    before: FieldElement,
+    after:  FieldElement,
+}
+
+impl Verifiable for Claim {
+    fn public_input_bytes(&self) -> Vec<u8> {
+        let params = &self.mimc.params;
+        let mut seed = params.rounds.to_be_bytes().to_vec();
+        seed.extend_from_slice(&params.exponent.to_be_bytes());
+        for constant in &params.round_constants {
+            seed.extend_from_slice(&constant.as_montgomery().to_bytes_be());
+        }
+        seed.extend_from_slice(&self.before.as_montgomery().to_bytes_be());
+        seed.extend_from_slice(&self.after.as_montgomery().to_bytes_be());
+        seed
+    }
+
+    fn constraints(&self) -> Constraints {
+        use RationalExpression::*;
+
+        // Constraint repetitions
+        let trace_length = self.mimc.trace_length();
+        let trace_generator = FieldElement::root(trace_length).unwrap();
+        let g = Constant(trace_generator);
+        let on_row = |index| (X - g.pow(index)).inv();
+        let every_row = || (X - g.pow(trace_length - 1)) / (X.pow(trace_length) - 1);
+
+        // Interpolate the round constants over the trace rows
+        let mut constants = self.mimc.padded_constants();
+        constants.ifft();
+        permute(&mut constants);
+        let constant = Polynomial(DensePolynomial::new(&constants), Box::new(X));
+
+        Constraints::from_expressions((trace_length, 1), self.public_input_bytes(), vec![
+            (Trace(0, 1) - Exp(Box::new(Trace(0, 0) + constant), self.mimc.params.exponent))
+                * every_row(),
+            (Trace(0, 0) - &self.before) * on_row(0),
+            (Trace(0, 0) - &self.after) * on_row(self.mimc.params.rounds),
+        ])
+        .unwrap()
+    }
+}
+
+impl Provable<()> for Claim {
+    fn trace(&self, _witness: ()) -> TraceTable {
+        let trace_length = self.mimc.trace_length();
+        let mut trace = TraceTable::new(trace_length, 1);
+        let mut x = self.before.clone();
+        for (i, constant) in self.mimc.padded_constants().iter().enumerate() {
+            trace[(i, 0)] = x.clone();
+            x = self.mimc.round(&x, constant);
+        }
+        trace
+    }
+}
+
+fn example_params(rounds: usize) -> MimcParams {
+    MimcParams {
+        rounds,
+        round_constants: (0..rounds)
+            .map(|i| FieldElement::from(i).pow(7_usize) + FieldElement::from(42))
+            .collect(),
+        exponent: 3,
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    info!("Constructing claim");
+    let mimc = Mimc::new(example_params(82)).unwrap();
+    let before = field_element!("00a74f2a70da4ea3723cabd2acc55d03f9ff6d0e7acef0fc63263b12c10dd837");
+    let after = mimc.permute(&before);
+    let claim = Claim {
+        mimc,
+        before,
+        after,
+    };
+    assert_eq!(claim.check(()), Ok(()));
+
+    // Start timer
+    let start = Instant::now();
+
+    info!("Constructing proof...");
+    let proof = claim.prove(()).unwrap();
+
+    // Measure time
+    let duration = start.elapsed();
+    info!("Time elapsed in proof function is: {:?}", duration);
+    info!("The proof length is {}", proof.as_bytes().len());
+
+    info!("Verifying proof...");
+    claim.verify(&proof).expect("Verification failed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkp_primefield::One;
+
+    fn claim(rounds: usize) -> Claim {
+        let mimc = Mimc::new(example_params(rounds)).unwrap();
+        let before = FieldElement::from(1234);
+        let after = mimc.permute(&before);
+        Claim {
+            mimc,
+            before,
+            after,
+        }
+    }
+
+    #[test]
+    fn rejects_constant_count() {
+        let mut params = example_params(10);
+        let _ = params.round_constants.pop();
+        assert_eq!(
+            Mimc::new(params).unwrap_err(),
+            Error::RoundConstantsLength {
+                rounds:    10,
+                constants: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn prove_rounds() {
+        for rounds in &[10, 82] {
+            let claim = claim(*rounds);
+            assert_eq!(claim.check(()), Ok(()));
+            let proof = claim.prove(()).unwrap();
+            claim.verify(&proof).unwrap();
+
+            let mut wrong = claim.clone();
+            wrong.after += FieldElement::one();
+            assert!(wrong.verify(&proof).is_err());
+        }
+    }
+}
@@ -35,12 +35,14 @@ pub struct Claim {
 }
 
 impl Verifiable for Claim {
-    fn constraints(&self) -> Constraints {
-        use RationalExpression::*;
-
-        // Seed
+    fn public_input_bytes(&self) -> Vec<u8> {
         let mut seed = self.before.as_montgomery().to_bytes_be().to_vec();
         seed.extend_from_slice(&self.after.as_montgomery().to_bytes_be());
+        seed
+    }
+
+    fn constraints(&self) -> Constraints {
+        use RationalExpression::*;
 
         // Constraint repetitions
         let trace_length = ROUNDS;
@@ -60,7 +62,7 @@ impl Verifiable for Claim {
         permute(&mut k_coef);
         let k_coef = periodic(&k_coef);
 
-        Constraints::from_expressions((trace_length, 1), seed, vec![
+        Constraints::from_expressions((trace_length, 1), self.public_input_bytes(), vec![
             // Says the next row for each row is current x_0^alpha + k
             (Trace(0, 1) - (Exp(Box::new(Trace(0, 0)), ALPHA) + k_coef)) * every_row(),
             // Says the first x_0 is the before
@@ -147,13 +147,15 @@ pub struct Claim {
 }
 
 impl Verifiable for Claim {
-    fn constraints(&self) -> Constraints {
-        use RationalExpression::*;
-
-        // Seed
+    fn public_input_bytes(&self) -> Vec<u8> {
         let mut seed = self.before_x.as_montgomery().to_bytes_be().to_vec();
         seed.extend_from_slice(&self.before_y.as_montgomery().to_bytes_be());
         seed.extend_from_slice(&self.after.as_montgomery().to_bytes_be());
+        seed
+    }
+
+    fn constraints(&self) -> Constraints {
+        use RationalExpression::*;
 
         // Constraint repetitions
         let trace_length = 256;
@@ -202,7 +204,8 @@ impl Verifiable for Claim {
 
         let _public = vec![&const_before_x, &const_before_y, &const_after];
 
-        Constraints::from_expressions((trace_length, 2), seed, expressions).unwrap()
+        Constraints::from_expressions((trace_length, 2), self.public_input_bytes(), expressions)
+            .unwrap()
     }
 }
 
@@ -36,12 +36,14 @@ pub struct Claim {
 }
 
 impl Verifiable for Claim {
-    fn constraints(&self) -> Constraints {
-        use RationalExpression::*;
-
-        // Seed
+    fn public_input_bytes(&self) -> Vec<u8> {
         let mut seed = self.before.as_montgomery().to_bytes_be().to_vec();
         seed.extend_from_slice(&self.after.as_montgomery().to_bytes_be());
+        seed
+    }
+
+    fn constraints(&self) -> Constraints {
+        use RationalExpression::*;
 
         // Constraint repetitions
         let trace_length = ROUNDS;
@@ -61,7 +63,7 @@ impl Verifiable for Claim {
         permute(&mut k_coef);
         let k_coef = periodic(&k_coef);
 
-        Constraints::from_expressions((trace_length, 3), seed, vec![
+        Constraints::from_expressions((trace_length, 3), self.public_input_bytes(), vec![
             // Says x_1 = x_0^2
             (Trace(0, 0) * Trace(0, 0) - Trace(1, 0)) * every_row(),
             // Says x_2 = x_1*x_0
@@ -147,12 +147,14 @@ pub struct Claim {
 }
 
 impl Verifiable for Claim {
-    fn constraints(&self) -> Constraints {
-        use RationalExpression::*;
-
-        // Seed
+    fn public_input_bytes(&self) -> Vec<u8> {
         let mut seed = self.element.as_montgomery().to_bytes_be().to_vec();
         seed.extend_from_slice(&self.root.as_montgomery().to_bytes_be());
+        seed
+    }
+
+    fn constraints(&self) -> Constraints {
+        use RationalExpression::*;
 
         // Constraint repetitions
         let trace_length = 256 * 8192;
@@ -188,7 +190,7 @@ impl Verifiable for Claim {
                     - Constant(trace_generator.pow((trace_length / loop_len) * shift)))
         };
 
-        Constraints::from_expressions((trace_length, 2), seed, vec![
+        Constraints::from_expressions((trace_length, 2), self.public_input_bytes(), vec![
             ((Exp(Trace(0, 0).into(), 3)
                 + Constant(3.into()) * Constant(Q) * Trace(0, 0) * Exp(Trace(1, 0).into(), 2)
                 + k_coef)
@@ -0,0 +1,154 @@
+#![warn(clippy::all)]
+use log::info;
+use std::time::Instant;
+use zkp_macros_decl::field_element;
+use zkp_primefield::{FieldElement, Root};
+use zkp_stark::{Constraints, Provable, RationalExpression, TraceTable, Verifiable};
+use zkp_u256::U256;
+
+/// Claim to know a secret such that the Fibonacci-like sequence starting
+/// with `1, secret` has the given values at the given rows.
+#[derive(Clone, Debug)]
+struct MultiBoundaryClaim {
+    outputs: Vec<(usize, FieldElement)>,
+}
+
+#[derive(Clone, Debug)]
+struct Witness {
+    secret: FieldElement,
+}
+
+impl MultiBoundaryClaim {
+    fn trace_length(&self) -> usize {
+        let last_row = self.outputs.iter().map(|(row, _)| *row).max().unwrap_or(0);
+        std::cmp::max(last_row + 1, 2).next_power_of_two()
+    }
+}
+
+impl Verifiable for MultiBoundaryClaim {
+    fn public_input_bytes(&self) -> Vec<u8> {
+        let mut seed = Vec::with_capacity(self.outputs.len() * 40);
+        for (row, value) in &self.outputs {
+            seed.extend_from_slice(&row.to_be_bytes());
+            seed.extend_from_slice(&value.as_montgomery().to_bytes_be());
+        }
+        seed
+    }
+
+    fn constraints(&self) -> Constraints {
+        use RationalExpression::*;
+
+        // Constraint repetitions
+        let trace_length = self.trace_length();
+        let trace_generator = FieldElement::root(trace_length).unwrap();
+        let g = Constant(trace_generator);
+        let on_row = |index| (X - g.pow(index)).inv();
+        let every_row = || (X - g.pow(trace_length - 1)) / (X.pow(trace_length) - 1);
+
+        // One boundary constraint per claimed output
+        let mut expressions = vec![
+            (Trace(0, 1) - Trace(1, 0)) * every_row(),
+            (Trace(1, 1) - Trace(0, 0) - Trace(1, 0)) * every_row(),
+            (Trace(0, 0) - 1) * on_row(0),
+        ];
+        expressions.extend(
+            self.outputs
+                .iter()
+                .map(|(row, value)| (Trace(0, 0) - value) * on_row(*row)),
+        );
+        Constraints::from_expressions((trace_length, 2), self.public_input_bytes(), expressions)
+            .unwrap()
+    }
+}
+
+impl Provable<&Witness> for MultiBoundaryClaim {
+    fn trace(&self, witness: &Witness) -> TraceTable {
+        let trace_length = self.trace_length();
+        let mut trace = TraceTable::new(trace_length, 2);
+        trace[(0, 0)] = 1.into();
+        trace[(0, 1)] = witness.secret.clone();
+        for i in 0..(trace_length - 1) {
+            trace[(i + 1, 0)] = trace[(i, 1)].clone();
+            trace[(i + 1, 1)] = &trace[(i, 0)] + &trace[(i, 1)];
+        }
+        trace
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    info!("Constructing claim");
+    let claim = MultiBoundaryClaim {
+        outputs: vec![
+            (
+                10,
+                field_element!("0000000000000000000000000000000000000000000000000000002b9cba1ef4"),
+            ),
+            (
+                1000,
+                field_element!("0142c45e5d743d10eae7ebb70f1526c65de7dbcdb65b322b6ddc36a812591e8f"),
+            ),
+        ],
+    };
+    info!("Claim: {:?}", claim);
+
+    info!("Constructing witness");
+    let witness = Witness {
+        secret: field_element!("cafebabe"),
+    };
+    info!("Witness: {:?}", witness);
+
+    assert_eq!(claim.check(&witness), Ok(()));
+
+    // Start timer
+    let start = Instant::now();
+
+    info!("Constructing proof...");
+    let proof = claim.prove(&witness).unwrap();
+
+    // Measure time
+    let duration = start.elapsed();
+    info!("Time elapsed in proof function is: {:?}", duration);
+    info!("The proof length is {}", proof.as_bytes().len());
+
+    info!("Verifying proof...");
+    claim.verify(&proof).expect("Verification failed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkp_primefield::One;
+
+    fn claim_for_rows(rows: &[usize], witness: &Witness) -> MultiBoundaryClaim {
+        let placeholder = MultiBoundaryClaim {
+            outputs: rows.iter().map(|row| (*row, FieldElement::one())).collect(),
+        };
+        let trace = placeholder.trace(witness);
+        MultiBoundaryClaim {
+            outputs: rows
+                .iter()
+                .map(|row| (*row, trace[(*row, 0)].clone()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn three_outputs() {
+        let witness = Witness {
+            secret: field_element!("cafebabe"),
+        };
+        let claim = claim_for_rows(&[10, 100, 1000], &witness);
+        assert_eq!(claim.check(&witness), Ok(()));
+        let proof = claim.prove(&witness).unwrap();
+        claim.verify(&proof).unwrap();
+
+        for i in 0..claim.outputs.len() {
+            let mut wrong = claim.clone();
+            wrong.outputs[i].1 += FieldElement::one();
+            assert!(wrong.check(&witness).is_err());
+            assert!(wrong.verify(&proof).is_err());
+        }
+    }
+}
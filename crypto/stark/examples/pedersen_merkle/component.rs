@@ -1,6 +1,6 @@
 use super::inputs::{Claim, Witness};
 use crate::{
-    pedersen_points::{merkle_hash, PEDERSEN_POINTS, SHIFT_POINT},
+    pedersen_points::{pedersen_hash, PEDERSEN_POINTS, SHIFT_POINT},
     periodic_columns::{
         LEFT_X_COEFFICIENTS_REF, LEFT_Y_COEFFICIENTS_REF, RIGHT_X_COEFFICIENTS_REF,
         RIGHT_Y_COEFFICIENTS_REF,
@@ -10,7 +10,7 @@ use zkp_elliptic_curve::Affine;
 use zkp_primefield::{FieldElement, One, Pow, Root, Zero};
 use zkp_stark::{
     component::{Component, PolynomialWriter, Vertical},
-    DensePolynomial, RationalExpression,
+    DensePolynomial, RationalExpression, TraceTable,
 };
 use zkp_u256::{Binary, U256};
 
@@ -231,6 +231,11 @@ impl Component for MerkleTree {
 
     fn constraints(&self, claim: &Self::Claim) -> Vec<RationalExpression> {
         use RationalExpression::*;
+        assert_eq!(
+            claim.path_length,
+            self.layers.size(),
+            "Claim path length does not match the tree depth"
+        );
         let fake_claim = vec![(); self.layers.size()];
         let mut constraints = self.layers.constraints(&fake_claim);
 
@@ -279,21 +284,55 @@ impl Component for MerkleTree {
     }
 
     fn trace<P: PolynomialWriter>(&self, trace: &mut P, witness: &Self::Witness) {
-        let witness = witness
-            .path
-            .iter()
-            .scan(witness.leaf.clone(), |leaf, (direction, sibling)| {
-                let layer: <MerkleTreeLayer as Component>::Witness =
-                    (leaf.clone(), sibling.clone(), *direction);
-                *leaf = if *direction {
-                    merkle_hash(&sibling, &leaf)
-                } else {
-                    merkle_hash(&leaf, &sibling)
-                };
-                Some(layer)
-            })
-            .collect::<Vec<_>>();
-        self.layers.trace(trace, &witness)
+        for (i, chunk) in self.trace_chunks(witness).enumerate() {
+            let offset = i * chunk.num_rows();
+            for row in 0..chunk.num_rows() {
+                for column in 0..chunk.num_columns() {
+                    trace.write(column, offset + row, chunk[(row, column)].clone());
+                }
+            }
+        }
+    }
+}
+
+impl MerkleTree {
+    /// The trace one layer at a time.
+    ///
+    /// Item `i` equals rows `i * 256..(i + 1) * 256` of the full trace, so
+    /// only one layer's trace is held in memory at a time.
+    pub(crate) fn trace_chunks<'a>(&'a self, witness: &'a Witness) -> PedersenMerkleTraceIter<'a> {
+        PedersenMerkleTraceIter {
+            layer: self.layers.element(),
+            leaf:  witness.leaf.clone(),
+            path:  witness.path.iter(),
+        }
+    }
+}
+
+/// Iterator over the trace of a [`MerkleTree`], hashing the leaf up the path
+/// one layer at a time.
+pub(crate) struct PedersenMerkleTraceIter<'a> {
+    layer: &'a MerkleTreeLayer,
+    leaf:  FieldElement,
+    path:  std::slice::Iter<'a, (bool, FieldElement)>,
+}
+
+impl Iterator for PedersenMerkleTraceIter<'_> {
+    type Item = TraceTable;
+
+    fn next(&mut self) -> Option<TraceTable> {
+        let (direction, sibling) = self.path.next()?;
+        let witness = (self.leaf.clone(), sibling.clone(), *direction);
+        self.leaf = if *direction {
+            pedersen_hash(sibling, &self.leaf)
+        } else {
+            pedersen_hash(&self.leaf, sibling)
+        };
+        Some(self.layer.trace_table(&witness))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.path.size_hint()
     }
 }
 
@@ -302,12 +341,13 @@ mod test {
     use super::*;
     use crate::{
         inputs::{short_witness, SHORT_CLAIM},
-        pedersen_points::merkle_hash,
+        pedersen_points::pedersen_hash,
     };
     use proptest::{collection::vec as prop_vec, prelude::*};
     use zkp_macros_decl::field_element;
+    use zkp_merkle_tree::VectorCommitment;
     use zkp_primefield::FieldElement;
-    use zkp_stark::{prove, Constraints, TraceTable};
+    use zkp_stark::{check_constraints, prove, verify, Constraints};
     use zkp_u256::U256;
 
     // TODO: Move to TraceTable or RationalExpression?
@@ -344,9 +384,9 @@ mod test {
         let config = ProptestConfig::with_cases(10);
         proptest!(config, |(leaf: FieldElement, direction: bool, sibling: FieldElement)| {
             let hash = if direction {
-                merkle_hash(&sibling, &leaf)
+                pedersen_hash(&sibling, &leaf)
             } else {
-                merkle_hash(&leaf, &sibling)
+                pedersen_hash(&leaf, &sibling)
             };
             let component = MerkleTreeLayer::new();
             let witness = (leaf, sibling, direction);
@@ -395,6 +435,79 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_membership_proof() {
+        let leaf = field_element!("0bad");
+        let path = vec![
+            (false, field_element!("cafe")),
+            (true, field_element!("f00d")),
+            (true, field_element!("beef")),
+            (false, field_element!("dead")),
+        ];
+        let claim = Claim::new(leaf.clone(), &path);
+        let witness = Witness::new(leaf, path);
+        let component = MerkleTree::new(claim.path_length);
+        assert_eq!(component.claim(&witness), claim);
+        assert_eq!(component.check(&witness), Ok(()));
+
+        let trace = component.trace_table(&witness);
+        let mut constraints = Constraints::from_expressions(
+            (trace.num_rows(), trace.num_columns()),
+            (&claim).into(),
+            component.constraints(&claim),
+        )
+        .unwrap();
+        constraints.num_queries = 13;
+        constraints.fri_layout = vec![3, 2];
+        let proof = prove(&constraints, &trace).unwrap();
+        assert_eq!(verify(&constraints, &proof), Ok(()));
+    }
+
+    fn path(path_length: usize) -> Vec<(bool, FieldElement)> {
+        (0..path_length)
+            .map(|i| (i % 3 == 0, FieldElement::from(i + 100)))
+            .collect()
+    }
+
+    #[test]
+    fn test_path_lengths() {
+        for &path_length in &[8, 32] {
+            let witness = Witness::new(field_element!("0bad"), path(path_length));
+            let component = MerkleTree::new(path_length);
+            let (trace, constraints) = component.to_constraints(&witness);
+            assert_eq!(trace.num_rows(), path_length * 256);
+            assert_eq!(check_constraints(&constraints, &trace), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_trace_chunks() {
+        let witness = Witness::new(field_element!("0bad"), path(8));
+        let component = MerkleTree::new(8);
+        let trace = component.trace_table(&witness);
+        let (commitment, _) = trace.clone().commit().unwrap();
+
+        let mut chunk_roots = Vec::new();
+        for (i, chunk) in component.trace_chunks(&witness).enumerate() {
+            assert_eq!(chunk.num_rows(), 256);
+            for row in 0..256 {
+                assert_eq!(chunk[row], trace[i * 256 + row]);
+            }
+            let (chunk_commitment, _) = chunk.commit().unwrap();
+            chunk_roots.push(chunk_commitment.hash().clone());
+        }
+        assert_eq!(chunk_roots.len(), 8);
+        let (root, _) = chunk_roots.commit().unwrap();
+        assert_eq!(root.hash(), commitment.hash());
+    }
+
+    #[test]
+    #[should_panic(expected = "Claim path length does not match the tree depth")]
+    fn test_path_length_mismatch() {
+        let claim = Claim::new(field_element!("0bad"), &path(8));
+        let _ = MerkleTree::new(4).constraints(&claim);
+    }
+
     #[test]
     fn test_pedersen_merkle() {
         let config = ProptestConfig::with_cases(10);
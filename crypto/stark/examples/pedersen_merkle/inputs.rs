@@ -1,4 +1,4 @@
-use super::pedersen_points::merkle_hash;
+use super::pedersen_points::pedersen_hash;
 use std::{prelude::v1::*, vec};
 use zkp_primefield::FieldElement;
 
@@ -18,17 +18,38 @@ pub struct Witness {
     pub root: FieldElement,
 }
 
+/// Hash `leaf` up the `path` of (direction, sibling) pairs.
+///
+/// When the direction is `true` the sibling is on the left.
+fn path_root(leaf: &FieldElement, path: &[(bool, FieldElement)]) -> FieldElement {
+    path.iter()
+        .fold(leaf.clone(), |leaf, (direction, sibling)| {
+            if *direction {
+                pedersen_hash(sibling, &leaf)
+            } else {
+                pedersen_hash(&leaf, sibling)
+            }
+        })
+}
+
+impl Claim {
+    /// Claim that `leaf` is a member of the tree reached through `path`.
+    ///
+    /// The root is computed from the leaf and the path, the path itself is
+    /// part of the [`Witness`].
+    pub fn new(leaf: FieldElement, path: &[(bool, FieldElement)]) -> Claim {
+        let root = path_root(&leaf, path);
+        Claim {
+            path_length: path.len(),
+            leaf,
+            root,
+        }
+    }
+}
+
 impl Witness {
     pub fn new(leaf: FieldElement, path: Vec<(bool, FieldElement)>) -> Witness {
-        let root = path
-            .iter()
-            .fold(leaf.clone(), |leaf, (direction, sibling)| {
-                if *direction {
-                    merkle_hash(sibling, &leaf)
-                } else {
-                    merkle_hash(&leaf, sibling)
-                }
-            });
+        let root = path_root(&leaf, &path);
         Witness { leaf, path, root }
     }
 }
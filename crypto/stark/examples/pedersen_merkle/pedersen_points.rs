@@ -3,7 +3,12 @@ use zkp_macros_decl::field_element;
 use zkp_primefield::FieldElement;
 use zkp_u256::{Binary, U256};
 
-pub(crate) fn merkle_hash(left: &FieldElement, right: &FieldElement) -> FieldElement {
+/// Pedersen hash of a pair of field elements.
+///
+/// This is the hash enforced by the `MerkleTreeLayer` constraints: starting
+/// from `SHIFT_POINT`, the bits of `left` and `right` select which of the
+/// `PEDERSEN_POINTS` are added. Use it to compute the nodes of a witness path.
+pub(crate) fn pedersen_hash(left: &FieldElement, right: &FieldElement) -> FieldElement {
     let mut point = Jacobian::from(SHIFT_POINT);
     let mut left = U256::from(left);
     let mut right = U256::from(right);
@@ -103,6 +103,23 @@ pub(crate) fn starkware_witness() -> Witness {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pedersen_points::pedersen_hash;
+
+    #[test]
+    fn test_starkware_path() {
+        let witness = starkware_witness();
+        let root = witness
+            .path
+            .iter()
+            .fold(witness.leaf, |node, (direction, sibling)| {
+                if *direction {
+                    pedersen_hash(sibling, &node)
+                } else {
+                    pedersen_hash(&node, sibling)
+                }
+            });
+        assert_eq!(root, STARKWARE_CLAIM.root);
+    }
 
     #[test]
     #[ignore] // Expensive test to run
@@ -17,22 +17,32 @@ struct Witness {
     secret: FieldElement,
 }
 
-impl Verifiable for Claim {
-    fn constraints(&self) -> Constraints {
-        use RationalExpression::*;
+impl Claim {
+    /// The trace needs a row for `index` and at least two rows for the
+    /// recurrence.
+    fn trace_length(&self) -> usize {
+        std::cmp::max(self.index + 1, 2).next_power_of_two()
+    }
+}
 
-        // Seed
+impl Verifiable for Claim {
+    fn public_input_bytes(&self) -> Vec<u8> {
         let mut seed = self.index.to_be_bytes().to_vec();
         seed.extend_from_slice(&self.value.as_montgomery().to_bytes_be());
+        seed
+    }
+
+    fn constraints(&self) -> Constraints {
+        use RationalExpression::*;
 
         // Constraint repetitions
-        let trace_length = self.index.next_power_of_two();
+        let trace_length = self.trace_length();
         let trace_generator = FieldElement::root(trace_length).unwrap();
         let g = Constant(trace_generator);
         let on_row = |index| (X - g.pow(index)).inv();
         let every_row = || (X - g.pow(trace_length - 1)) / (X.pow(trace_length) - 1);
 
-        Constraints::from_expressions((trace_length, 2), seed, vec![
+        Constraints::from_expressions((trace_length, 2), self.public_input_bytes(), vec![
             (Trace(0, 1) - Trace(1, 0)) * every_row(),
             (Trace(1, 1) - Trace(0, 0) - Trace(1, 0)) * every_row(),
             (Trace(0, 0) - 1) * on_row(0),
@@ -44,7 +54,7 @@ impl Verifiable for Claim {
 
 impl Provable<&Witness> for Claim {
     fn trace(&self, witness: &Witness) -> TraceTable {
-        let trace_length = self.index.next_power_of_two();
+        let trace_length = self.trace_length();
         let mut trace = TraceTable::new(trace_length, 2);
         trace[(0, 0)] = 1.into();
         trace[(0, 1)] = witness.secret.clone();
@@ -84,3 +94,46 @@ fn main() {
     let duration = start.elapsed();
     info!("Time elapsed in proof function is: {:?}", duration);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkp_primefield::One;
+
+    fn witness() -> Witness {
+        Witness {
+            secret: field_element!("cafebabe"),
+        }
+    }
+
+    fn claim(index: usize) -> Claim {
+        let placeholder = Claim {
+            index,
+            value: FieldElement::one(),
+        };
+        Claim {
+            index,
+            value: placeholder.trace(&witness())[(index, 0)].clone(),
+        }
+    }
+
+    #[test]
+    fn prove_indices() {
+        for index in &[1, 8, 1000] {
+            let claim = claim(*index);
+            assert_eq!(claim.check(&witness()), Ok(()));
+            let proof = claim.prove(&witness()).unwrap();
+            claim.verify(&proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn wrong_value() {
+        let claim = claim(8);
+        let proof = claim.prove(&witness()).unwrap();
+        let mut wrong = claim;
+        wrong.value += FieldElement::one();
+        assert!(wrong.check(&witness()).is_err());
+        assert!(wrong.verify(&proof).is_err());
+    }
+}
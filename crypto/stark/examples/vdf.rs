@@ -17,14 +17,16 @@ struct Claim {
 }
 
 impl Verifiable for Claim {
-    fn constraints(&self) -> Constraints {
-        use RationalExpression::*;
-
-        // Seed
+    fn public_input_bytes(&self) -> Vec<u8> {
         let mut seed = self.c0_start.as_montgomery().to_bytes_be().to_vec();
         seed.extend_from_slice(&self.c1_start.as_montgomery().to_bytes_be());
         seed.extend_from_slice(&self.c0_end.as_montgomery().to_bytes_be());
         seed.extend_from_slice(&self.c1_end.as_montgomery().to_bytes_be());
+        seed
+    }
+
+    fn constraints(&self) -> Constraints {
+        use RationalExpression::*;
 
         // Constraint repetitions
         let trace_length = 1_048_576;
@@ -33,7 +35,7 @@ impl Verifiable for Claim {
         let on_row = |index| (X - g.pow(index)).inv();
         let every_row = || (X - g.pow(trace_length - 1)) / (X.pow(trace_length) - 1);
 
-        Constraints::from_expressions((trace_length, 4), seed, vec![
+        Constraints::from_expressions((trace_length, 4), self.public_input_bytes(), vec![
             // Square (Trace(0,0), Trace(1, 0)) and check that it equals (Trace(2,0),
             // Trace(3,0))
             ((Trace(0, 0) * Trace(0, 0) + Constant(R) * Trace(1, 0) * Trace(1, 0) - Trace(2, 0))
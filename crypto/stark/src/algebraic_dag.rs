@@ -2,7 +2,7 @@ use crate::{
     polynomial::DensePolynomial, rational_expression::RationalExpression, trace_table::TraceTable,
 };
 use log::info;
-use std::{cmp::min, ops::Neg, prelude::v1::*};
+use std::{cmp::min, collections::BTreeMap, ops::Neg, prelude::v1::*};
 use tiny_keccak::{Hasher, Keccak};
 use zkp_macros_decl::field_element;
 use zkp_primefield::{invert_batch_src_dst, FieldElement, Inv, One, Pow, Root, Zero};
@@ -341,6 +341,9 @@ impl AlgebraicGraph {
                 self.op(Op::Poly(p, a))
             }
             RE::ClaimPolynomial(..) => panic!("ClaimPolynomial not supported"),
+            RE::Periodic(coefficients, repetitions) => {
+                self.expression(RE::periodic_as_polynomial(&coefficients, repetitions))
+            }
             RE::Add(a, b) => {
                 let a = self.expression(*a);
                 let b = self.expression(*b);
@@ -582,9 +585,195 @@ impl AlgebraicGraph {
     }
 }
 
+/// Constraint expressions with common subexpressions shared.
+///
+/// Structurally identical subexpressions are stored once, so they are only
+/// evaluated once. Unlike [`AlgebraicGraph`] this evaluates in a single point
+/// and does no algebraic simplification. Create it using [`build`].
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Dag {
+    /// Nodes in causal order, each only refers to earlier nodes.
+    nodes: Vec<DagNode>,
+
+    /// Position of each node in `nodes` by its [`DagNode::key`].
+    index: BTreeMap<[u8; 32], usize>,
+
+    /// Node for each of the input expressions.
+    outputs: Vec<usize>,
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+enum DagNode {
+    X,
+    Constant(FieldElement),
+    Trace(usize, isize),
+    Polynomial(DensePolynomial, usize),
+    Add(usize, usize),
+    Neg(usize),
+    Mul(usize, usize),
+    Inv(usize),
+    Exp(usize, usize),
+}
+
+impl DagNode {
+    /// Keccak hash of the operation, its data and its operand indices.
+    ///
+    /// Operands are inserted before the node that uses them, so structurally
+    /// identical subexpressions end up with the same key.
+    fn key(&self) -> [u8; 32] {
+        use DagNode::*;
+        let mut keccak = Keccak::v256();
+        match self {
+            X => keccak.update(&[0]),
+            Constant(c) => {
+                keccak.update(&[1]);
+                keccak.update(&c.to_uint().to_bytes_be());
+            }
+            &Trace(i, j) => {
+                keccak.update(&[2]);
+                keccak.update(&(i as u64).to_be_bytes());
+                keccak.update(&(j as i64).to_be_bytes());
+            }
+            Polynomial(p, a) => {
+                keccak.update(&[3]);
+                keccak.update(&(*a as u64).to_be_bytes());
+                keccak.update(&(p.coefficients().len() as u64).to_be_bytes());
+                for coefficient in p.coefficients() {
+                    keccak.update(&coefficient.to_uint().to_bytes_be());
+                }
+            }
+            &Add(a, b) => {
+                keccak.update(&[4]);
+                keccak.update(&(a as u64).to_be_bytes());
+                keccak.update(&(b as u64).to_be_bytes());
+            }
+            &Neg(a) => {
+                keccak.update(&[5]);
+                keccak.update(&(a as u64).to_be_bytes());
+            }
+            &Mul(a, b) => {
+                keccak.update(&[6]);
+                keccak.update(&(a as u64).to_be_bytes());
+                keccak.update(&(b as u64).to_be_bytes());
+            }
+            &Inv(a) => {
+                keccak.update(&[7]);
+                keccak.update(&(a as u64).to_be_bytes());
+            }
+            &Exp(a, e) => {
+                keccak.update(&[8]);
+                keccak.update(&(a as u64).to_be_bytes());
+                keccak.update(&(e as u64).to_be_bytes());
+            }
+        }
+        let mut key = [0; 32];
+        keccak.finalize(&mut key);
+        key
+    }
+}
+
+/// Eliminate common subexpressions from `expressions`.
+///
+/// # Panics
+///
+/// Panics if the expressions contain a `ClaimPolynomial`, these need to be
+/// substituted first.
+pub fn build(expressions: &[RationalExpression]) -> Dag {
+    let mut dag = Dag {
+        nodes:   vec![],
+        index:   BTreeMap::new(),
+        outputs: vec![],
+    };
+    for expression in expressions {
+        let output = dag.insert(expression);
+        dag.outputs.push(output);
+    }
+    dag
+}
+
+impl Dag {
+    /// Number of distinct nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Number of field multiplications done by [`evaluate`](Self::evaluate),
+    /// not counting those in exponentiations and polynomials.
+    pub fn multiplications(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| matches!(node, DagNode::Mul(..)))
+            .count()
+    }
+
+    /// Evaluate all expressions in `x`, in the order they were given to
+    /// [`build`].
+    pub fn evaluate(
+        &self,
+        x: &FieldElement,
+        trace: &dyn Fn(usize, isize) -> FieldElement,
+    ) -> Vec<FieldElement> {
+        use DagNode::*;
+        let mut values: Vec<FieldElement> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let value = match node {
+                X => x.clone(),
+                Constant(c) => c.clone(),
+                &Trace(i, j) => trace(i, j),
+                Polynomial(p, a) => p.evaluate(&values[*a]),
+                Add(a, b) => &values[*a] + &values[*b],
+                Neg(a) => -&values[*a],
+                Mul(a, b) => &values[*a] * &values[*b],
+                Inv(a) => values[*a].inv().expect("divided by zero"),
+                Exp(a, e) => values[*a].pow(*e),
+            };
+            values.push(value);
+        }
+        self.outputs.iter().map(|&i| values[i].clone()).collect()
+    }
+
+    /// Add the node, or find an identical existing one, and return its index.
+    fn op(&mut self, node: DagNode) -> usize {
+        let nodes = &mut self.nodes;
+        *self.index.entry(node.key()).or_insert_with(|| {
+            nodes.push(node);
+            nodes.len() - 1
+        })
+    }
+
+    fn insert(&mut self, expression: &RationalExpression) -> usize {
+        use RationalExpression as RE;
+        let node = match expression {
+            RE::X => DagNode::X,
+            RE::Constant(c) => DagNode::Constant(c.clone()),
+            &RE::Trace(i, j) => DagNode::Trace(i, j),
+            RE::Polynomial(p, a) => DagNode::Polynomial(p.clone(), self.insert(a)),
+            RE::ClaimPolynomial(..) => {
+                panic!("ClaimPolynomial should be substituted by Polynomial")
+            }
+            RE::Periodic(coefficients, repetitions) => {
+                return self.insert(&RE::periodic_as_polynomial(coefficients, *repetitions));
+            }
+            RE::Add(a, b) => DagNode::Add(self.insert(a), self.insert(b)),
+            RE::Neg(a) => DagNode::Neg(self.insert(a)),
+            RE::Mul(a, b) => DagNode::Mul(self.insert(a), self.insert(b)),
+            RE::Inv(a) => DagNode::Inv(self.insert(a)),
+            RE::Exp(a, e) => DagNode::Exp(self.insert(a), *e),
+        };
+        self.op(node)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     use zkp_macros_decl::field_element;
     use Operation as Op;
     use RationalExpression as RE;
@@ -627,4 +816,39 @@ mod tests {
         let index = dag.expression(RE::X);
         assert_eq!(dag[index].hash, dag.seed);
     }
+
+    proptest!(
+        #[test]
+        fn dag_matches_expressions(x: FieldElement, a: FieldElement) {
+            use RationalExpression::*;
+            prop_assume!(x != FieldElement::from(3));
+            let shared = (Trace(0, 0) - Trace(1, 0)) * (X - 3).inv();
+            let expressions = vec![
+                shared.clone() * Trace(0, 1),
+                shared.clone() * shared.clone() - &a,
+                (shared * X).pow(3) + Trace(1, -1) * X,
+                X,
+            ];
+            let trace =
+                |i: usize, j: isize| &x * FieldElement::from(i) + FieldElement::from(j) + &a;
+            let dag = build(&expressions);
+            let expected = expressions
+                .iter()
+                .map(|expression| expression.evaluate(&x, &trace))
+                .collect::<Vec<_>>();
+            prop_assert_eq!(dag.evaluate(&x, &trace), expected);
+
+            let naive: usize = expressions
+                .iter()
+                .map(|expression| {
+                    expression.fold(&mut |node, children: &[usize]| {
+                        let own = if let Mul(..) = node { 1 } else { 0 };
+                        own + children.iter().sum::<usize>()
+                    })
+                })
+                .sum();
+            prop_assert_eq!(naive, 8);
+            prop_assert_eq!(dag.multiplications(), 5);
+        }
+    );
 }
@@ -1,9 +1,8 @@
 // TODO: Naming?
 #![allow(clippy::module_name_repetitions)]
 use crate::proof_of_work;
-use std::{convert::TryInto, prelude::v1::*};
-use tiny_keccak::{Hasher, Keccak};
-use zkp_hash::Hash;
+use std::{convert::TryInto, fmt, marker::PhantomData, prelude::v1::*};
+use zkp_hash::{Hash, Hasher, Keccak256};
 use zkp_macros_decl::u256h;
 use zkp_primefield::FieldElement;
 use zkp_u256::U256;
@@ -17,43 +16,257 @@ pub(crate) trait Writable<T> {
 }
 
 pub(crate) trait Replayable<T> {
-    fn replay(&mut self) -> T;
+    fn replay(&mut self) -> Result<T, Error>;
 
-    fn replay_many(&mut self, count: usize) -> Vec<T> {
+    fn replay_many(&mut self, count: usize) -> Result<Vec<T>, Error> {
         (0..count).map(|_| self.replay()).collect()
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Error {
+    /// The proof ended before all values could be read.
+    UnexpectedEof { requested: usize, remaining: usize },
+    /// The nonce in the proof does not solve the proof of work.
+    InvalidProofOfWork { nonce: u64 },
+    /// Reading the proof from an `io::Read` failed.
+    #[cfg(feature = "std")]
+    Io(std::io::ErrorKind),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+        match *self {
+            UnexpectedEof {
+                requested,
+                remaining,
+            } => {
+                write!(
+                    f,
+                    "Proof ended early: {} bytes requested but only {} remaining",
+                    requested, remaining
+                )
+            }
+            InvalidProofOfWork { nonce } => {
+                write!(f, "Nonce {} does not solve the proof of work", nonce)
+            }
+            #[cfg(feature = "std")]
+            Io(kind) => write!(f, "Reading the proof failed: {:?}", kind),
+        }
+    }
+}
+
+/// An entry in the Fiat-Shamir transcript.
+///
+/// Recording is enabled using `with_trace()` on the prover and verifier
+/// channels. Comparing the prover's and the verifier's transcript shows the
+/// first place where they diverge.
+#[derive(PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum TranscriptEvent {
+    /// The public coin was seeded with the given bytes.
+    Seed(Vec<u8>),
+    /// The given bytes were absorbed into the public coin.
+    Absorb(Vec<u8>),
+    /// The given random bytes were drawn from the public coin.
+    Squeeze([u8; 32]),
+}
+
+/// Protocol phase of the values absorbed next.
+///
+/// With domain separation enabled the tag is absorbed before the values, so
+/// the same values give different challenges in different phases.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub(crate) enum ChannelTag {
+    TraceRoot      = 1,
+    ConstraintRoot = 2,
+    OodsValues     = 3,
+    FriLayer       = 4,
+    Pow            = 5,
+}
+
 // TODO: Limit to crate
-#[derive(PartialEq, Eq, Clone, Default)]
+/// Fiat-Shamir public coin hashing with `H`.
+#[derive(Clone, Default)]
 #[cfg_attr(feature = "std", derive(Debug))]
-pub(crate) struct PublicCoin {
+pub(crate) struct PublicCoin<H: Hasher = Keccak256> {
     pub(crate) digest: [u8; 32],
     counter:           u64,
+    transcript:        Option<Vec<TranscriptEvent>>,
+    domain_separation: bool,
+    hasher:            PhantomData<H>,
 }
 
-#[derive(PartialEq, Eq, Clone, Default)]
+#[derive(Clone)]
 #[cfg_attr(feature = "std", derive(Debug))]
-pub(crate) struct ProverChannel {
-    pub(crate) coin:  PublicCoin,
+pub(crate) struct ProverChannel<H: Hasher = Keccak256> {
+    pub(crate) coin:  PublicCoin<H>,
     pub(crate) proof: Vec<u8>,
 }
 
-#[derive(PartialEq, Eq, Clone, Default)]
+#[derive(Clone)]
 #[cfg_attr(feature = "std", derive(Debug))]
-pub(crate) struct VerifierChannel {
-    pub(crate) coin:  PublicCoin,
-    pub(crate) proof: Vec<u8>,
-    proof_index:      usize,
+pub(crate) struct VerifierChannel<H: Hasher = Keccak256, S: ProofSource = ProofBytes> {
+    pub(crate) coin: PublicCoin<H>,
+    source:          S,
 }
 
-impl PublicCoin {
+/// Source of the proof bytes read by a [`VerifierChannel`].
+pub(crate) trait ProofSource {
+    /// Fill `buffer` with the next bytes of the proof.
+    fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), Error>;
+
+    /// Whether all bytes of the proof have been read.
+    fn at_end(&mut self) -> Result<bool, Error>;
+}
+
+/// A proof held in memory.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub(crate) struct ProofBytes {
+    bytes: Vec<u8>,
+    index: usize,
+}
+
+impl ProofBytes {
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.index
+    }
+}
+
+impl ProofSource for ProofBytes {
+    fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), Error> {
+        if buffer.len() > self.remaining() {
+            return Err(Error::UnexpectedEof {
+                requested: buffer.len(),
+                remaining: self.remaining(),
+            });
+        }
+        let from = self.index;
+        self.index += buffer.len();
+        buffer.copy_from_slice(&self.bytes[from..self.index]);
+        Ok(())
+    }
+
+    fn at_end(&mut self) -> Result<bool, Error> {
+        Ok(self.remaining() == 0)
+    }
+}
+
+/// A proof pulled from an `io::Read` as the verifier needs it.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub(crate) struct ProofReader<R: std::io::Read> {
+    reader: R,
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> ProofSource for ProofReader<R> {
+    // On `UnexpectedEof` the bytes before the end have been consumed, and
+    // `remaining` is their number.
+    fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), Error> {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match self.reader.read(&mut buffer[filled..]) {
+                Ok(0) => {
+                    return Err(Error::UnexpectedEof {
+                        requested: buffer.len(),
+                        remaining: filled,
+                    })
+                }
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(Error::Io(e.kind())),
+            }
+        }
+        Ok(())
+    }
+
+    // Reads one byte ahead, so nothing should be read afterwards.
+    fn at_end(&mut self) -> Result<bool, Error> {
+        match self.read_exact(&mut [0_u8; 1]) {
+            Ok(()) => Ok(false),
+            Err(Error::UnexpectedEof { .. }) => Ok(true),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+// Implemented by hand because deriving would require `H: PartialEq`. The
+// hasher is only a marker, so equality is that of the coin state.
+impl<H: Hasher> PartialEq for PublicCoin<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.digest == other.digest
+            && self.counter == other.counter
+            && self.transcript == other.transcript
+            && self.domain_separation == other.domain_separation
+    }
+}
+
+impl<H: Hasher> Eq for PublicCoin<H> {}
+
+impl<H: Hasher> PartialEq for ProverChannel<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.coin == other.coin && self.proof == other.proof
+    }
+}
+
+impl<H: Hasher> Eq for ProverChannel<H> {}
+
+impl<H: Hasher, S: ProofSource + PartialEq> PartialEq for VerifierChannel<H, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.coin == other.coin && self.source == other.source
+    }
+}
+
+impl<H: Hasher, S: ProofSource + Eq> Eq for VerifierChannel<H, S> {}
+
+impl<H: Hasher> PublicCoin<H> {
     pub(crate) fn seed(&mut self, seed: &[u8]) {
-        let mut keccak = Keccak::v256();
-        keccak.update(seed);
-        keccak.finalize(&mut self.digest);
+        self.record(|| TranscriptEvent::Seed(seed.to_vec()));
+        self.digest = Self::hash(&[seed]);
         self.counter = 0;
     }
+
+    /// Hash the concatenation of `parts` with `H`.
+    fn hash(parts: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = H::default();
+        for part in parts {
+            hasher.update(part);
+        }
+        let mut result = [0; 32];
+        result.copy_from_slice(hasher.hash().as_bytes());
+        result
+    }
+
+    fn enable_trace(&mut self) {
+        self.transcript = Some(Vec::new());
+    }
+
+    /// Absorb `tag` if domain separation is enabled.
+    pub(crate) fn tag(&mut self, tag: ChannelTag) {
+        if self.domain_separation {
+            self.write(&[tag as u8][..]);
+        }
+    }
+
+    fn transcript(&self) -> &[TranscriptEvent] {
+        self.transcript.as_deref().unwrap_or(&[])
+    }
+
+    fn record(&mut self, event: impl FnOnce() -> TranscriptEvent) {
+        if let Some(transcript) = &mut self.transcript {
+            transcript.push(event());
+        }
+    }
+}
+
+impl Default for ProverChannel {
+    fn default() -> Self {
+        Self::from(Vec::new())
+    }
 }
 
 impl From<Vec<u8>> for ProverChannel {
@@ -66,18 +279,111 @@ impl From<Vec<u8>> for ProverChannel {
 }
 
 #[cfg(feature = "prover")]
-impl ProverChannel {
+impl<H: Hasher> ProverChannel<H> {
+    /// An empty channel whose public coin hashes with `H`.
+    pub(crate) fn with_hasher() -> Self {
+        Self {
+            coin:  PublicCoin::default(),
+            proof: Vec::new(),
+        }
+    }
+
     pub(crate) fn initialize(&mut self, seed: &[u8]) {
         self.coin.seed(seed);
     }
+
+    /// Record all absorbed values and drawn challenges.
+    pub(crate) fn with_trace(mut self) -> Self {
+        self.coin.enable_trace();
+        self
+    }
+
+    pub(crate) fn transcript(&self) -> &[TranscriptEvent] {
+        self.coin.transcript()
+    }
+
+    /// Absorb a tag before the values of each protocol phase.
+    pub(crate) fn set_domain_separation(&mut self, enabled: bool) {
+        self.coin.domain_separation = enabled;
+    }
+
+    pub(crate) fn tag(&mut self, tag: ChannelTag) {
+        self.coin.tag(tag);
+    }
+
+    /// Remove and return the proof bytes written so far.
+    ///
+    /// The public coin is unaffected, so writing can continue as normal.
+    pub(crate) fn take_proof(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.proof)
+    }
+
+    /// Solve a proof of work of `bits` bits and write the nonce.
+    ///
+    /// The challenge is the current digest of the public coin, which commits
+    /// to everything written to the channel so far.
+    pub(crate) fn grind_pow(&mut self, bits: usize) -> u64 {
+        let seed: proof_of_work::ChallengeSeed = self.get_random();
+        let challenge = seed.with_difficulty(bits);
+        let response = challenge.solve();
+        debug_assert!(challenge.verify(response));
+        self.tag(ChannelTag::Pow);
+        self.write(response);
+        response.nonce()
+    }
 }
 
 impl VerifierChannel {
     pub(crate) fn new(proof: Vec<u8>) -> Self {
+        Self::with_hasher(proof)
+    }
+}
+
+impl<H: Hasher> VerifierChannel<H> {
+    /// A channel reading `proof` whose public coin hashes with `H`.
+    pub(crate) fn with_hasher(proof: Vec<u8>) -> Self {
         Self {
-            coin: PublicCoin::default(),
-            proof,
-            proof_index: 0,
+            coin:   PublicCoin::default(),
+            source: ProofBytes {
+                bytes: proof,
+                index: 0,
+            },
+        }
+    }
+
+    /// Number of proof bytes not read yet.
+    pub(crate) fn remaining(&self) -> usize {
+        self.source.remaining()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: Hasher, R: std::io::Read> VerifierChannel<H, ProofReader<R>> {
+    /// A channel reading the proof from `reader` as it is needed.
+    ///
+    /// Reads are not buffered, so readers like files should be wrapped in a
+    /// `BufReader`.
+    pub(crate) fn from_reader(reader: R) -> Self {
+        Self {
+            coin:   PublicCoin::default(),
+            source: ProofReader { reader },
+        }
+    }
+}
+
+impl<H: Hasher, S: ProofSource> VerifierChannel<H, S> {
+    /// Read the nonce written by `ProverChannel::grind_pow` and check it.
+    pub(crate) fn verify_pow(&mut self, bits: usize) -> Result<u64, Error> {
+        let seed: proof_of_work::ChallengeSeed = self.get_random();
+        let challenge = seed.with_difficulty(bits);
+        self.tag(ChannelTag::Pow);
+        let response: proof_of_work::Response = self.replay()?;
+        if challenge.verify(response) {
+            Ok(response.nonce())
+        } else {
+            Err(Error::InvalidProofOfWork {
+                nonce: response.nonce(),
+            })
         }
     }
 
@@ -85,8 +391,38 @@ impl VerifierChannel {
         self.coin.seed(seed);
     }
 
-    pub(crate) fn at_end(self) -> bool {
-        self.proof_index == self.proof.len()
+    /// Record all absorbed values and drawn challenges.
+    pub(crate) fn with_trace(mut self) -> Self {
+        self.coin.enable_trace();
+        self
+    }
+
+    pub(crate) fn transcript(&self) -> &[TranscriptEvent] {
+        self.coin.transcript()
+    }
+
+    /// Absorb a tag before the values of each protocol phase.
+    pub(crate) fn set_domain_separation(&mut self, enabled: bool) {
+        self.coin.domain_separation = enabled;
+    }
+
+    pub(crate) fn tag(&mut self, tag: ChannelTag) {
+        self.coin.tag(tag);
+    }
+
+    pub(crate) fn at_end(&mut self) -> Result<bool, Error> {
+        self.source.at_end()
+    }
+
+    /// Fill `buffer` with the next proof bytes and absorb them in the coin.
+    pub(crate) fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), Error> {
+        self.source.read_exact(buffer)?;
+        self.coin.write(&buffer[..]);
+        Ok(())
+    }
+
+    pub(crate) fn read_field(&mut self) -> Result<FieldElement, Error> {
+        self.replay()
     }
 
     pub(crate) fn get_coefficients(&mut self, n: usize) -> Vec<FieldElement> {
@@ -96,61 +432,50 @@ impl VerifierChannel {
     // This differs from Replayable::<FieldElement>::replay_many in that it only
     // updates the public coin once, with the contents of the entire layer, instead
     // of onces for each FieldElement in the layer.
-    pub(crate) fn replay_fri_layer(&mut self, size: usize) -> Vec<FieldElement> {
-        let start_index = self.proof_index;
-        self.proof_index += 32 * size;
-        let layer_contents = &self.proof[start_index..self.proof_index];
-
-        self.coin.write(layer_contents);
-
-        layer_contents
+    pub(crate) fn replay_fri_layer(&mut self, size: usize) -> Result<Vec<FieldElement>, Error> {
+        let mut layer_contents = vec![0_u8; 32 * size];
+        self.read_bytes(&mut layer_contents)?;
+        Ok(layer_contents
             .chunks_exact(32)
             .map(|bytes| {
                 FieldElement::from_montgomery(U256::from_bytes_be(bytes.try_into().unwrap()))
             })
-            .collect()
+            .collect())
     }
 
-    fn read_32_bytes(&mut self) -> [u8; 32] {
+    fn read_32_bytes(&mut self) -> Result<[u8; 32], Error> {
         let mut holder = [0_u8; 32];
-        let from = self.proof_index;
-        let to = from + 32;
-        self.proof_index = to;
-        // OPT: Use arrayref crate or similar to avoid copy
-        holder.copy_from_slice(&self.proof[from..to]);
-        self.coin.write(&holder[..]);
-        holder
+        self.read_bytes(&mut holder)?;
+        Ok(holder)
     }
 }
 
-impl RandomGenerator<proof_of_work::ChallengeSeed> for PublicCoin {
+impl<H: Hasher> RandomGenerator<proof_of_work::ChallengeSeed> for PublicCoin<H> {
     fn get_random(&mut self) -> proof_of_work::ChallengeSeed {
+        let digest = self.digest;
+        self.record(|| TranscriptEvent::Squeeze(digest));
         self.counter += 1;
         // FIX: Use get_random::<[u8;32]>();
         proof_of_work::ChallengeSeed::from_bytes(self.digest)
     }
 }
 
-impl Writable<proof_of_work::Response> for ProverChannel {
+impl<H: Hasher> Writable<proof_of_work::Response> for ProverChannel<H> {
     fn write(&mut self, data: proof_of_work::Response) {
         self.write(&data.nonce().to_be_bytes()[..]);
     }
 }
 
-impl Replayable<proof_of_work::Response> for VerifierChannel {
-    fn replay(&mut self) -> proof_of_work::Response {
+impl<H: Hasher, S: ProofSource> Replayable<proof_of_work::Response> for VerifierChannel<H, S> {
+    fn replay(&mut self) -> Result<proof_of_work::Response, Error> {
         let mut holder = [0_u8; 8];
-        let from = self.proof_index;
-        let to = from + 8;
-        self.proof_index = to;
-        holder.copy_from_slice(&self.proof[from..to]);
-        self.coin.write(&holder[..]);
+        self.read_bytes(&mut holder)?;
         let nonce = u64::from_be_bytes(holder);
-        proof_of_work::Response::from_nonce(nonce)
+        Ok(proof_of_work::Response::from_nonce(nonce))
     }
 }
 
-impl RandomGenerator<FieldElement> for PublicCoin {
+impl<H: Hasher> RandomGenerator<FieldElement> for PublicCoin<H> {
     fn get_random(&mut self) -> FieldElement {
         const MASK: U256 =
             u256h!("0FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF");
@@ -164,52 +489,44 @@ impl RandomGenerator<FieldElement> for PublicCoin {
     }
 }
 
-impl RandomGenerator<U256> for PublicCoin {
+impl<H: Hasher> RandomGenerator<U256> for PublicCoin<H> {
     fn get_random(&mut self) -> U256 {
         U256::from_bytes_be(&self.get_random())
     }
 }
 
-impl RandomGenerator<[u8; 32]> for PublicCoin {
+impl<H: Hasher> RandomGenerator<[u8; 32]> for PublicCoin<H> {
     fn get_random(&mut self) -> [u8; 32] {
-        let mut result = [0; 32];
-        let mut keccak = Keccak::v256();
-        keccak.update(&self.digest);
-        keccak.update(&[0_u8; 24]);
-        keccak.update(&self.counter.to_be_bytes());
-        keccak.finalize(&mut result);
+        let result = Self::hash(&[&self.digest, &[0_u8; 24], &self.counter.to_be_bytes()]);
         self.counter += 1;
+        self.record(|| TranscriptEvent::Squeeze(result));
         result
     }
 }
 
-impl<T> RandomGenerator<T> for ProverChannel
+impl<T, H: Hasher> RandomGenerator<T> for ProverChannel<H>
 where
-    PublicCoin: RandomGenerator<T>,
+    PublicCoin<H>: RandomGenerator<T>,
 {
     fn get_random(&mut self) -> T {
         self.coin.get_random()
     }
 }
 
-impl<T> RandomGenerator<T> for VerifierChannel
+impl<T, H: Hasher, S: ProofSource> RandomGenerator<T> for VerifierChannel<H, S>
 where
-    PublicCoin: RandomGenerator<T>,
+    PublicCoin<H>: RandomGenerator<T>,
 {
     fn get_random(&mut self) -> T {
         self.coin.get_random()
     }
 }
 
-impl Writable<&[u8]> for PublicCoin {
+impl<H: Hasher> Writable<&[u8]> for PublicCoin<H> {
     fn write(&mut self, data: &[u8]) {
-        let mut result: [u8; 32] = [0; 32];
-        let mut keccak = Keccak::v256();
-        keccak.update(&self.digest);
-        keccak.update(data);
-        keccak.finalize(&mut result);
+        self.record(|| TranscriptEvent::Absorb(data.to_vec()));
         // FIX: Hash counter into digest.
-        self.digest = result;
+        self.digest = Self::hash(&[&self.digest, data]);
         self.counter = 0;
     }
 }
@@ -218,26 +535,26 @@ impl Writable<&[u8]> for PublicCoin {
 // the proof with the same encoding for the writing and the non writing. However
 // by writing directly to the coin, other writes for the channel could separate
 // encoding from random perturbation.
-impl Writable<&[u8]> for ProverChannel {
+impl<H: Hasher> Writable<&[u8]> for ProverChannel<H> {
     fn write(&mut self, data: &[u8]) {
         self.proof.extend_from_slice(data);
         self.coin.write(data);
     }
 }
 
-impl Writable<&Hash> for ProverChannel {
+impl<H: Hasher> Writable<&Hash> for ProverChannel<H> {
     fn write(&mut self, data: &Hash) {
         self.write(data.as_bytes());
     }
 }
 
-impl Writable<&zkp_merkle_tree::Commitment> for ProverChannel {
+impl<H: Hasher> Writable<&zkp_merkle_tree::Commitment> for ProverChannel<H> {
     fn write(&mut self, data: &zkp_merkle_tree::Commitment) {
         self.write(data.hash())
     }
 }
 
-impl Writable<&zkp_merkle_tree::Proof> for ProverChannel {
+impl<H: Hasher> Writable<&zkp_merkle_tree::Proof> for ProverChannel<H> {
     fn write(&mut self, data: &zkp_merkle_tree::Proof) {
         for hash in data.hashes() {
             self.write(hash)
@@ -246,7 +563,7 @@ impl Writable<&zkp_merkle_tree::Proof> for ProverChannel {
 }
 
 // OPT - Remove allocation of vectors
-impl Writable<&[FieldElement]> for ProverChannel {
+impl<H: Hasher> Writable<&[FieldElement]> for ProverChannel<H> {
     fn write(&mut self, data: &[FieldElement]) {
         let mut container = Vec::with_capacity(32 * data.len());
         for element in data {
@@ -258,7 +575,7 @@ impl Writable<&[FieldElement]> for ProverChannel {
     }
 }
 
-impl Writable<&FieldElement> for ProverChannel {
+impl<H: Hasher> Writable<&FieldElement> for ProverChannel<H> {
     fn write(&mut self, data: &FieldElement) {
         self.write(&data.as_montgomery().to_bytes_be()[..]);
     }
@@ -266,7 +583,7 @@ impl Writable<&FieldElement> for ProverChannel {
 
 // Note -- This method of writing is distinct from the field element, and is
 // used in the decommitment when groups are decommited from the rows
-impl Writable<Vec<U256>> for ProverChannel {
+impl<H: Hasher> Writable<Vec<U256>> for ProverChannel<H> {
     fn write(&mut self, data: Vec<U256>) {
         for element in data {
             self.write(element)
@@ -274,30 +591,30 @@ impl Writable<Vec<U256>> for ProverChannel {
     }
 }
 
-impl Writable<U256> for ProverChannel {
+impl<H: Hasher> Writable<U256> for ProverChannel<H> {
     fn write(&mut self, data: U256) {
         self.write(&data.to_bytes_be()[..]);
     }
 }
 
-impl Replayable<Hash> for VerifierChannel {
-    fn replay(&mut self) -> Hash {
-        let hash: [u8; 32] = self.read_32_bytes();
-        Hash::new(hash)
+impl<H: Hasher, S: ProofSource> Replayable<Hash> for VerifierChannel<H, S> {
+    fn replay(&mut self) -> Result<Hash, Error> {
+        let hash: [u8; 32] = self.read_32_bytes()?;
+        Ok(Hash::new(hash))
     }
 }
 
-impl Replayable<U256> for VerifierChannel {
-    fn replay(&mut self) -> U256 {
-        let big_endian_bytes: [u8; 32] = self.read_32_bytes();
-        U256::from_bytes_be(&big_endian_bytes)
+impl<H: Hasher, S: ProofSource> Replayable<U256> for VerifierChannel<H, S> {
+    fn replay(&mut self) -> Result<U256, Error> {
+        let big_endian_bytes: [u8; 32] = self.read_32_bytes()?;
+        Ok(U256::from_bytes_be(&big_endian_bytes))
     }
 }
 
-impl Replayable<FieldElement> for VerifierChannel {
-    fn replay(&mut self) -> FieldElement {
-        let montgomery_modulus: U256 = self.replay();
-        FieldElement::from_montgomery(montgomery_modulus)
+impl<H: Hasher, S: ProofSource> Replayable<FieldElement> for VerifierChannel<H, S> {
+    fn replay(&mut self) -> Result<FieldElement, Error> {
+        let montgomery_modulus: U256 = self.replay()?;
+        Ok(FieldElement::from_montgomery(montgomery_modulus))
     }
 }
 
@@ -331,6 +648,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_channel_eq() {
+        // `Keccak256` does not implement `PartialEq`
+        let mut left = ProverChannel::default();
+        let mut right = ProverChannel::default();
+        left.initialize(&hex!("0123456789abcded"));
+        right.initialize(&hex!("0123456789abcded"));
+        assert_eq!(left, right);
+        left.write(&[1_u8][..]);
+        assert_ne!(left, right);
+    }
+
     // Note - This test depends on the specific ordering of the subtests because of
     // the nature of the channel
     #[test]
@@ -372,6 +701,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tags_separate_phases() {
+        let challenge = |domain_separation: bool, tags: [ChannelTag; 2]| {
+            let mut channel = ProverChannel::default();
+            channel.set_domain_separation(domain_separation);
+            channel.initialize(&hex!("0123456789abcded"));
+            channel.tag(tags[0]);
+            channel.write(&Hash::new([1; 32]));
+            channel.tag(tags[1]);
+            channel.write(&Hash::new([2; 32]));
+            let challenge: FieldElement = channel.get_random();
+            challenge
+        };
+        let in_order = [ChannelTag::TraceRoot, ChannelTag::ConstraintRoot];
+        let swapped = [ChannelTag::ConstraintRoot, ChannelTag::TraceRoot];
+        assert_ne!(challenge(true, in_order), challenge(true, swapped));
+        assert_ne!(challenge(true, in_order), challenge(false, in_order));
+
+        // Without domain separation tags are not absorbed
+        assert_eq!(challenge(false, in_order), challenge(false, swapped));
+    }
+
     #[test]
     fn verifier_channel_test() {
         let mut source = ProverChannel::default();
@@ -401,33 +752,109 @@ mod tests {
 
         let mut verifier = VerifierChannel::new(source.proof.clone());
         verifier.initialize(&hex!("0123456789abcded"));
-        let bytes_test: [u8; 32] = verifier.read_32_bytes();
+        let bytes_test: [u8; 32] = verifier.read_32_bytes().unwrap();
         assert_eq!(bytes_test, rand_bytes);
         assert_eq!(
             verifier.coin.digest,
             hex!("3174a00d031bc8deff799e24a78ee347b303295a6cb61986a49873d9b6f13a0d")
         );
-        let pow_response_test: proof_of_work::Response = verifier.replay();
+        let pow_response_test: proof_of_work::Response = verifier.replay().unwrap();
         assert_eq!(pow_response_test.nonce(), 11_028_357_238_u64);
         assert_eq!(
             verifier.coin.digest,
             hex!("21571e2a323daa1e6f2adda87ce912608e1325492d868e8fe41626633d6acb93")
         );
-        let field_element_test: FieldElement = verifier.replay();
+        let field_element_test: FieldElement = verifier.replay().unwrap();
         assert_eq!(field_element_test, written_field_element);
         assert_eq!(
             verifier.coin.digest,
             hex!("34a12938f047c34da72b5949434950fa2b24220270fd26e6f64b6eb5e86c6626")
         );
-        let field_element_vec_test: Vec<FieldElement> = verifier.replay_fri_layer(2);
+        let field_element_vec_test: Vec<FieldElement> = verifier.replay_fri_layer(2).unwrap();
         assert_eq!(field_element_vec_test, written_field_element_vec);
         assert_eq!(
             verifier.coin.digest,
             hex!("586b2c12cd444cfe29932fcb167fc0be2e575a8d68e4a41d35de8602b0aea929")
         );
-        let bit_int_vec_test: Vec<U256> = verifier.replay_many(2);
+        let bit_int_vec_test: Vec<U256> = verifier.replay_many(2).unwrap();
         assert_eq!(bit_int_vec_test, written_big_int_vec);
         assert_eq!(verifier.coin.digest, source.coin.digest);
+        assert_eq!(verifier.remaining(), 0);
+    }
+
+    #[test]
+    fn read_past_end() {
+        let mut source = ProverChannel::default();
+        source.write(&FieldElement::from(5));
+        let mut proof = source.proof;
+        let _ = proof.pop();
+        let mut verifier = VerifierChannel::new(proof);
+        assert_eq!(verifier.remaining(), 31);
+        assert_eq!(
+            verifier.read_field(),
+            Err(Error::UnexpectedEof {
+                requested: 32,
+                remaining: 31,
+            })
+        );
+        assert_eq!(verifier.read_bytes(&mut [0; 31]), Ok(()));
+        assert_eq!(verifier.at_end(), Ok(true));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_from_reader() {
+        let mut source = ProverChannel::default();
+        source.initialize(&hex!("0123456789abcded"));
+        source.write(&FieldElement::from(5));
+        source.write(proof_of_work::Response::from_nonce(7));
+
+        let mut verifier = VerifierChannel::<Keccak256, _>::from_reader(&source.proof[..]);
+        verifier.initialize(&hex!("0123456789abcded"));
+        assert_eq!(verifier.read_field(), Ok(FieldElement::from(5)));
+        let response: proof_of_work::Response = verifier.replay().unwrap();
+        assert_eq!(response.nonce(), 7);
+        assert_eq!(verifier.coin, source.coin);
+        assert_eq!(verifier.at_end(), Ok(true));
+
+        let mut verifier = VerifierChannel::<Keccak256, _>::from_reader(&source.proof[..20]);
+        assert_eq!(
+            verifier.read_field(),
+            Err(Error::UnexpectedEof {
+                requested: 32,
+                remaining: 20,
+            })
+        );
+    }
+
+    #[test]
+    fn test_pow_bound_to_transcript() {
+        let prove = |value: u64| {
+            let mut channel = ProverChannel::default();
+            channel.initialize(&hex!("0123456789abcded"));
+            channel.write(&FieldElement::from(value));
+            let nonce = channel.grind_pow(8);
+            let challenge: [u8; 32] = channel.get_random();
+            (channel, nonce, challenge)
+        };
+        let (channel, nonce, challenge) = prove(1);
+        let (_, _, other_challenge) = prove(2);
+        assert_ne!(challenge, other_challenge);
+
+        let mut verifier = VerifierChannel::new(channel.proof.clone());
+        verifier.initialize(&hex!("0123456789abcded"));
+        assert_eq!(verifier.read_field(), Ok(FieldElement::from(1)));
+        assert_eq!(verifier.verify_pow(8), Ok(nonce));
+        let verifier_challenge: [u8; 32] = verifier.get_random();
+        assert_eq!(verifier_challenge, challenge);
+
+        // The nonce does not solve the proof of work for a different transcript
+        let mut proof = channel.proof;
+        proof[..32].copy_from_slice(&FieldElement::from(2).as_montgomery().to_bytes_be());
+        let mut verifier = VerifierChannel::new(proof);
+        verifier.initialize(&hex!("0123456789abcded"));
+        assert_eq!(verifier.read_field(), Ok(FieldElement::from(2)));
+        assert!(verifier.verify_pow(8).is_err());
     }
 
     #[test]
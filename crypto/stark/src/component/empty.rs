@@ -1,5 +1,6 @@
 use super::{Component, PolynomialWriter};
 use crate::RationalExpression;
+use std::prelude::v1::*;
 
 #[derive(Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
@@ -1,5 +1,6 @@
 use super::{Component, Mapped, PolynomialWriter};
 use crate::RationalExpression;
+use std::prelude::v1::*;
 use zkp_primefield::fft::permute_index;
 
 /// Note: `Fold::new(Fold::new(A, m), n) == Fold::new(A, m + n)`
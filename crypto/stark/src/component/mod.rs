@@ -9,10 +9,12 @@ use crate::{
     constraint_check::check_constraints,
     proof::Proof,
     prover::prove,
+    trace_table::TraceViewMut,
     verifier::{verify, Error as VerifierError},
     Constraints, ProverError, RationalExpression, TraceTable,
 };
 use log::trace;
+use std::prelude::v1::*;
 use zkp_primefield::{FieldElement, Root};
 
 pub use empty::Empty;
@@ -49,6 +51,20 @@ impl PolynomialWriter for TraceTable {
     }
 }
 
+impl PolynomialWriter for TraceViewMut<'_> {
+    fn num_polynomials(&self) -> usize {
+        self.num_columns()
+    }
+
+    fn polynomial_size(&self) -> usize {
+        self.num_rows()
+    }
+
+    fn write(&mut self, polynomial: usize, location: usize, value: FieldElement) {
+        self[(location, polynomial)] = value;
+    }
+}
+
 pub trait Component {
     type Claim;
     type Witness;
@@ -85,38 +101,61 @@ pub trait Component {
         trace_table
     }
 
-    fn prove(&self, witness: &Self::Witness) -> Result<Proof, ProverError> {
+    /// Constraint system for a given claim, for use with the generic
+    /// [`prove`] and [`verify`] functions.
+    fn constraint_system(&self, claim: &Self::Claim) -> Constraints {
         let polynomials = self.num_polynomials();
         let size = self.polynomial_size();
-        let claim = self.claim(witness);
         let channel_seed = Vec::new();
-        let expressions = self.constraints(&claim);
-        let trace = self.trace_table(witness);
-        let constraints =
-            Constraints::from_expressions((size, polynomials), channel_seed, expressions).unwrap();
+        let expressions = self.constraints(claim);
+        // TODO: Error handling
+        Constraints::from_expressions((size, polynomials), channel_seed, expressions).unwrap()
+    }
+
+    /// Trace table and constraint system for a witness.
+    ///
+    /// The result can be passed directly to [`prove`], so a trace built by
+    /// composing components can be proven like any other constraint system.
+    fn to_constraints(&self, witness: &Self::Witness) -> (TraceTable, Constraints) {
+        let claim = self.claim(witness);
+        (self.trace_table(witness), self.constraint_system(&claim))
+    }
+
+    fn prove(&self, witness: &Self::Witness) -> Result<Proof, ProverError> {
+        let (trace, constraints) = self.to_constraints(witness);
         prove(&constraints, &trace)
     }
 
     fn verify(&self, claim: &Self::Claim, proof: &Proof) -> Result<(), VerifierError> {
-        let polynomials = self.num_polynomials();
-        let size = self.polynomial_size();
-        let channel_seed = Vec::new();
-        let expressions = self.constraints(claim);
-        let constraints =
-            Constraints::from_expressions((size, polynomials), channel_seed, expressions).unwrap();
-        verify(&constraints, proof)
+        verify(&self.constraint_system(claim), proof)
     }
 
     fn check(&self, witness: &Self::Witness) -> Result<(), (usize, usize)> {
-        let polynomials = self.num_polynomials();
-        let size = self.polynomial_size();
-        let claim = self.claim(witness);
-        let channel_seed = Vec::new();
-        let expressions = self.constraints(&claim);
-        // TODO: Error handling
-        let constraints =
-            Constraints::from_expressions((size, polynomials), channel_seed, expressions).unwrap();
-        let trace = self.trace_table(witness);
+        let (trace, constraints) = self.to_constraints(witness);
         check_constraints(&constraints, &trace)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_constraints() {
+        let component = Horizontal::new(
+            Test::new(8, 2, &FieldElement::from(3)),
+            Test::new(8, 3, &FieldElement::from(5)),
+        );
+        let claim = (FieldElement::from(7), FieldElement::from(11));
+        let witness = (
+            (claim.0.clone(), FieldElement::from(13)),
+            (claim.1.clone(), FieldElement::from(17)),
+        );
+        let (trace, constraints) = component.to_constraints(&witness);
+        assert_eq!(trace.num_columns(), 5);
+        assert_eq!(trace.num_rows(), 8);
+        let proof = prove(&constraints, &trace).unwrap();
+        assert_eq!(verify(&constraints, &proof), Ok(()));
+        assert_eq!(component.verify(&claim, &proof), Ok(()));
+    }
+}
@@ -1,5 +1,6 @@
 use super::{Component, PolynomialWriter};
 use crate::RationalExpression;
+use std::prelude::v1::*;
 use zkp_primefield::{FieldElement, Root};
 
 /// Test constraint system
@@ -1,6 +1,6 @@
 use crate::{constraints::Constraints, trace_table::TraceTable};
-use std::convert::TryInto;
-use zkp_primefield::{FieldElement, One, Pow, Root};
+use std::{convert::TryInto, prelude::v1::*};
+use zkp_primefield::{geometric_series::geometric_series, FieldElement, One, Pow, Root};
 
 #[allow(clippy::doc_markdown)]
 /// # Check a set of constraints on a trace table
@@ -35,33 +35,60 @@ pub fn check_constraints(
     constraints: &Constraints,
     table: &TraceTable,
 ) -> Result<(), (usize, usize)> {
+    failures(constraints, table).next().map_or(Ok(()), Err)
+}
+
+impl Constraints {
+    /// Check every constraint on every row of the trace.
+    ///
+    /// This evaluates the constraints on the trace domain only, which is much
+    /// cheaper than proving and catches invalid witnesses early. Unlike
+    /// [`check_constraints`] it does not stop at the first failure but returns
+    /// all `(row, constraint_index)` pairs that fail, ordered by row. The same
+    /// caveats on complex rational expressions apply.
+    pub fn check_trace(&self, trace: &TraceTable) -> Result<(), Vec<(usize, usize)>> {
+        let failures: Vec<_> = failures(self, trace).collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+/// Lazily iterate over all failing `(row, constraint_index)` pairs.
+fn failures<'a>(
+    constraints: &'a Constraints,
+    table: &'a TraceTable,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
     let trace_generator = FieldElement::root(table.num_rows()).unwrap();
-    let mut current_root = FieldElement::one();
     let len = table.num_rows();
 
-    for row in 0..len {
-        // Note - Still in col row form
-        let trace = |i: usize, j: isize| {
-            if j.is_positive() {
-                let j: usize = j.try_into().unwrap();
-                table[((j + row) % len, i)].clone()
-            } else {
-                let j: usize = j.abs().try_into().unwrap();
-                if row < j {
-                    table[(len + row - j, i)].clone()
+    geometric_series(&FieldElement::one(), &trace_generator)
+        .take(len)
+        .enumerate()
+        .flat_map(move |(row, current_root)| {
+            // Note - Still in col row form
+            let trace = move |i: usize, j: isize| {
+                if j.is_positive() {
+                    let j: usize = j.try_into().unwrap();
+                    table[((j + row) % len, i)].clone()
                 } else {
-                    table[(row - j, i)].clone()
+                    let j: usize = j.abs().try_into().unwrap();
+                    if row < j {
+                        table[(len + row - j, i)].clone()
+                    } else {
+                        table[(row - j, i)].clone()
+                    }
                 }
-            }
-        };
-        for (which, expression) in constraints.expressions().iter().enumerate() {
-            if !expression.check(&current_root, &trace).1 {
-                return Err((row, which));
-            }
-        }
-        current_root *= &trace_generator;
-    }
-    Ok(())
+            };
+            constraints
+                .expressions()
+                .iter()
+                .enumerate()
+                .filter(move |(_, expression)| !expression.check(&current_root, &trace).1)
+                .map(move |(which, _)| (row, which))
+        })
 }
 
 pub(crate) fn check_specific_constraint(
@@ -127,6 +154,26 @@ mod tests {
         assert_eq!(check_constraints(&constraints, &trace), Err((799, 0)));
     }
 
+    #[test]
+    fn check_trace_reports_all_failures() {
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let witness = recurrance.witness();
+        let claim = recurrance.claim();
+
+        let constraints = claim.constraints();
+        let mut trace = claim.trace(&witness);
+        assert_eq!(constraints.check_trace(&trace), Ok(()));
+        trace[(800, 0)] = FieldElement::zero();
+        assert_eq!(
+            constraints.check_trace(&trace),
+            Err(vec![(799, 0), (800, 1)])
+        );
+    }
+
     #[test]
     fn specific_constraint_checker() {
         let recurrance = Recurrance {
@@ -1,11 +1,41 @@
-use crate::{polynomial::DensePolynomial, rational_expression::RationalExpression};
+use crate::{
+    polynomial::DensePolynomial,
+    proof_params::{Error as ProofParamsError, ProofParams},
+    rational_expression::RationalExpression,
+    verifier::{oods_value_from_constraint_values, oods_value_from_trace_values},
+};
 use itertools::Itertools;
-use std::{collections::BTreeSet, fmt, prelude::v1::*};
-use zkp_primefield::{FieldElement, Root};
-
-#[derive(Clone, Debug)]
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    ops::{Range, RangeInclusive},
+    prelude::v1::*,
+};
+use tiny_keccak::{Hasher, Keccak};
+use zkp_hash::Hash;
+use zkp_primefield::{FieldElement, One, Pow, Root};
+
+/// Default for [`Constraints::max_expression_nodes`].
+const DEFAULT_MAX_EXPRESSION_NODES: usize = 1 << 20;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Error {
     InvalidTraceLength,
+    DegreeOverflow {
+        constraint_index: usize,
+    },
+    DegreeExceedsBlowup {
+        constraint_index: usize,
+        degree:           usize,
+        blowup:           usize,
+    },
+    TraceDimensionMismatch,
+    ChannelSeedMismatch,
+    ExpressionTooLarge {
+        constraint_index: usize,
+        nodes:            usize,
+        maximum:          usize,
+    },
 }
 
 impl fmt::Display for Error {
@@ -13,6 +43,37 @@ impl fmt::Display for Error {
         use Error::*;
         match *self {
             InvalidTraceLength => write!(f, "Invalid trace length (must be power of two)"),
+            DegreeOverflow { constraint_index } => {
+                write!(
+                    f,
+                    "Constraint {} exceeds the degree bound of the constraint system",
+                    constraint_index
+                )
+            }
+            DegreeExceedsBlowup {
+                constraint_index,
+                degree,
+                blowup,
+            } => {
+                write!(
+                    f,
+                    "Constraint {} has degree {} times the trace length, but the blowup is only {}",
+                    constraint_index, degree, blowup
+                )
+            }
+            TraceDimensionMismatch => write!(f, "Constraint systems have different trace sizes"),
+            ChannelSeedMismatch => write!(f, "Constraint systems have different channel seeds"),
+            ExpressionTooLarge {
+                constraint_index,
+                nodes,
+                maximum,
+            } => {
+                write!(
+                    f,
+                    "Constraint {} has {} nodes, but at most {} are allowed",
+                    constraint_index, nodes, maximum
+                )
+            }
         }
     }
 }
@@ -67,31 +128,55 @@ pub struct Constraints {
     /// The following Vec of dense polys can be used to substitute claim
     /// polynomials inside of the prover.
     pub claim_polynomials: Vec<DensePolynomial>,
+
+    /// Absorb a tag for each protocol phase into the channel
+    ///
+    /// This separates the trace root, constraint root, out of domain values,
+    /// FRI layers and proof of work in the Fiat-Shamir transcript, so values
+    /// can not be moved from one phase to another. It changes the transcript
+    /// and is off by default to stay compatible with the Solidity verifier.
+    pub domain_separation: bool,
+
+    /// Maximum number of nodes in a constraint expression
+    ///
+    /// Deeply composed components can produce very large expressions that
+    /// take unexpectedly long to evaluate. The prover refuses constraints
+    /// with more nodes than this, see
+    /// [`RationalExpression::count_nodes`].
+    pub max_expression_nodes: usize,
 }
 
-impl Constraints {
-    fn default_fri_layout(trace_nrows: usize) -> Vec<usize> {
-        // The binary logarithm of the final layer polynomial degree.
-        const LOG2_TARGET: usize = 8;
-
-        // Number of reductions to reach target degree
-        // TODO: For very small traces we fold to a constant, but this is not
-        // necessarily optimal.
-        let log2_trace = trace_nrows.trailing_zeros() as usize;
-        let num_reductions = if log2_trace > LOG2_TARGET {
-            log2_trace - LOG2_TARGET
-        } else {
-            log2_trace
-        };
+/// Out of domain values of a proof, recomputed from its polynomials.
+///
+/// See [`Constraints::oods_check`].
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct OodsResult {
+    /// The trace values at the shifted out of domain points, in the order of
+    /// [`Constraints::trace_arguments`].
+    pub trace_values: Vec<FieldElement>,
 
-        // Do as many three reductions as possible
-        let mut fri_layout = vec![3; num_reductions / 3];
-        if num_reductions % 3 != 0 {
-            fri_layout.push(num_reductions % 3);
-        }
-        fri_layout
+    /// The values of the constraint polynomials at `oods_point^n`, where `n`
+    /// is the number of constraint polynomials. For degree two constraints
+    /// these are the even and odd parts of the combined constraint.
+    pub constraint_values: Vec<FieldElement>,
+
+    /// The combined constraint evaluated from the trace values.
+    pub combined_value: FieldElement,
+
+    /// The combined constraint reassembled from the constraint values.
+    pub decomposed_value: FieldElement,
+}
+
+impl OodsResult {
+    /// Both ways of computing the combined constraint agree. This is the
+    /// check the verifier does on the values in the proof.
+    pub fn is_consistent(&self) -> bool {
+        self.combined_value == self.decomposed_value
     }
+}
 
+impl Constraints {
     /// Requires all instances of `RationalExpression::ClaimPolynomial` in the
     /// expressions to have been replaced by
     /// `RationalExpression::DensePolynomial`.
@@ -109,7 +194,7 @@ impl Constraints {
         // TODO - Examine if we want to up these security params further.
         // 22.5*4  + 0 queries = 90
         // TODO: Sensible default for pow_bits. For small proofs it should be small.
-        Ok(Self {
+        let result = Self {
             channel_seed,
             trace_nrows,
             trace_ncolumns,
@@ -117,9 +202,13 @@ impl Constraints {
             blowup: 16,
             pow_bits: 0,
             num_queries: 45,
-            fri_layout: Self::default_fri_layout(trace_nrows),
+            fri_layout: ProofParams::default_fri_layout(trace_nrows),
             claim_polynomials: vec![],
-        })
+            domain_separation: false,
+            max_expression_nodes: DEFAULT_MAX_EXPRESSION_NODES,
+        };
+        result.check_degrees()?;
+        Ok(result)
     }
 
     /// Requires all instances of `RationalExpression::ClaimPolynomial` in the
@@ -141,7 +230,7 @@ impl Constraints {
         let _ = FieldElement::root(trace_nrows).ok_or(Error::InvalidTraceLength)?;
         // TODO: Hash expressions into channel seed
         // 15*4 + 30 queries = 90
-        Ok(Self {
+        let result = Self {
             channel_seed,
             trace_nrows,
             trace_ncolumns,
@@ -166,10 +255,100 @@ impl Constraints {
             },
             fri_layout: match op_fri_layout {
                 Some(x) => x,
-                None => Self::default_fri_layout(trace_nrows),
+                None => ProofParams::default_fri_layout(trace_nrows),
             },
             claim_polynomials: vec![],
-        })
+            domain_separation: false,
+            max_expression_nodes: DEFAULT_MAX_EXPRESSION_NODES,
+        };
+        result.check_degrees()?;
+        Ok(result)
+    }
+
+    /// Check that every constraint can be adjusted up to the degree bound.
+    ///
+    /// In `combine` each constraint is multiplied by a power of `X` to bring
+    /// it to the target degree. A constraint whose degree is already above the
+    /// target would need a negative adjustment.
+    fn check_degrees(&self) -> Result<(), Error> {
+        if self.expressions.is_empty() {
+            return Ok(());
+        }
+        let mut max_trace_degree = 0;
+        for (constraint_index, expression) in self.expressions.iter().enumerate() {
+            let (numerator_degree, denominator_degree) = expression.trace_degree();
+            let trace_degree = numerator_degree
+                .checked_sub(denominator_degree)
+                .ok_or(Error::DegreeOverflow { constraint_index })?;
+            max_trace_degree = std::cmp::max(max_trace_degree, trace_degree);
+        }
+        let target_degree = (max_trace_degree * self.trace_nrows).saturating_sub(1);
+        for (constraint_index, expression) in self.expressions.iter().enumerate() {
+            let (num, den) = expression.degree(self.trace_nrows - 1);
+            if num > target_degree + den {
+                return Err(Error::DegreeOverflow { constraint_index });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every constraint fits in the low-degree extension.
+    ///
+    /// The constraint polynomial is evaluated on a domain `blowup` times the
+    /// size of the trace. A constraint whose degree (after division, in
+    /// multiples of the trace length) exceeds `blowup` can not be
+    /// reconstructed from these evaluations and the proof would not verify.
+    pub fn validate_degrees(&self, blowup: usize) -> Result<(), Error> {
+        for (constraint_index, expression) in self.expressions.iter().enumerate() {
+            let (numerator_degree, denominator_degree) = expression.trace_degree();
+            let degree = numerator_degree.saturating_sub(denominator_degree);
+            if degree > blowup {
+                return Err(Error::DegreeExceedsBlowup {
+                    constraint_index,
+                    degree,
+                    blowup,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that no constraint has more than `max_expression_nodes` nodes.
+    pub fn validate_size(&self) -> Result<(), Error> {
+        for (constraint_index, expression) in self.expressions.iter().enumerate() {
+            let nodes = expression.count_nodes();
+            if nodes > self.max_expression_nodes {
+                return Err(Error::ExpressionTooLarge {
+                    constraint_index,
+                    nodes,
+                    maximum: self.max_expression_nodes,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The tuning parameters of the proof.
+    pub fn params(&self) -> ProofParams {
+        ProofParams {
+            blowup:      self.blowup,
+            pow_bits:    self.pow_bits,
+            num_queries: self.num_queries,
+            fri_layout:  self.fri_layout.clone(),
+        }
+    }
+
+    /// Replace the tuning parameters of the proof.
+    ///
+    /// The parameters are validated against the trace length of these
+    /// constraints and left unchanged if they are not compatible.
+    pub fn set_params(&mut self, params: ProofParams) -> Result<(), ProofParamsError> {
+        params.validate(self.trace_nrows)?;
+        self.blowup = params.blowup;
+        self.pow_bits = params.pow_bits;
+        self.num_queries = params.num_queries;
+        self.fri_layout = params.fri_layout;
+        Ok(())
     }
 
     pub fn channel_seed(&self) -> &[u8] {
@@ -209,10 +388,7 @@ impl Constraints {
 
     // TODO: Better explanation with literature references.
     pub fn security_bits(&self) -> usize {
-        // Our conservative formula is (1/2^blowup_log)^(queries/2)*(1/2^pow_bits)
-        // So the bit security should be blowup_log*(queries/2) + pow_bits
-        let blowup_log = (64 - (self.blowup as u64).leading_zeros()) as usize;
-        blowup_log * (self.num_queries / 2) + self.pow_bits
+        self.params().security_bits()
     }
 
     // Returns an upper bound on proof size in terms of bytes in the proof.
@@ -265,6 +441,73 @@ impl Constraints {
             .sum()
     }
 
+    /// Merge two constraint systems over the same trace into one.
+    ///
+    /// The expressions of `other` are appended to those of `self`, so a
+    /// single proof covers both. The tuning parameters of `self` are kept.
+    /// Both systems must have the same trace dimensions and channel seed.
+    pub fn merge(mut self, other: Self) -> Result<Self, Error> {
+        if self.trace_nrows != other.trace_nrows || self.trace_ncolumns != other.trace_ncolumns {
+            return Err(Error::TraceDimensionMismatch);
+        }
+        if self.channel_seed != other.channel_seed {
+            return Err(Error::ChannelSeedMismatch);
+        }
+        self.expressions.extend(other.expressions);
+        self.claim_polynomials.extend(other.claim_polynomials);
+        self.check_degrees()?;
+        Ok(self)
+    }
+
+    /// Recompute the out of domain sampling step of a proof.
+    ///
+    /// Given the trace and constraint polynomials of a proof, for example as
+    /// produced by `prove_with_polynomials`, the constraint coefficients and
+    /// the out of domain point drawn from the channel, this evaluates the
+    /// values the prover writes to the proof and both sides of the equation
+    /// the verifier checks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `trace_polys` does not have a polynomial for every column or
+    /// there are not two coefficients per constraint.
+    pub fn oods_check(
+        &self,
+        trace_polys: &[DensePolynomial],
+        constraint_polys: &[DensePolynomial],
+        coefficients: &[FieldElement],
+        oods_point: &FieldElement,
+    ) -> OodsResult {
+        assert_eq!(trace_polys.len(), self.trace_ncolumns());
+        let mut parseable_constraints = self.clone();
+        parseable_constraints.substitute();
+        let g = FieldElement::root(self.trace_nrows()).expect("No root for trace length");
+        let trace_map: BTreeMap<(usize, isize), FieldElement> = parseable_constraints
+            .trace_arguments()
+            .into_iter()
+            .map(|(column, offset)| {
+                let x = oods_point * g.pow(offset).unwrap();
+                ((column, offset), trace_polys[column].evaluate(&x))
+            })
+            .collect();
+        let x = oods_point.pow(constraint_polys.len());
+        let constraint_values: Vec<FieldElement> = constraint_polys
+            .iter()
+            .map(|polynomial| polynomial.evaluate(&x))
+            .collect();
+        OodsResult {
+            combined_value: oods_value_from_trace_values(
+                self,
+                coefficients,
+                &trace_map,
+                oods_point,
+            ),
+            decomposed_value: oods_value_from_constraint_values(&constraint_values, oods_point),
+            trace_values: trace_map.values().cloned().collect(),
+            constraint_values,
+        }
+    }
+
     pub fn trace_arguments(&self) -> Vec<(usize, isize)> {
         self.expressions
             .iter()
@@ -274,6 +517,163 @@ impl Constraints {
             .collect()
     }
 
+    /// Hash of everything the verifier depends on.
+    ///
+    /// This commits to the trace dimensions, the channel seed, the proof
+    /// parameters, the claim polynomials and the expressions. A verifier that
+    /// receives the constraints from an untrusted source can compare the
+    /// digest with a pinned value, see
+    /// [`verify_with_digest`](crate::verify_with_digest).
+    pub fn digest(&self) -> Hash {
+        fn write_usize(keccak: &mut Keccak, value: usize) {
+            keccak.update(&(value as u64).to_be_bytes());
+        }
+
+        let mut keccak = Keccak::v256();
+        write_usize(&mut keccak, self.channel_seed.len());
+        keccak.update(&self.channel_seed);
+        write_usize(&mut keccak, self.trace_nrows);
+        write_usize(&mut keccak, self.trace_ncolumns);
+        write_usize(&mut keccak, self.blowup);
+        write_usize(&mut keccak, self.pow_bits);
+        write_usize(&mut keccak, self.num_queries);
+        write_usize(&mut keccak, self.fri_layout.len());
+        for &reduction in &self.fri_layout {
+            write_usize(&mut keccak, reduction);
+        }
+        keccak.update(&[u8::from(self.domain_separation)]);
+        write_usize(&mut keccak, self.claim_polynomials.len());
+        for polynomial in &self.claim_polynomials {
+            write_usize(&mut keccak, polynomial.coefficients().len());
+            for coefficient in polynomial.coefficients() {
+                keccak.update(&coefficient.to_uint().to_bytes_be());
+            }
+        }
+        write_usize(&mut keccak, self.expressions.len());
+        for expression in &self.expressions {
+            keccak.update(expression.digest().as_bytes());
+        }
+        let mut digest = [0_u8; 32];
+        keccak.finalize(&mut digest);
+        Hash::new(digest)
+    }
+
+    /// Number of trace columns and range of row offsets used by the
+    /// expressions.
+    ///
+    /// Together these form the mask of the constraints: each query reveals
+    /// the columns at every offset in the range. If the expressions do not
+    /// refer to the trace the result is `(0, 0..=0)`.
+    pub fn trace_arity_and_offsets(&self) -> (usize, RangeInclusive<isize>) {
+        let arguments = self.trace_arguments();
+        let columns = arguments
+            .iter()
+            .map(|(column, _)| column)
+            .collect::<BTreeSet<_>>();
+        let offsets = arguments
+            .iter()
+            .map(|(_, offset)| *offset)
+            .minmax()
+            .into_option()
+            .map_or(0..=0, |(min, max)| min..=max);
+        (columns.len(), offsets)
+    }
+
+    /// Constraints proving that `column` sums to `total`.
+    ///
+    /// This requires an auxiliary `accumulator` column in the trace table that
+    /// holds the running sum of `column`, i.e. row `i` of `accumulator`
+    /// contains the sum of rows `0..=i` of `column`. The constraints enforce
+    /// that the accumulator starts with the first value, adds one value
+    /// per row and ends at `total` on the last row. They can be combined with
+    /// other expressions in `from_expressions`.
+    pub fn column_sum(
+        trace_nrows: usize,
+        column: usize,
+        accumulator: usize,
+        total: &FieldElement,
+    ) -> Vec<RationalExpression> {
+        use RationalExpression::*;
+
+        let trace_generator = FieldElement::root(trace_nrows).expect("Invalid trace length");
+        let g = Constant(trace_generator);
+        let on_row = |index| (X - g.pow(index)).inv();
+        let every_row = || (X - g.pow(trace_nrows - 1)) / (X.pow(trace_nrows) - 1);
+
+        vec![
+            (Trace(accumulator, 0) - Trace(column, 0)) * on_row(0),
+            (Trace(accumulator, 1) - Trace(accumulator, 0) - Trace(column, 1)) * every_row(),
+            (Trace(accumulator, 0) - Constant(total.clone())) * on_row(trace_nrows - 1),
+        ]
+    }
+
+    /// Constraints proving that `permuted` is a permutation of `original`.
+    ///
+    /// This is a grand product argument. It requires an auxiliary `product`
+    /// column where row $i$ holds
+    ///
+    /// $$
+    /// \prod_{j \le i} \frac{a_j + \gamma}{b_j + \gamma}
+    /// $$
+    ///
+    /// with $a$ the `original` column, $b$ the `permuted` column and $\gamma$
+    /// the `challenge`. The constraints enforce the first row, the update rule
+    /// on every following row and that the product ends at one on the last
+    /// row.
+    ///
+    /// The argument is only sound if the challenge is unpredictable to whoever
+    /// fills in the columns. The constraints are fixed before the trace is
+    /// committed, so the challenge can not come from the trace commitment.
+    pub fn permutation_argument(
+        trace_nrows: usize,
+        original: usize,
+        permuted: usize,
+        product: usize,
+        challenge: &FieldElement,
+    ) -> Vec<RationalExpression> {
+        use RationalExpression::*;
+
+        let trace_generator = FieldElement::root(trace_nrows).expect("Invalid trace length");
+        let g = Constant(trace_generator);
+        let on_row = |index| (X - g.pow(index)).inv();
+        let every_row = || (X - g.pow(trace_nrows - 1)) / (X.pow(trace_nrows) - 1);
+        let gamma = || Constant(challenge.clone());
+
+        vec![
+            (Trace(product, 0) * (Trace(permuted, 0) + gamma()) - (Trace(original, 0) + gamma()))
+                * on_row(0),
+            (Trace(product, 1) * (Trace(permuted, 1) + gamma())
+                - Trace(product, 0) * (Trace(original, 1) + gamma()))
+                * every_row(),
+            (Trace(product, 0) - 1) * on_row(trace_nrows - 1),
+        ]
+    }
+
+    /// Constraints proving that `value` fits in `bits.len()` bits.
+    ///
+    /// The columns in `bits` hold the binary decomposition of `value`, least
+    /// significant bit first. On every row each bit must be zero or one and
+    /// the bits must add up to `value`.
+    pub fn range_check(
+        trace_nrows: usize,
+        value: usize,
+        bits: Range<usize>,
+    ) -> Vec<RationalExpression> {
+        use RationalExpression::*;
+
+        let every_row = || (X.pow(trace_nrows) - 1).inv();
+        let mut recomposition = Trace(value, 0);
+        let mut power = FieldElement::one();
+        let mut constraints = Vec::with_capacity(bits.len() + 1);
+        for bit in bits {
+            constraints.push(Trace(bit, 0) * (Trace(bit, 0) - 1) * every_row());
+            recomposition = recomposition - Trace(bit, 0) * Constant(power.clone());
+            power += power.clone();
+        }
+        constraints.push(recomposition * every_row());
+        constraints
+    }
+
     // This sets a the claim polynomials field
     // Note that since we didn't want to change the interface this is the
     // only way to set or change the field
@@ -299,9 +699,14 @@ impl Constraints {
 
 #[cfg(test)]
 mod tests {
-    use crate::{prove, traits::tests::Recurrance, Provable, Verifiable};
+    use super::*;
+    use crate::{
+        check_constraints, prove, traits::tests::Recurrance, verify, ProofParams, ProofParamsError,
+        Provable, TraceTable, Verifiable,
+    };
     use zkp_macros_decl::field_element;
-    use zkp_primefield::FieldElement;
+    use zkp_primefield::{One, Pow, Zero};
+    use zkp_merkle_tree::Commitment;
     use zkp_u256::U256;
 
     #[test]
@@ -323,4 +728,273 @@ mod tests {
         let actual = prove(&constraints, &public.trace(&private)).unwrap();
         assert!(actual.as_bytes().len() <= constraints.max_proof_size());
     }
+
+    #[test]
+    fn size_estimate_fri_layers() {
+        // Each FRI layer in `max_proof_size` budgets a full path and all but
+        // one value of a coset per query. Spread out queries open the most
+        // cosets and share the fewest hashes.
+        let num_queries = 20;
+        let mut current_size = 12 - 3;
+        for &log_coset in &[2, 1, 4, 2] {
+            current_size -= log_coset;
+            let num_values = 1 << (current_size + log_coset);
+            let indices: Vec<usize> = (0..num_queries)
+                .map(|i| i * num_values / num_queries)
+                .collect();
+            let commitment =
+                Commitment::from_size_hash(1 << current_size, &Hash::default()).unwrap();
+            assert!(
+                commitment.decommitment_size(&indices, log_coset).unwrap()
+                    <= num_queries * (current_size + (1 << log_coset) - 1)
+            );
+        }
+    }
+
+    #[test]
+    fn trace_arity_and_offsets_test() {
+        use RationalExpression::*;
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let constraints = recurrance.claim().constraints();
+        assert_eq!(constraints.trace_arity_and_offsets(), (2, 0..=1));
+
+        let constraints =
+            Constraints::from_expressions((8, 3), vec![], vec![Trace(2, -1) * Trace(0, 3), X - 1])
+                .unwrap();
+        assert_eq!(constraints.trace_arity_and_offsets(), (2, -1..=3));
+        let constraints =
+            Constraints::from_expressions((8, 1), vec![], vec![(X - 1) / (X.pow(8) - 1)]).unwrap();
+        assert_eq!(constraints.trace_arity_and_offsets(), (0, 0..=0));
+    }
+
+    #[test]
+    fn digest_test() {
+        use RationalExpression::*;
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let constraints = recurrance.claim().constraints();
+        let digest = constraints.digest();
+        assert_eq!(recurrance.claim().constraints().digest(), digest);
+
+        // Change the first row to the second row in a single constraint
+        let mut expressions = constraints.expressions().to_vec();
+        expressions[0] = expressions[0].map(&|node| {
+            match node {
+                Trace(column, 0) => Trace(column, 1),
+                other => other,
+            }
+        });
+        let tampered = Constraints::from_expressions(
+            (constraints.trace_nrows(), constraints.trace_ncolumns()),
+            constraints.channel_seed().to_vec(),
+            expressions,
+        )
+        .unwrap();
+        assert_ne!(tampered.digest(), digest);
+
+        let mut more_queries = constraints.clone();
+        more_queries.num_queries += 1;
+        assert_ne!(more_queries.digest(), digest);
+    }
+
+    #[test]
+    fn set_params_test() {
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("0f00dbabe0cafebabe"),
+            exponent:      1,
+        };
+        let private = recurrance.witness();
+        let public = recurrance.claim();
+
+        let mut constraints = public.constraints();
+        let params = ProofParams::builder(constraints.trace_nrows())
+            .blowup(32)
+            .queries(20)
+            .fri_layout(vec![3, 2, 3])
+            .build()
+            .unwrap();
+        constraints.set_params(params.clone()).unwrap();
+        assert_eq!(constraints.params(), params);
+
+        let proof = prove(&constraints, &public.trace(&private)).unwrap();
+        verify(&constraints, &proof).unwrap();
+
+        let too_long = ProofParams {
+            fri_layout: vec![3, 3, 3, 3],
+            ..params.clone()
+        };
+        assert_eq!(
+            constraints.set_params(too_long),
+            Err(ProofParamsError::FriLayoutTooLong {
+                reductions: 12,
+                maximum:    10,
+            })
+        );
+        assert_eq!(constraints.params(), params);
+    }
+
+    #[test]
+    fn column_sum_test() {
+        let mut trace = TraceTable::new(16, 2);
+        let mut total = FieldElement::zero();
+        for i in 0..16 {
+            trace[(i, 0)] = FieldElement::from(i * i + 3);
+            total += &trace[(i, 0)];
+            trace[(i, 1)] = total.clone();
+        }
+
+        let constraints = Constraints::from_expressions(
+            (16, 2),
+            b"column sum".to_vec(),
+            Constraints::column_sum(16, 0, 1, &total),
+        )
+        .unwrap();
+        assert_eq!(check_constraints(&constraints, &trace), Ok(()));
+        let proof = prove(&constraints, &trace).unwrap();
+        verify(&constraints, &proof).unwrap();
+
+        let wrong = Constraints::from_expressions(
+            (16, 2),
+            b"column sum".to_vec(),
+            Constraints::column_sum(16, 0, 1, &(total + FieldElement::one())),
+        )
+        .unwrap();
+        assert_eq!(check_constraints(&wrong, &trace), Err((15, 2)));
+        assert!(verify(&wrong, &proof).is_err());
+    }
+
+    #[test]
+    fn permutation_argument_test() {
+        let challenge = field_element!("0123456789abcdef");
+        let trace = |permuted: &dyn Fn(usize) -> usize| {
+            let mut trace = TraceTable::new(16, 3);
+            let mut product = FieldElement::one();
+            for i in 0..16 {
+                trace[(i, 0)] = FieldElement::from(i * i + 3);
+                trace[(i, 1)] = FieldElement::from(permuted(i) * permuted(i) + 3);
+                product *= (&trace[(i, 0)] + &challenge) / (&trace[(i, 1)] + &challenge);
+                trace[(i, 2)] = product.clone();
+            }
+            trace
+        };
+        let constraints = Constraints::from_expressions(
+            (16, 3),
+            b"permutation".to_vec(),
+            Constraints::permutation_argument(16, 0, 1, 2, &challenge),
+        )
+        .unwrap();
+
+        let shuffled = trace(&|i| (7 * i + 5) % 16);
+        assert_eq!(check_constraints(&constraints, &shuffled), Ok(()));
+        let proof = prove(&constraints, &shuffled).unwrap();
+        verify(&constraints, &proof).unwrap();
+
+        // Row 3 is duplicated and row 4 is missing.
+        let duplicated = trace(&|i| if i == 4 { 3 } else { i });
+        assert_eq!(check_constraints(&constraints, &duplicated), Err((15, 2)));
+        assert!(prove(&constraints, &duplicated).is_err());
+    }
+
+    #[test]
+    fn range_check_test() {
+        let mut trace = TraceTable::new(16, 9);
+        for i in 0..16 {
+            let value = 17 * i;
+            trace[(i, 0)] = FieldElement::from(value);
+            for bit in 0..8 {
+                trace[(i, bit + 1)] = FieldElement::from((value >> bit) & 1);
+            }
+        }
+        let constraints = Constraints::from_expressions(
+            (16, 9),
+            b"range check".to_vec(),
+            Constraints::range_check(16, 0, 1..9),
+        )
+        .unwrap();
+        assert_eq!(check_constraints(&constraints, &trace), Ok(()));
+        let proof = prove(&constraints, &trace).unwrap();
+        verify(&constraints, &proof).unwrap();
+
+        // Row 1 holds 17 = 0b10001. Replace it by 2 with a "bit" of value 2,
+        // which recomposes correctly but is not boolean.
+        trace[(1, 0)] = FieldElement::from(2);
+        trace[(1, 1)] = FieldElement::from(2);
+        trace[(1, 5)] = FieldElement::zero();
+        assert_eq!(check_constraints(&constraints, &trace), Err((1, 0)));
+        assert!(prove(&constraints, &trace).is_err());
+    }
+
+    #[test]
+    fn degree_overflow_test() {
+        use RationalExpression::*;
+        let trace_generator = FieldElement::root(16).unwrap();
+        let every_row =
+            || (X - Constant(trace_generator.pow(15_usize))) / (X.pow(16) - Constant(1.into()));
+        let valid = (Trace(0, 1) - Trace(0, 0)) * every_row();
+        let over_degree = Trace(0, 0) * X.pow(32) * every_row();
+
+        assert!(Constraints::from_expressions((16, 1), vec![], vec![valid.clone()]).is_ok());
+        let result = Constraints::from_expressions((16, 1), vec![], vec![valid, over_degree]);
+        match result {
+            Err(Error::DegreeOverflow { constraint_index }) => assert_eq!(constraint_index, 1),
+            _ => panic!("Expected a degree overflow"),
+        }
+    }
+
+    #[test]
+    fn merge_test() {
+        use RationalExpression::*;
+        let recurrance = Recurrance {
+            index:         100,
+            initial_value: field_element!("0f00dbabe0cafebabe"),
+            exponent:      1,
+        };
+        let private = recurrance.witness();
+        let public = recurrance.claim();
+        let fibonacci = public.constraints();
+        let trace_nrows = fibonacci.trace_nrows();
+        let on_first_row = (X - 1).inv();
+
+        let identity = Constraints::from_expressions(
+            (trace_nrows, 2),
+            fibonacci.channel_seed().to_vec(),
+            vec![(Trace(0, 0) - 1) * on_first_row.clone()],
+        )
+        .unwrap();
+        let merged = fibonacci.clone().merge(identity).unwrap();
+        assert_eq!(merged.len(), fibonacci.len() + 1);
+
+        let trace = public.trace(&private);
+        assert_eq!(check_constraints(&merged, &trace), Ok(()));
+        let proof = prove(&merged, &trace).unwrap();
+        verify(&merged, &proof).unwrap();
+
+        let narrow = Constraints::from_expressions(
+            (trace_nrows, 1),
+            fibonacci.channel_seed().to_vec(),
+            vec![(Trace(0, 0) - 1) * on_first_row.clone()],
+        )
+        .unwrap();
+        match fibonacci.clone().merge(narrow) {
+            Err(Error::TraceDimensionMismatch) => {}
+            _ => panic!("Expected a trace dimension mismatch"),
+        }
+
+        let other_seed = Constraints::from_expressions((trace_nrows, 2), b"other".to_vec(), vec![
+            (Trace(0, 0) - 1) * on_first_row,
+        ])
+        .unwrap();
+        match fibonacci.merge(other_seed) {
+            Err(Error::ChannelSeedMismatch) => {}
+            _ => panic!("Expected a channel seed mismatch"),
+        }
+    }
 }
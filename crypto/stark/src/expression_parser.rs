@@ -0,0 +1,354 @@
+use crate::rational_expression::RationalExpression;
+use std::{convert::TryFrom, fmt, prelude::v1::*, str::FromStr};
+use zkp_primefield::{FieldElement, Zero};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum Error {
+    UnexpectedCharacter { position: usize },
+    UnexpectedEnd,
+    InvalidNumber { position: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+        match *self {
+            UnexpectedCharacter { position } => {
+                write!(f, "Unexpected character at position {}", position)
+            }
+            UnexpectedEnd => write!(f, "Unexpected end of expression"),
+            InvalidNumber { position } => write!(f, "Invalid number at position {}", position),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Parse an expression in the format written by `Display`.
+///
+/// The grammar supports `x`, `trace(column, offset)`, decimal and `0x`
+/// prefixed hexadecimal constants, `inv(..)`, the binary operators `+`, `-`,
+/// `*` and `/`, unary `-`, integer powers `^` and parentheses. The usual
+/// precedence rules apply and binary operators are left associative.
+///
+/// Polynomial and claim polynomial nodes can not be parsed.
+impl FromStr for RationalExpression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            input:    s.as_bytes(),
+            position: 0,
+        };
+        let result = parser.expression()?;
+        if parser.peek().is_some() {
+            return Err(parser.unexpected());
+        }
+        Ok(result)
+    }
+}
+
+struct Parser<'a> {
+    input:    &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    /// Skip whitespace and return the next character.
+    fn peek(&mut self) -> Option<u8> {
+        while self
+            .input
+            .get(self.position)
+            .map_or(false, u8::is_ascii_whitespace)
+        {
+            self.position += 1;
+        }
+        self.input.get(self.position).copied()
+    }
+
+    fn unexpected(&self) -> Error {
+        if self.position < self.input.len() {
+            Error::UnexpectedCharacter {
+                position: self.position,
+            }
+        } else {
+            Error::UnexpectedEnd
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), Error> {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    // expression = term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<RationalExpression, Error> {
+        let mut result = self.term()?;
+        loop {
+            match self.peek() {
+                Some(b'+') => {
+                    self.position += 1;
+                    result = result + self.term()?;
+                }
+                Some(b'-') => {
+                    self.position += 1;
+                    result = result - self.term()?;
+                }
+                _ => return Ok(result),
+            }
+        }
+    }
+
+    // term = unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<RationalExpression, Error> {
+        let mut result = self.unary()?;
+        loop {
+            match self.peek() {
+                Some(b'*') => {
+                    self.position += 1;
+                    result = result * self.unary()?;
+                }
+                Some(b'/') => {
+                    self.position += 1;
+                    result = result / self.unary()?;
+                }
+                _ => return Ok(result),
+            }
+        }
+    }
+
+    // unary = '-' unary | power
+    fn unary(&mut self) -> Result<RationalExpression, Error> {
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+            Ok(self.unary()?.neg())
+        } else {
+            self.power()
+        }
+    }
+
+    // power = atom ('^' integer)?
+    fn power(&mut self) -> Result<RationalExpression, Error> {
+        let base = self.atom()?;
+        if self.peek() == Some(b'^') {
+            self.position += 1;
+            Ok(base.pow(self.integer()?))
+        } else {
+            Ok(base)
+        }
+    }
+
+    // atom = '(' expression ')' | number | 'x' | 'trace(' integer ',' integer ')'
+    //      | 'inv(' expression ')'
+    fn atom(&mut self) -> Result<RationalExpression, Error> {
+        use RationalExpression::*;
+        match self.peek() {
+            Some(b'(') => {
+                self.position += 1;
+                let result = self.expression()?;
+                self.expect(b')')?;
+                Ok(result)
+            }
+            Some(c) if c.is_ascii_digit() => Ok(Constant(self.number()?)),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let start = self.position;
+                while self
+                    .input
+                    .get(self.position)
+                    .map_or(false, |c| c.is_ascii_alphanumeric() || *c == b'_')
+                {
+                    self.position += 1;
+                }
+                match &self.input[start..self.position] {
+                    b"x" => Ok(X),
+                    b"trace" => {
+                        self.expect(b'(')?;
+                        let column = self.integer()?;
+                        self.expect(b',')?;
+                        let offset = self.offset()?;
+                        self.expect(b')')?;
+                        Ok(Trace(column, offset))
+                    }
+                    b"inv" => {
+                        self.expect(b'(')?;
+                        let result = self.expression()?;
+                        self.expect(b')')?;
+                        Ok(result.inv())
+                    }
+                    _ => Err(Error::UnexpectedCharacter { position: start }),
+                }
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Read a run of characters satisfying `predicate`.
+    fn digits(&mut self, predicate: fn(&u8) -> bool) -> Result<&str, Error> {
+        let start = self.position;
+        while self.input.get(self.position).map_or(false, predicate) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(self.unexpected());
+        }
+        // Only ASCII digits were consumed
+        Ok(std::str::from_utf8(&self.input[start..self.position]).unwrap())
+    }
+
+    fn integer(&mut self) -> Result<usize, Error> {
+        let _ = self.peek();
+        let position = self.position;
+        self.digits(u8::is_ascii_digit)?
+            .parse()
+            .map_err(|_| Error::InvalidNumber { position })
+    }
+
+    fn offset(&mut self) -> Result<isize, Error> {
+        let negative = self.peek() == Some(b'-');
+        if negative {
+            self.position += 1;
+        }
+        let position = self.position;
+        let magnitude =
+            isize::try_from(self.integer()?).map_err(|_| Error::InvalidNumber { position })?;
+        Ok(if negative { -magnitude } else { magnitude })
+    }
+
+    /// Decimal or `0x` prefixed hexadecimal constant, reduced in the field.
+    fn number(&mut self) -> Result<FieldElement, Error> {
+        let (radix, digits) = if self.input[self.position..].starts_with(b"0x") {
+            self.position += 2;
+            (16, self.digits(u8::is_ascii_hexdigit)?)
+        } else {
+            (10, self.digits(u8::is_ascii_digit)?)
+        };
+        let radix_element = FieldElement::from(radix);
+        Ok(digits.chars().fold(FieldElement::zero(), |value, digit| {
+            // Only valid digits were consumed
+            value * &radix_element + FieldElement::from(digit.to_digit(radix).unwrap())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constraints::Constraints, RationalExpression::*};
+    use proptest::prelude::*;
+    use zkp_macros_decl::field_element;
+    use zkp_u256::U256;
+
+    fn parse(s: &str) -> Result<RationalExpression, Error> {
+        s.parse()
+    }
+
+    #[test]
+    fn parse_constraint() {
+        let expected = (Trace(0, 0) - X) / (X - 1);
+        assert_eq!(parse("(trace(0,0) - x) / (x - 1)"), Ok(expected.clone()));
+        assert_eq!(expected.to_string(), "(trace(0, 0) - x) / (x - 1)");
+        assert_eq!(parse(&expected.to_string()), Ok(expected.clone()));
+
+        let constraints = Constraints::from_expressions((1024, 1), vec![], vec![parse(
+            "(trace(0, 1) - trace(0, 0)^2) / (x^1024 - 1)",
+        )
+        .unwrap()])
+        .unwrap();
+        assert_eq!(
+            constraints.expressions()[0],
+            (Trace(0, 1) - Trace(0, 0).pow(2)) / (X.pow(1024) - 1)
+        );
+    }
+
+    #[test]
+    fn parse_precedence() {
+        assert_eq!(
+            parse("1 + 2 * x"),
+            Ok(Constant(1.into()) + Constant(2.into()) * X)
+        );
+        assert_eq!(parse("1 - 2 - 3"), Ok((Constant(1.into()) - 2) - 3));
+        assert_eq!(parse("-x^3"), Ok(X.pow(3).neg()));
+        assert_eq!(parse("(-x)^3"), Ok(X.neg().pow(3)));
+        assert_eq!(parse("x / x * x"), Ok((X / X) * X));
+        assert_eq!(parse("trace(2, -1)"), Ok(Trace(2, -1)));
+        assert_eq!(parse("inv(x)"), Ok(X.inv()));
+    }
+
+    #[test]
+    fn parse_constants() {
+        assert_eq!(parse("1234"), Ok(Constant(1234.into())));
+        assert_eq!(
+            parse("0x0548c135e26faa9c977fb2eda057b54b2e0baa9a77a0be7c80278f4f03462d4c"),
+            Ok(Constant(field_element!(
+                "0548c135e26faa9c977fb2eda057b54b2e0baa9a77a0be7c80278f4f03462d4c"
+            )))
+        );
+        assert_eq!(parse("0xff"), Ok(Constant(255.into())));
+    }
+
+    #[test]
+    fn parse_errors() {
+        use Error::*;
+        assert_eq!(parse(""), Err(UnexpectedEnd));
+        assert_eq!(parse("x +"), Err(UnexpectedEnd));
+        assert_eq!(parse("(x"), Err(UnexpectedEnd));
+        assert_eq!(parse("x )"), Err(UnexpectedCharacter { position: 2 }));
+        assert_eq!(parse("y"), Err(UnexpectedCharacter { position: 0 }));
+        assert_eq!(parse("trace(0)"), Err(UnexpectedCharacter { position: 7 }));
+        assert_eq!(parse("x^0x2"), Err(UnexpectedCharacter { position: 3 }));
+        assert_eq!(
+            parse("x^99999999999999999999999"),
+            Err(InvalidNumber { position: 2 })
+        );
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        let expressions = vec![
+            X,
+            Constant(field_element!(
+                "0548c135e26faa9c977fb2eda057b54b2e0baa9a77a0be7c80278f4f03462d4c"
+            )),
+            Trace(3, -2) * Trace(1, 5) - X.pow(7).neg(),
+            (X - 1).neg().pow(2),
+            X.pow(2).pow(3),
+            X.inv() / (Trace(0, 0) + 5).inv(),
+            Constant(FieldElement::zero() - FieldElement::from(1)) * X,
+        ];
+        for expression in expressions {
+            assert_eq!(parse(&expression.to_string()), Ok(expression));
+        }
+    }
+
+    fn arb_expression() -> impl Strategy<Value = RationalExpression> {
+        let leaf = prop_oneof![
+            Just(X),
+            any::<FieldElement>().prop_map(Constant),
+            (0_usize..4, -4_isize..4).prop_map(|(i, j)| Trace(i, j)),
+        ];
+        leaf.prop_recursive(4, 32, 2, |inner| {
+            prop_oneof![
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| a + b),
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| a - b),
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| a * b),
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| a / b),
+                inner.clone().prop_map(|a| a.neg()),
+                inner.clone().prop_map(|a| a.inv()),
+                (inner, 0_usize..8).prop_map(|(a, e)| a.pow(e)),
+            ]
+        })
+    }
+
+    proptest!(
+        #[test]
+        fn display_roundtrip_arbitrary(expression in arb_expression()) {
+            prop_assert_eq!(parse(&expression.to_string()), Ok(expression));
+        }
+    );
+}
@@ -0,0 +1,145 @@
+//! Building blocks of the FRI low-degree test.
+
+// False positive: attribute has a use
+#[allow(clippy::useless_attribute)]
+// False positive: Importing preludes is allowed
+#[allow(clippy::wildcard_imports)]
+use std::prelude::v1::*;
+
+use zkp_primefield::{
+    fft::permute, geometric_series::geometric_series, FieldElement, Inv, One, Root, SquareInline,
+};
+
+/// Fold a FRI layer into the next layer.
+///
+/// The `evaluations` are the values of a polynomial $P$ on the subgroup of
+/// size `evaluations.len()` in bit-reversed order, which is the layout the
+/// prover commits to. Each consecutive coset of `fold_factor` values is
+/// folded into a single value using the verifier challenge `beta`, halving
+/// the coset repeatedly as
+///
+/// $$
+/// (P(x) + P(-x)) + \frac{\beta}{x} (P(x) - P(-x))
+/// $$
+///
+/// while squaring `beta` after every halving. The result is the next layer
+/// on the subgroup of size `evaluations.len() / fold_factor`, again in
+/// bit-reversed order, and agrees with the values the verifier computes
+/// from the decommitted cosets.
+///
+/// # Panics
+///
+/// Panics if `fold_factor` is not a power of two or does not divide the
+/// number of evaluations, or if there is no root of unity of order
+/// `evaluations.len()`.
+pub fn fold_layer(
+    evaluations: &[FieldElement],
+    beta: &FieldElement,
+    fold_factor: usize,
+) -> Vec<FieldElement> {
+    assert!(
+        fold_factor.is_power_of_two(),
+        "Fold factor must be a power of two"
+    );
+    assert_eq!(
+        evaluations.len() % fold_factor,
+        0,
+        "Fold factor must divide the layer size"
+    );
+    let root_inv = FieldElement::root(evaluations.len())
+        .expect("No root of unity for layer size")
+        .inv()
+        .unwrap();
+    let mut x_inv: Vec<_> = geometric_series(&FieldElement::one(), &root_inv)
+        .take(evaluations.len() / 2)
+        .collect();
+    permute(&mut x_inv);
+    let mut result = Vec::with_capacity(evaluations.len() / fold_factor);
+    fold_layer_into(&mut result, evaluations, &x_inv, beta, fold_factor);
+    result
+}
+
+/// Fold `layer` and append the result to `next_layer`.
+///
+/// `x_inv` holds the inverses of the first half of the domain in bit-reversed
+/// order. Because of the bit-reversal a table computed for a larger domain
+/// also works for all the smaller layers.
+pub(crate) fn fold_layer_into<E: Extend<FieldElement>>(
+    next_layer: &mut E,
+    layer: &[FieldElement],
+    x_inv: &[FieldElement],
+    beta: &FieldElement,
+    fold_factor: usize,
+) {
+    // OPT: Parallelization
+    let mut buffer = Vec::with_capacity(fold_factor);
+    next_layer.extend(layer.chunks(fold_factor).enumerate().map(|(index, coset)| {
+        buffer.clear();
+        buffer.extend_from_slice(coset);
+        let mut beta = beta.clone();
+        let mut size = coset.len();
+        while size > 1 {
+            size /= 2;
+            for k in 0..size {
+                let p0 = &buffer[2 * k];
+                let p1 = &buffer[2 * k + 1];
+                let folded = (p0 + p1) + &beta * &x_inv[index * size + k] * (p0 - p1);
+                buffer[k] = folded;
+            }
+            beta = beta.square();
+        }
+        buffer[0].clone()
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{polynomial::DensePolynomial, verifier::fri_fold};
+    use proptest::{collection::vec as prop_vec, prelude::*};
+    use zkp_primefield::{fft::permute_index, geometric_series::root_series, Fft, Pow, Zero};
+
+    fn evaluate_layer(p: &DensePolynomial, size: usize) -> Vec<FieldElement> {
+        let root = FieldElement::root(size).unwrap();
+        (0..size)
+            .map(|i| p.evaluate(&root.pow(permute_index(size, i))))
+            .collect()
+    }
+
+    proptest!(
+        #[test]
+        fn fold_stays_low_degree(
+            coefficients in prop_vec(FieldElement::arbitrary(), 16),
+            beta: FieldElement,
+            log_fold in 0_usize..4,
+        ) {
+            let size = 128;
+            let fold_factor = 1 << log_fold;
+            let p = DensePolynomial::new(&coefficients);
+            let mut next = fold_layer(&evaluate_layer(&p, size), &beta, fold_factor);
+            prop_assert_eq!(next.len(), size / fold_factor);
+
+            // Interpolate and check that only the low coefficients are used.
+            permute(&mut next);
+            next.ifft();
+            permute(&mut next);
+            let degree_bound = coefficients.len() / fold_factor;
+            prop_assert!(next[degree_bound..].iter().all(FieldElement::is_zero));
+        }
+
+        #[test]
+        fn fold_matches_verifier(
+            layer in prop_vec(FieldElement::arbitrary(), 64),
+            beta: FieldElement,
+            log_fold in 1_usize..4,
+        ) {
+            let fold_factor = 1 << log_fold;
+            let eval_x: Vec<FieldElement> = root_series(layer.len()).collect();
+            let next = fold_layer(&layer, &beta, fold_factor);
+            for (i, coset) in layer.chunks(fold_factor).enumerate() {
+                let expected = fri_fold(coset, &beta, 1, (fold_factor / 2) * i, layer.len(), &eval_x);
+                prop_assert_eq!(&next[i], &expected);
+            }
+        }
+    );
+}
@@ -49,9 +49,12 @@
 
 mod channel;
 mod constraints;
+mod expression_parser;
+pub mod fri;
 mod polynomial;
 mod proof;
 mod proof_of_work;
+mod proof_params;
 mod rational_expression;
 #[cfg(feature = "std")]
 mod solidity_seralizer;
@@ -60,17 +63,16 @@ mod solidity_verifier;
 mod traits;
 mod verifier;
 
-// Optional prover functionality. Note that prover requires std.
-// TODO: Make it work without std.
+// Optional prover functionality. Without std it runs single-threaded.
 #[cfg(feature = "prover")]
-mod algebraic_dag;
+pub mod algebraic_dag;
 #[cfg(feature = "prover")]
 pub mod component;
 #[cfg(feature = "prover")]
 mod constraint_check;
 #[cfg(feature = "prover")]
 mod prover;
-#[cfg(feature = "prover")]
+#[cfg(all(feature = "prover", feature = "std"))]
 mod rational_equality;
 #[cfg(feature = "prover")]
 mod trace_table;
@@ -85,13 +87,23 @@ extern crate no_std_compat as std;
 pub use zkp_primefield as primefield;
 
 // Exports for verifier
-pub use constraints::{Constraints, Error as ConstraintError};
-pub use polynomial::DensePolynomial;
+pub use channel::{Error as ChannelError, TranscriptEvent};
+pub use constraints::{Constraints, Error as ConstraintError, OodsResult};
+pub use expression_parser::Error as ExpressionParseError;
+pub use polynomial::{DensePolynomial, SparsePolynomial};
 pub use proof::Proof;
+pub use proof_of_work::verify_pow;
+pub use proof_params::{Error as ProofParamsError, ProofParams, ProofParamsBuilder};
 pub use rational_expression::RationalExpression;
 pub use traits::Verifiable;
-pub use verifier::{verify, Error as VerifierError};
+#[cfg(feature = "std")]
+pub use verifier::verify_reader;
+pub use verifier::{
+    verify, verify_with_digest, verify_with_hasher, verify_with_transcript, Error as VerifierError,
+};
 
+#[cfg(feature = "std")]
+pub use polynomial::lde;
 // We want std for this so that we can use hex encode
 #[cfg(feature = "std")]
 pub use solidity_seralizer::proof_serialize;
@@ -102,9 +114,18 @@ pub use solidity_verifier::generate;
 #[cfg(feature = "prover")]
 pub use constraint_check::check_constraints;
 #[cfg(feature = "prover")]
-pub use prover::{prove, Error as ProverError};
+pub use proof_of_work::{grind_with_callback, PROGRESS_INTERVAL as POW_PROGRESS_INTERVAL};
+#[cfg(all(feature = "prover", feature = "std"))]
+pub use prover::prove_streaming;
+#[cfg(feature = "prover")]
+pub use prover::{
+    prove, prove_with_hasher, prove_with_polynomials, prove_with_transcript, Error as ProverError,
+    PolynomialKind,
+};
 #[cfg(feature = "prover")]
-pub use trace_table::TraceTable;
+pub use trace_table::{
+    Error as TraceTableError, Layout as TraceLayout, TraceTable, TraceView, TraceViewMut,
+};
 #[cfg(feature = "prover")]
 pub use traits::Provable;
 
@@ -1,20 +1,24 @@
 // TODO: Naming?
 #![allow(clippy::module_name_repetitions)]
-#[cfg(feature = "std")]
 use log::trace;
-use std::prelude::v1::*;
+use std::{
+    collections::BTreeMap,
+    ops::{Add, Mul},
+    prelude::v1::*,
+};
 use zkp_macros_decl::field_element;
 use zkp_mmap_vec::MmapVec;
 #[cfg(feature = "std")]
-use zkp_primefield::{fft::permute_index, Fft, Pow, Root};
-use zkp_primefield::{FieldElement, Zero};
+use zkp_primefield::{fft::permute, Inv};
+use zkp_primefield::{
+    fft::permute_index, geometric_series::geometric_series, Fft, FieldElement, One, Pow, Root, Zero,
+};
 use zkp_u256::U256;
 
 #[derive(Clone)]
 pub struct DensePolynomial(MmapVec<FieldElement>);
 
 // We normally don't want to spill thousands of coefficients in the logs.
-#[cfg(feature = "std")]
 impl std::fmt::Debug for DensePolynomial {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(fmt, "DensePolynomial(degree = {:?})", self.degree())
@@ -85,32 +89,106 @@ impl DensePolynomial {
         result
     }
 
-    #[cfg(feature = "std")]
+    /// Scale the variable by a constant.
+    ///
+    /// Returns P(factor · X) by multiplying coefficient i by factor^i.
+    pub fn scale(&self, factor: &FieldElement) -> Self {
+        let mut result = self.clone();
+        for (coefficient, power) in result
+            .0
+            .iter_mut()
+            .zip(geometric_series(&FieldElement::one(), factor))
+        {
+            *coefficient *= power;
+        }
+        result
+    }
+
+    /// Convert a polynomial from one coset of the domain to another.
+    ///
+    /// The result evaluates on the coset to · H to the same values as P does
+    /// on from · H. That is, it returns P((from / to) · X).
+    pub fn shift_domain(&self, from: &FieldElement, to: &FieldElement) -> Self {
+        self.scale(&(from / to))
+    }
+
+    /// Divide by the vanishing polynomial X^n - 1.
+    ///
+    /// Returns the quotient and remainder. The structure of the divisor means
+    /// each step of the long division is a single addition, so this takes time
+    /// linear in the length of the polynomial.
+    pub fn divide_by_vanishing(&self, n: usize) -> (Self, Self) {
+        assert!(n > 0);
+        let mut remainder = self.0.to_vec();
+        let mut quotient =
+            Self::zeros(std::cmp::max(1, self.len().saturating_sub(n)).next_power_of_two());
+        for i in (n..self.len()).rev() {
+            let coefficient = remainder[i].clone();
+            remainder[i - n] += &coefficient;
+            quotient.0[i - n] = coefficient;
+        }
+        remainder.truncate(n);
+        remainder.resize(remainder.len().next_power_of_two(), FieldElement::zero());
+        (quotient, Self::new(&remainder))
+    }
+
     pub fn low_degree_extension(&self, blowup: usize) -> MmapVec<FieldElement> {
         trace!("BEGIN Low degree extension");
         // TODO: shift polynomial by FieldElement::generator() outside of this function.
-        // TODO: Parameterize cofactor
-        let shift_factor: FieldElement = FieldElement::generator();
         let length = self.len() * blowup;
-        let generator =
-            FieldElement::root(length).expect("No generator for extended_domain_length.");
 
         // FieldElement is safe to initialize zero (which maps to zero)
+        #[cfg(feature = "std")]
         #[allow(unsafe_code)]
         let mut result: MmapVec<FieldElement> = unsafe { MmapVec::zero_initialized(length) };
+        #[cfg(not(feature = "std"))]
+        let mut result: MmapVec<FieldElement> = {
+            let mut result = MmapVec::with_capacity(length);
+            result.resize(length, FieldElement::zero());
+            result
+        };
+        self.low_degree_extension_into(&mut result, &FieldElement::generator());
+        trace!("END Low degree extension");
+        result
+    }
+
+    /// Interpolate values on a coset back to coefficients.
+    ///
+    /// The `evaluations` are on `coset_shift` times the subgroup of size
+    /// `evaluations.len()` in bit-reversed order, the layout returned by
+    /// [`lde`]. This is the inverse of [`lde`]: the result has length
+    /// `evaluations.len()` with the original coefficients at the start and
+    /// zeros after.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `coset_shift` is zero or there is no root of unity of order
+    /// `evaluations.len()`.
+    #[cfg(feature = "std")]
+    pub fn interpolate_coset(evaluations: &[FieldElement], coset_shift: &FieldElement) -> Self {
+        let mut coefficients = evaluations.to_vec();
+        permute(&mut coefficients);
+        coefficients.ifft();
+        permute(&mut coefficients);
+        Self::new(&coefficients).scale(&coset_shift.inv().expect("Coset shift can not be zero"))
+    }
+
+    /// Evaluate on the coset `shift_factor` times the subgroup of size
+    /// `result.len()`, writing the values in bit-reversed order.
+    fn low_degree_extension_into(&self, result: &mut [FieldElement], shift_factor: &FieldElement) {
+        let generator =
+            FieldElement::root(result.len()).expect("No generator for extended_domain_length.");
+        let blowup = result.len() / self.len();
 
         // Compute cosets
         result
-            .as_mut_slice()
             .chunks_mut(self.len())
             .enumerate()
             .for_each(|(i, slice)| {
-                let cofactor = &shift_factor * generator.pow(permute_index(blowup, i));
+                let cofactor = shift_factor * generator.pow(permute_index(blowup, i));
                 slice.clone_shifted(&self.coefficients(), &cofactor);
                 slice.fft();
             });
-        trace!("END Low degree extension");
-        result
     }
 
     /// Divide out a point and add the scaled result to target.
@@ -127,9 +205,238 @@ impl DensePolynomial {
     }
 }
 
+impl Mul<&DensePolynomial> for &DensePolynomial {
+    type Output = DensePolynomial;
+
+    fn mul(self, other: &DensePolynomial) -> DensePolynomial {
+        // Below this size the quadratic algorithm is faster.
+        const FFT_THRESHOLD: usize = 64;
+        let size = (self.degree() + other.degree() + 1).next_power_of_two();
+        if cfg!(feature = "std") && size > FFT_THRESHOLD {
+            #[cfg(feature = "std")]
+            return self.mul_fft(other, size);
+        }
+        let mut result = DensePolynomial::zeros(size);
+        for (i, a) in self.0.iter().enumerate().take(self.degree() + 1) {
+            for (j, b) in other.0.iter().enumerate().take(other.degree() + 1) {
+                result.0[i + j] += a * b;
+            }
+        }
+        result
+    }
+}
+
+impl DensePolynomial {
+    // Multiply by evaluating on the size-th roots of unity. The product has
+    // degree less than `size`, so it is recovered exactly.
+    #[cfg(feature = "std")]
+    fn mul_fft(&self, other: &Self, size: usize) -> Self {
+        let evaluate = |polynomial: &Self| {
+            let mut values = MmapVec::with_capacity(size);
+            values.extend_from_slice(&polynomial.0[..=polynomial.degree()]);
+            values.resize(size, FieldElement::zero());
+            values.fft();
+            values
+        };
+        let mut product = evaluate(self);
+        for (a, b) in product.iter_mut().zip(evaluate(other).iter()) {
+            *a *= b;
+        }
+        // Values are in permuted order, but `ifft` requires natural order.
+        permute(&mut product);
+        product.ifft();
+        permute(&mut product);
+        Self::from_mmap_vec(product)
+    }
+}
+
+/// A polynomial stored as a map from exponent to non-zero coefficient.
+///
+/// Arithmetic keeps the result sparse. Use `to_dense` to convert.
+#[derive(Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct SparsePolynomial(BTreeMap<usize, FieldElement>);
+
+impl SparsePolynomial {
+    // Terms are given as (coefficient, exponent) pairs. E.g. &[(1, 0), (2, 1)]
+    // corresponds to the polynomial f(x) = 1 + 2x. Repeated exponents are
+    // summed.
+    pub fn new(terms: &[(FieldElement, usize)]) -> Self {
+        let mut result = Self::default();
+        for (coefficient, exponent) in terms {
+            result.add_term(coefficient, *exponent);
+        }
+        result
+    }
+
+    /// The polynomial $x^n - 1$ that vanishes on the subgroup of size $n$.
+    pub fn vanishing(domain_size: usize) -> Self {
+        Self::new(&[
+            (FieldElement::one(), domain_size),
+            (-FieldElement::one(), 0),
+        ])
+    }
+
+    /// The polynomial $(x^n - 1) / (x - r)$ that vanishes on the subgroup of
+    /// size $n$ except at the root $r$.
+    ///
+    /// The quotient is computed symbolically as $\sum_{k < n} r^{n - 1 - k}
+    /// x^k$, so unlike [`vanishing`](Self::vanishing) the result has $n$
+    /// terms.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `excluded_root` is not an $n$-th root of unity.
+    pub fn vanishing_except(domain_size: usize, excluded_root: &FieldElement) -> Self {
+        assert!(
+            excluded_root.pow(domain_size).is_one(),
+            "Excluded point is not in the domain"
+        );
+        let mut powers: Vec<_> = geometric_series(&FieldElement::one(), excluded_root)
+            .take(domain_size)
+            .collect();
+        powers.reverse();
+        Self::new(
+            &powers
+                .into_iter()
+                .enumerate()
+                .map(|(exponent, coefficient)| (coefficient, exponent))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// The polynomial $p(x^r)$ where $p$ has the given coefficients and $r$ is
+    /// `repetitions`.
+    ///
+    /// This describes a periodic column. On a trace of length $n$ the values
+    /// repeat every $n / r$ rows, so if $p$ interpolates one period on the
+    /// roots of unity of order $n / r$, the trace column is that period
+    /// repeated $r$ times.
+    pub fn periodic(coefficients: &[FieldElement], repetitions: usize) -> Self {
+        Self::new(
+            &coefficients
+                .iter()
+                .enumerate()
+                .map(|(i, coefficient)| (coefficient.clone(), i * repetitions))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn add_term(&mut self, coefficient: &FieldElement, exponent: usize) {
+        let sum = self
+            .0
+            .get(&exponent)
+            .map_or_else(|| coefficient.clone(), |c| c + coefficient);
+        if sum.is_zero() {
+            let _ = self.0.remove(&exponent);
+        } else {
+            let _ = self.0.insert(exponent, sum);
+        }
+    }
+
+    /// Iterate over the non-zero (coefficient, exponent) pairs in order of
+    /// ascending exponent.
+    pub fn terms(&self) -> impl Iterator<Item = (&FieldElement, usize)> {
+        self.0
+            .iter()
+            .map(|(exponent, coefficient)| (coefficient, *exponent))
+    }
+
+    /// The number of non-zero terms.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Like for `DensePolynomial`, the zero polynomial has degree 0.
+    pub fn degree(&self) -> usize {
+        self.0.keys().next_back().copied().unwrap_or(0)
+    }
+
+    pub fn evaluate(&self, x: &FieldElement) -> FieldElement {
+        self.terms()
+            .map(|(coefficient, exponent)| coefficient * x.pow(exponent))
+            .sum()
+    }
+
+    /// Convert to a `DensePolynomial` with a power of two length.
+    pub fn to_dense(&self) -> DensePolynomial {
+        let mut result = DensePolynomial::zeros((self.degree() + 1).next_power_of_two());
+        for (coefficient, exponent) in self.terms() {
+            result.0[exponent] = coefficient.clone();
+        }
+        result
+    }
+}
+
+impl Add<&SparsePolynomial> for &SparsePolynomial {
+    type Output = SparsePolynomial;
+
+    fn add(self, other: &SparsePolynomial) -> SparsePolynomial {
+        let mut result = self.clone();
+        for (coefficient, exponent) in other.terms() {
+            result.add_term(coefficient, exponent);
+        }
+        result
+    }
+}
+
+impl Add for SparsePolynomial {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        &self + &other
+    }
+}
+
+impl Mul<&SparsePolynomial> for &SparsePolynomial {
+    type Output = SparsePolynomial;
+
+    fn mul(self, other: &SparsePolynomial) -> SparsePolynomial {
+        let mut result = SparsePolynomial::default();
+        for (a, i) in self.terms() {
+            for (b, j) in other.terms() {
+                result.add_term(&(a * b), i + j);
+            }
+        }
+        result
+    }
+}
+
+impl Mul for SparsePolynomial {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        &self * &other
+    }
+}
+
+/// Evaluate a polynomial on a coset of size `blowup * poly.len()`.
+///
+/// The evaluation domain is `coset_shift` times the subgroup of that size.
+/// The values are returned in bit-reversed order: index $i$ holds the value
+/// at $\mathtt{coset\_shift} \cdot \omega^{\mathrm{rev}(i)}$. With
+/// `FieldElement::generator()` as the shift this is the domain and layout the
+/// prover uses for the trace and constraint LDEs.
+///
+/// # Panics
+///
+/// Panics if `blowup` is not a power of two.
+#[cfg(feature = "std")]
+pub fn lde(poly: &DensePolynomial, blowup: usize, coset_shift: &FieldElement) -> Vec<FieldElement> {
+    assert!(blowup.is_power_of_two(), "Blowup must be a power of two");
+    let mut result = vec![FieldElement::zero(); poly.len() * blowup];
+    poly.low_degree_extension_into(&mut result, coset_shift);
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::{collection::vec as prop_vec, prelude::*};
 
     fn dense_polynomial(coefficients: &[isize]) -> DensePolynomial {
         DensePolynomial::new(
@@ -140,9 +447,213 @@ mod tests {
         )
     }
 
+    #[test]
+    fn lde_matches_prover() {
+        let p = dense_polynomial(&[3, 1, 4, 1, 5, 9, 2, 6]);
+        assert_eq!(
+            lde(&p, 4, &FieldElement::generator()),
+            p.low_degree_extension(4).to_vec()
+        );
+    }
+
     #[test]
     fn example_evaluate() {
         let p = dense_polynomial(&[1, 0, 0, 2]);
         assert_eq!(p.evaluate(&FieldElement::from(2)), FieldElement::from(17));
     }
+
+    proptest!(
+        #[test]
+        fn scale_evaluate(
+            coefficients in prop_vec(FieldElement::arbitrary(), 8),
+            factor: FieldElement,
+            x: FieldElement,
+        ) {
+            let p = DensePolynomial::new(&coefficients);
+            prop_assert_eq!(p.scale(&factor).evaluate(&x), p.evaluate(&(&factor * &x)));
+        }
+
+        #[test]
+        fn lde_evaluate(
+            coefficients in prop_vec(FieldElement::arbitrary(), 8),
+            log_blowup in 0_usize..4,
+            coset_shift: FieldElement,
+        ) {
+            let p = DensePolynomial::new(&coefficients);
+            let blowup = 1 << log_blowup;
+            let values = lde(&p, blowup, &coset_shift);
+            let length = p.len() * blowup;
+            let root = FieldElement::root(length).unwrap();
+            prop_assert_eq!(values.len(), length);
+            for (i, value) in values.iter().enumerate() {
+                let x = &coset_shift * root.pow(permute_index(length, i));
+                prop_assert_eq!(value, &p.evaluate(&x));
+            }
+        }
+
+        #[test]
+        fn lde_interpolate_coset(
+            coefficients in prop_vec(FieldElement::arbitrary(), 8),
+            log_blowup in 0_usize..4,
+            coset_shift: FieldElement,
+        ) {
+            prop_assume!(!coset_shift.is_zero());
+            let p = DensePolynomial::new(&coefficients);
+            let values = lde(&p, 1 << log_blowup, &coset_shift);
+            let result = DensePolynomial::interpolate_coset(&values, &coset_shift);
+            prop_assert_eq!(result.len(), values.len());
+            prop_assert_eq!(&result.coefficients()[..8], &coefficients[..]);
+            prop_assert!(result.coefficients()[8..].iter().all(FieldElement::is_zero));
+        }
+
+        #[test]
+        fn shift_domain_evaluate(
+            coefficients in prop_vec(FieldElement::arbitrary(), 8),
+            from: FieldElement,
+            to: FieldElement,
+            x: FieldElement,
+        ) {
+            prop_assume!(!to.is_zero());
+            let p = DensePolynomial::new(&coefficients);
+            prop_assert_eq!(
+                p.shift_domain(&from, &to).evaluate(&(&to * &x)),
+                p.evaluate(&(&from * &x))
+            );
+        }
+
+        #[test]
+        fn sparse_add_evaluate(
+            a in prop_vec((FieldElement::arbitrary(), 0_usize..100), 0..8),
+            b in prop_vec((FieldElement::arbitrary(), 0_usize..100), 0..8),
+            x: FieldElement,
+        ) {
+            let a = SparsePolynomial::new(&a);
+            let b = SparsePolynomial::new(&b);
+            let sum = &a + &b;
+            prop_assert!(sum.len() <= a.len() + b.len());
+            prop_assert_eq!(sum.evaluate(&x), a.evaluate(&x) + b.evaluate(&x));
+            prop_assert_eq!(
+                sum.to_dense().evaluate(&x),
+                a.to_dense().evaluate(&x) + b.to_dense().evaluate(&x)
+            );
+        }
+
+        #[test]
+        fn sparse_mul_evaluate(
+            a in prop_vec((FieldElement::arbitrary(), 0_usize..100), 0..8),
+            b in prop_vec((FieldElement::arbitrary(), 0_usize..100), 0..8),
+            x: FieldElement,
+        ) {
+            let a = SparsePolynomial::new(&a);
+            let b = SparsePolynomial::new(&b);
+            let product = &a * &b;
+            prop_assert!(product.len() <= a.len() * b.len());
+            prop_assert_eq!(product.evaluate(&x), a.evaluate(&x) * b.evaluate(&x));
+            prop_assert_eq!(
+                product.to_dense().evaluate(&x),
+                a.to_dense().evaluate(&x) * b.to_dense().evaluate(&x)
+            );
+        }
+
+        #[test]
+        fn mul_evaluate(
+            a in prop_vec(FieldElement::arbitrary(), 64),
+            b in prop_vec(FieldElement::arbitrary(), 4),
+            x: FieldElement,
+        ) {
+            let a = DensePolynomial::new(&a);
+            let b = DensePolynomial::new(&b);
+            prop_assert_eq!((&a * &b).evaluate(&x), a.evaluate(&x) * b.evaluate(&x));
+            prop_assert_eq!((&b * &b).evaluate(&x), b.evaluate(&x) * b.evaluate(&x));
+        }
+
+        #[test]
+        fn divide_by_vanishing_evaluate(
+            coefficients in prop_vec(FieldElement::arbitrary(), 32),
+            n in 1_usize..40,
+            x: FieldElement,
+        ) {
+            let p = DensePolynomial::new(&coefficients);
+            let (quotient, remainder) = p.divide_by_vanishing(n);
+            prop_assert!(remainder.len() < 2 * n);
+            prop_assert_eq!(
+                quotient.evaluate(&x) * (x.pow(n) - FieldElement::one()) + remainder.evaluate(&x),
+                p.evaluate(&x)
+            );
+        }
+    );
+
+    fn mul_naive(a: &DensePolynomial, b: &DensePolynomial) -> Vec<FieldElement> {
+        let mut result = vec![FieldElement::zero(); a.len() + b.len()];
+        for (i, a) in a.coefficients().iter().enumerate() {
+            for (j, b) in b.coefficients().iter().enumerate() {
+                result[i + j] += a * b;
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn mul_large() {
+        let random = |seed: usize| {
+            (0..1024)
+                .map(|i| FieldElement::from(i * i + seed).pow(7_usize))
+                .collect::<Vec<_>>()
+        };
+        let mut a = random(1);
+        let mut b = random(2);
+        a.truncate(1001);
+        b.truncate(1001);
+        a.resize(1024, FieldElement::zero());
+        b.resize(1024, FieldElement::zero());
+        let a = DensePolynomial::new(&a);
+        let b = DensePolynomial::new(&b);
+        let product = &a * &b;
+        assert_eq!(product.len(), 2048);
+        assert_eq!(product.coefficients(), &mul_naive(&a, &b)[..2048]);
+        for x in random(3).iter().take(4) {
+            assert_eq!(product.evaluate(x), a.evaluate(x) * b.evaluate(x));
+        }
+    }
+
+    #[test]
+    fn sparse_cancellation() {
+        let one = FieldElement::one();
+        let a = SparsePolynomial::new(&[(one.clone(), 0), (one.clone(), 16)]);
+        let b = SparsePolynomial::new(&[(-&one, 16)]);
+        assert_eq!(a + b, SparsePolynomial::new(&[(one, 0)]));
+    }
+
+    #[test]
+    fn sparse_vanishing() {
+        let root = FieldElement::root(16).unwrap();
+        let vanishing = SparsePolynomial::vanishing(16);
+        let except = SparsePolynomial::vanishing_except(16, &root.pow(15_usize));
+        assert_eq!(vanishing.len(), 2);
+        assert_eq!(except.degree(), 15);
+        for i in 0..16_usize {
+            let x = root.pow(i);
+            assert!(vanishing.evaluate(&x).is_zero());
+            assert_eq!(except.evaluate(&x).is_zero(), i != 15);
+        }
+        let x = FieldElement::from(5);
+        assert_eq!(
+            except.evaluate(&x) * (&x - root.pow(15_usize)),
+            vanishing.evaluate(&x)
+        );
+    }
+
+    #[test]
+    fn sparse_periodic() {
+        let coefficients: Vec<_> = (1..=4).map(FieldElement::from).collect();
+        let periodic = SparsePolynomial::periodic(&coefficients, 4);
+        assert_eq!(periodic.degree(), 12);
+        let root = FieldElement::root(16).unwrap();
+        for i in 0..4_usize {
+            let value = periodic.evaluate(&root.pow(i));
+            for j in 1..4_usize {
+                assert_eq!(periodic.evaluate(&root.pow(i + 4 * j)), value);
+            }
+        }
+    }
 }
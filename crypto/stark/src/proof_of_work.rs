@@ -1,11 +1,14 @@
+#[cfg(feature = "prover")]
+use log::info;
 #[cfg(all(feature = "std", feature = "prover"))]
-use log::{info, trace};
+use log::trace;
 #[cfg(all(feature = "std", feature = "prover"))]
 use rayon::prelude::*;
 use std::convert::TryFrom;
 #[cfg(all(feature = "std", feature = "prover"))]
 use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
 use tiny_keccak::{Hasher, Keccak};
+use zkp_hash::Hash;
 use zkp_macros_decl::hex;
 use zkp_u256::{Binary, U256};
 
@@ -99,6 +102,55 @@ impl Challenge {
     }
 }
 
+/// Number of nonces tried between calls to the progress callback of
+/// [`grind_with_callback`].
+#[cfg(feature = "prover")]
+pub const PROGRESS_INTERVAL: u64 = 1 << 16;
+
+fn challenge_from_hash(challenge: &Hash, bits: usize) -> Challenge {
+    let mut seed = [0_u8; 32];
+    seed.copy_from_slice(challenge.as_bytes());
+    ChallengeSeed::from_bytes(seed).with_difficulty(bits)
+}
+
+/// Check that `nonce` solves the proof of work for `challenge` with `bits`
+/// bits of difficulty.
+///
+/// This is the same check the verifier does on the proof of work in a proof.
+pub fn verify_pow(challenge: &Hash, nonce: u64, bits: usize) -> bool {
+    challenge_from_hash(challenge, bits).verify(Response { nonce })
+}
+
+/// Find the smallest nonce that solves the proof of work, reporting progress.
+///
+/// Every [`PROGRESS_INTERVAL`] nonces the callback is called with the number
+/// of nonces tried so far, for example to update a progress bar. On average
+/// `2^bits` nonces are needed. With `std` each interval is searched in
+/// parallel on the rayon thread pool, the callback is only called from the
+/// current thread.
+#[cfg(feature = "prover")]
+pub fn grind_with_callback<F>(challenge: &Hash, bits: usize, mut progress: F) -> u64
+where
+    F: FnMut(u64),
+{
+    let challenge = challenge_from_hash(challenge, bits);
+    let mut start = 0;
+    loop {
+        #[cfg(feature = "std")]
+        let found = (start..start + PROGRESS_INTERVAL)
+            .into_par_iter()
+            .find_first(|&nonce| challenge.verify(Response { nonce }));
+        #[cfg(not(feature = "std"))]
+        let found =
+            (start..start + PROGRESS_INTERVAL).find(|&nonce| challenge.verify(Response { nonce }));
+        if let Some(nonce) = found {
+            return nonce;
+        }
+        start += PROGRESS_INTERVAL;
+        progress(start);
+    }
+}
+
 impl Response {
     pub(crate) fn from_nonce(nonce: u64) -> Self {
         Self { nonce }
@@ -138,4 +190,43 @@ mod tests {
         assert_eq!(response.nonce, 138);
         assert!(challenge.verify(response));
     }
+
+    #[test]
+    fn grind_test() {
+        let challenge = Hash::new(hex!(
+            "0123456789abcded0123456789abcded0123456789abcded0123456789abcded"
+        ));
+        let mut reports = Vec::new();
+        let nonce = grind_with_callback(&challenge, 8, |tried| reports.push(tried));
+        assert_eq!(nonce, 138);
+        assert!(reports.is_empty());
+        assert!(verify_pow(&challenge, nonce, 8));
+        assert!(!verify_pow(&challenge, nonce - 1, 8));
+
+        let nonce = grind_with_callback(&challenge, 18, |tried| reports.push(tried));
+        assert!(verify_pow(&challenge, nonce, 18));
+        assert_eq!(reports.len() as u64, nonce / PROGRESS_INTERVAL);
+        assert!(reports
+            .iter()
+            .enumerate()
+            .all(|(i, &tried)| tried == (i as u64 + 1) * PROGRESS_INTERVAL));
+    }
+
+    #[test]
+    fn grind_difficulties() {
+        let challenge = Hash::new([0x5a; 32]);
+        for bits in (0..=20).step_by(4) {
+            let nonce = grind_with_callback(&challenge, bits, |_| {});
+            assert!(verify_pow(&challenge, nonce, bits));
+            assert_eq!(challenge_from_hash(&challenge, bits).solve().nonce(), nonce);
+        }
+    }
+
+    #[test]
+    fn zero_difficulty() {
+        let challenge = Hash::new([0xab; 32]);
+        let nonce = grind_with_callback(&challenge, 0, |_| panic!("No progress expected"));
+        assert_eq!(nonce, 0);
+        assert!(verify_pow(&challenge, 12345, 0));
+    }
 }
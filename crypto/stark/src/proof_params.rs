@@ -0,0 +1,432 @@
+// TODO: Naming?
+#![allow(clippy::module_name_repetitions)]
+use std::{convert::TryFrom, fmt, prelude::v1::*};
+
+/// Magic bytes at the start of serialized [`ProofParams`].
+const MAGIC: [u8; 4] = *b"ZKPP";
+
+/// Version of the serialization format written by
+/// [`ProofParams::to_bytes`].
+const VERSION: u8 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    InvalidTraceLength,
+    BlowupNotPowerOfTwo(usize),
+    InvalidFriReduction(usize),
+    FriLayoutTooLong {
+        reductions: usize,
+        maximum:    usize,
+    },
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    Truncated,
+    TrailingBytes,
+    ValueOutOfRange,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+        match *self {
+            InvalidTraceLength => write!(f, "Invalid trace length (must be power of two)"),
+            BlowupNotPowerOfTwo(blowup) => {
+                write!(f, "Blowup factor {} is not a power of two", blowup)
+            }
+            InvalidFriReduction(reduction) => {
+                write!(
+                    f,
+                    "FRI layout step {} is not supported (must be between 1 and 4)",
+                    reduction
+                )
+            }
+            FriLayoutTooLong {
+                reductions,
+                maximum,
+            } => {
+                write!(
+                    f,
+                    "FRI layout does {} reductions, but the trace allows at most {}",
+                    reductions, maximum
+                )
+            }
+            InvalidMagic => write!(f, "Input is not serialized proof parameters"),
+            UnsupportedVersion(version) => {
+                write!(f, "Unsupported proof parameter format version {}", version)
+            }
+            Truncated => write!(f, "Serialized proof parameters are truncated"),
+            TrailingBytes => write!(f, "Unexpected bytes after the proof parameters"),
+            ValueOutOfRange => write!(f, "Proof parameter does not fit in a usize"),
+        }
+    }
+}
+
+/// Parameters that determine how a proof is computed.
+///
+/// These trade off between security, prover time, verifier time and proof
+/// size. See the fields of [`Constraints`](crate::Constraints) for their
+/// meaning.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ProofParams {
+    pub blowup:      usize,
+    pub pow_bits:    usize,
+    pub num_queries: usize,
+    pub fri_layout:  Vec<usize>,
+}
+
+impl ProofParams {
+    /// Start building parameters for a trace with `trace_nrows` rows.
+    ///
+    /// The defaults are the same as for `Constraints::from_expressions`.
+    pub fn builder(trace_nrows: usize) -> ProofParamsBuilder {
+        ProofParamsBuilder {
+            trace_nrows,
+            params: Self {
+                blowup:      16,
+                pow_bits:    0,
+                num_queries: 45,
+                fri_layout:  Self::default_fri_layout(trace_nrows),
+            },
+        }
+    }
+
+    /// Parameters for about 96 bits of conjectured security.
+    ///
+    /// For a trace of `2^log_trace_nrows` rows. This is a reasonable starting
+    /// point for production use; increase `num_queries` for more security or
+    /// `pow_bits` to trade prover time for a smaller proof.
+    pub fn minimal_secure(log_trace_nrows: usize) -> Self {
+        Self {
+            blowup:      16,
+            pow_bits:    16,
+            num_queries: 40,
+            fri_layout:  Self::default_fri_layout(1 << log_trace_nrows),
+        }
+    }
+
+    /// Parameters for fast proofs in tests and examples.
+    ///
+    /// **Warning**: These proofs are *not secure*. They use a handful of
+    /// queries and no proof of work, so a forged proof will be accepted with
+    /// high probability.
+    pub fn fast_insecure(log_trace_nrows: usize) -> Self {
+        Self {
+            blowup:      8,
+            pow_bits:    0,
+            num_queries: 4,
+            fri_layout:  Self::default_fri_layout(1 << log_trace_nrows),
+        }
+    }
+
+    /// Conservative estimate of the bits of security of the proof.
+    pub fn security_bits(&self) -> usize {
+        // Our conservative formula is (1/2^blowup_log)^(queries/2)*(1/2^pow_bits)
+        // So the bit security should be blowup_log*(queries/2) + pow_bits
+        let blowup_log = self.blowup.trailing_zeros() as usize;
+        blowup_log * (self.num_queries / 2) + self.pow_bits
+    }
+
+    /// Parameters for a trace of `trace_nrows` rows with at least
+    /// `target_bits` of [`security_bits`](Self::security_bits).
+    ///
+    /// Uses a blowup of 16 and takes a fifth of the target from proof of
+    /// work, then adds queries until the target is met.
+    pub fn for_security(trace_nrows: usize, target_bits: usize) -> Self {
+        let mut params = Self {
+            blowup:      16,
+            pow_bits:    target_bits / 5,
+            num_queries: 0,
+            fri_layout:  Self::default_fri_layout(trace_nrows),
+        };
+        while params.security_bits() < target_bits {
+            params.num_queries += 2;
+        }
+        params
+    }
+
+    pub(crate) fn default_fri_layout(trace_nrows: usize) -> Vec<usize> {
+        // The binary logarithm of the final layer polynomial degree.
+        const LOG2_TARGET: usize = 8;
+
+        // Number of reductions to reach target degree
+        // TODO: For very small traces we fold to a constant, but this is not
+        // necessarily optimal.
+        let log2_trace = trace_nrows.trailing_zeros() as usize;
+        let num_reductions = if log2_trace > LOG2_TARGET {
+            log2_trace - LOG2_TARGET
+        } else {
+            log2_trace
+        };
+
+        // Do as many three reductions as possible
+        let mut fri_layout = vec![3; num_reductions / 3];
+        if num_reductions % 3 != 0 {
+            fri_layout.push(num_reductions % 3);
+        }
+        fri_layout
+    }
+
+    /// Serialize to a compact byte format.
+    ///
+    /// The layout is the magic bytes `ZKPP`, a version byte, then
+    /// `blowup`, `pow_bits` and `num_queries` as little-endian `u64`s and
+    /// finally the number of FRI steps followed by one byte per step.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(30 + self.fri_layout.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(VERSION);
+        for value in &[self.blowup, self.pow_bits, self.num_queries] {
+            bytes.extend_from_slice(&(*value as u64).to_le_bytes());
+        }
+        bytes.push(u8::try_from(self.fri_layout.len()).expect("FRI layout too long"));
+        for step in &self.fri_layout {
+            bytes.push(u8::try_from(*step).expect("FRI step too large"));
+        }
+        bytes
+    }
+
+    /// Deserialize from the format written by [`to_bytes`](Self::to_bytes).
+    ///
+    /// The blowup and FRI steps are checked, but since the trace length is
+    /// not known here the total length of the FRI layout is not. Use
+    /// [`validate`](Self::validate) for that.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], Error> {
+            if bytes.len() < n {
+                return Err(Error::Truncated);
+            }
+            let (head, tail) = bytes.split_at(n);
+            *bytes = tail;
+            Ok(head)
+        }
+        fn take_usize(bytes: &mut &[u8]) -> Result<usize, Error> {
+            let mut buffer = [0_u8; 8];
+            buffer.copy_from_slice(take(bytes, 8)?);
+            usize::try_from(u64::from_le_bytes(buffer)).map_err(|_| Error::ValueOutOfRange)
+        }
+
+        let mut bytes = bytes;
+        if take(&mut bytes, MAGIC.len()).map_err(|_| Error::InvalidMagic)? != MAGIC {
+            return Err(Error::InvalidMagic);
+        }
+        let version = take(&mut bytes, 1)?[0];
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let blowup = take_usize(&mut bytes)?;
+        let pow_bits = take_usize(&mut bytes)?;
+        let num_queries = take_usize(&mut bytes)?;
+        let fri_steps = take(&mut bytes, 1)?[0] as usize;
+        let fri_layout = take(&mut bytes, fri_steps)?
+            .iter()
+            .map(|step| *step as usize)
+            .collect::<Vec<_>>();
+        if !bytes.is_empty() {
+            return Err(Error::TrailingBytes);
+        }
+        if !blowup.is_power_of_two() {
+            return Err(Error::BlowupNotPowerOfTwo(blowup));
+        }
+        if let Some(&reduction) = fri_layout.iter().find(|&&r| r == 0 || r > 4) {
+            return Err(Error::InvalidFriReduction(reduction));
+        }
+        Ok(Self {
+            blowup,
+            pow_bits,
+            num_queries,
+            fri_layout,
+        })
+    }
+
+    /// Check that the parameters can be used for a trace of `trace_nrows`.
+    ///
+    /// The blowup must be a power of two and every FRI step must do between
+    /// one and four reductions. After all the FRI reductions the final layer
+    /// is written in coefficient form. It has `trace_nrows` divided by
+    /// `2^fri_layout.sum()` coefficients, so the layout can do at most
+    /// `log2(trace_nrows)` reductions.
+    pub fn validate(&self, trace_nrows: usize) -> Result<(), Error> {
+        if !trace_nrows.is_power_of_two() {
+            return Err(Error::InvalidTraceLength);
+        }
+        if !self.blowup.is_power_of_two() {
+            return Err(Error::BlowupNotPowerOfTwo(self.blowup));
+        }
+        if let Some(&reduction) = self.fri_layout.iter().find(|&&r| r == 0 || r > 4) {
+            return Err(Error::InvalidFriReduction(reduction));
+        }
+        let reductions = self.fri_layout.iter().sum();
+        let maximum = trace_nrows.trailing_zeros() as usize;
+        if reductions > maximum {
+            return Err(Error::FriLayoutTooLong {
+                reductions,
+                maximum,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Builder for [`ProofParams`] that validates the result.
+#[derive(Clone)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ProofParamsBuilder {
+    trace_nrows: usize,
+    params:      ProofParams,
+}
+
+impl ProofParamsBuilder {
+    pub fn blowup(mut self, blowup: usize) -> Self {
+        self.params.blowup = blowup;
+        self
+    }
+
+    pub fn pow_bits(mut self, pow_bits: usize) -> Self {
+        self.params.pow_bits = pow_bits;
+        self
+    }
+
+    pub fn queries(mut self, num_queries: usize) -> Self {
+        self.params.num_queries = num_queries;
+        self
+    }
+
+    pub fn fri_layout(mut self, fri_layout: Vec<usize>) -> Self {
+        self.params.fri_layout = fri_layout;
+        self
+    }
+
+    pub fn build(self) -> Result<ProofParams, Error> {
+        self.params.validate(self.trace_nrows)?;
+        Ok(self.params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn default_is_valid() {
+        for log2_trace in 0..20 {
+            assert!(ProofParams::builder(1 << log2_trace).build().is_ok());
+        }
+    }
+
+    #[test]
+    fn builder_sets_fields() {
+        let params = ProofParams::builder(1024)
+            .blowup(32)
+            .pow_bits(12)
+            .queries(20)
+            .fri_layout(vec![3, 2])
+            .build()
+            .unwrap();
+        assert_eq!(params, ProofParams {
+            blowup:      32,
+            pow_bits:    12,
+            num_queries: 20,
+            fri_layout:  vec![3, 2],
+        });
+    }
+
+    #[test]
+    fn mismatched_layout() {
+        let error = ProofParams::builder(1024)
+            .fri_layout(vec![3, 3, 3, 2])
+            .build()
+            .unwrap_err();
+        assert_eq!(error, Error::FriLayoutTooLong {
+            reductions: 11,
+            maximum:    10,
+        });
+        assert_eq!(
+            error.to_string(),
+            "FRI layout does 11 reductions, but the trace allows at most 10"
+        );
+        assert_eq!(
+            ProofParams::builder(1024).fri_layout(vec![3, 0]).build(),
+            Err(Error::InvalidFriReduction(0))
+        );
+    }
+
+    #[test]
+    fn presets() {
+        for log2_trace in 0..20 {
+            let secure = ProofParams::minimal_secure(log2_trace);
+            assert!(secure.validate(1 << log2_trace).is_ok());
+            assert!(secure.security_bits() >= 96);
+            let insecure = ProofParams::fast_insecure(log2_trace);
+            assert!(insecure.validate(1 << log2_trace).is_ok());
+            assert!(insecure.security_bits() < 32);
+        }
+    }
+
+    #[test]
+    fn for_security() {
+        for log2_trace in 0..20 {
+            for &target in &[0, 1, 32, 80, 128] {
+                let params = ProofParams::for_security(1 << log2_trace, target);
+                assert!(params.validate(1 << log2_trace).is_ok());
+                assert!(params.security_bits() >= target);
+            }
+        }
+        let params = ProofParams::for_security(1024, 80);
+        assert_eq!(params.pow_bits, 16);
+        assert_eq!(params.num_queries, 32);
+        assert_eq!(params.security_bits(), 80);
+    }
+
+    #[test]
+    fn blowup_not_power_of_two() {
+        assert_eq!(
+            ProofParams::builder(1024).blowup(12).build(),
+            Err(Error::BlowupNotPowerOfTwo(12))
+        );
+    }
+
+    #[test]
+    fn bytes_rejected() {
+        let bytes = ProofParams::minimal_secure(10).to_bytes();
+        assert_eq!(
+            ProofParams::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(Error::Truncated)
+        );
+        assert_eq!(
+            ProofParams::from_bytes(&bytes[..2]),
+            Err(Error::InvalidMagic)
+        );
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            ProofParams::from_bytes(&extended),
+            Err(Error::TrailingBytes)
+        );
+        let mut wrong_version = bytes;
+        wrong_version[4] = 2;
+        assert_eq!(
+            ProofParams::from_bytes(&wrong_version),
+            Err(Error::UnsupportedVersion(2))
+        );
+    }
+
+    proptest!(
+        #[test]
+        fn bytes_roundtrip(
+            log_blowup in 0_usize..10,
+            pow_bits in 0_usize..64,
+            num_queries: u16,
+            fri_layout in proptest::collection::vec(1_usize..=4, 0..10),
+        ) {
+            let params = ProofParams {
+                blowup: 1 << log_blowup,
+                pow_bits,
+                num_queries: num_queries as usize,
+                fri_layout,
+            };
+            prop_assert_eq!(ProofParams::from_bytes(&params.to_bytes()), Ok(params));
+        }
+    );
+}
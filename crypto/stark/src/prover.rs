@@ -1,21 +1,25 @@
 use crate::{
     algebraic_dag::AlgebraicGraph,
-    channel::{ProverChannel, RandomGenerator, Writable},
-    constraints::Constraints,
+    channel::{ChannelTag, ProverChannel, RandomGenerator, TranscriptEvent, Writable},
+    constraints::{Constraints, Error as ConstraintError},
+    fri,
     polynomial::DensePolynomial,
-    proof_of_work, verify, Proof, TraceTable, VerifierError,
+    trace_table::Layout,
+    verifier::verify_with_hashers,
+    Proof, TraceTable, VerifierError,
 };
 use itertools::Itertools;
 use log::{info, trace};
+#[cfg(feature = "std")]
 use rayon::prelude::*;
 use std::{fmt, prelude::v1::*, vec};
-use zkp_hash::{Hash, Hashable, MaskedKeccak};
+use zkp_hash::{Hash, Hashable, Hasher, Keccak256, MaskedKeccak};
 use zkp_merkle_tree::{Error as MerkleError, Tree, VectorCommitment};
 use zkp_mmap_vec::MmapVec;
 use zkp_primefield::{
     fft::{permute, permute_index},
     geometric_series::geometric_series,
-    Fft, FieldElement, Inv, One, Pow, Root, SquareInline, Zero,
+    Fft, FieldElement, Inv, One, Pow, Root, Zero,
 };
 use zkp_u256::U256;
 
@@ -24,8 +28,11 @@ type Result<T> = std::result::Result<T, Error>;
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Error {
     RootUnavailable,
+    InvalidConstraints(ConstraintError),
     MerkleFailed(MerkleError),
     VerificationFailed(VerifierError),
+    #[cfg(feature = "std")]
+    Io(std::io::ErrorKind),
 }
 
 impl fmt::Display for Error {
@@ -33,12 +40,21 @@ impl fmt::Display for Error {
         use Error::*;
         match *self {
             RootUnavailable => write!(f, "The prime field doesn't have a root of this order"),
+            InvalidConstraints(ref e) => std::fmt::Display::fmt(e, f),
             MerkleFailed(ref e) => std::fmt::Display::fmt(e, f),
             VerificationFailed(ref e) => std::fmt::Display::fmt(e, f),
+            #[cfg(feature = "std")]
+            Io(kind) => write!(f, "Writing the proof failed: {:?}", kind),
         }
     }
 }
 
+impl From<ConstraintError> for Error {
+    fn from(err: ConstraintError) -> Self {
+        Self::InvalidConstraints(err)
+    }
+}
+
 impl From<MerkleError> for Error {
     fn from(err: MerkleError) -> Self {
         Self::MerkleFailed(err)
@@ -73,13 +89,13 @@ impl VectorCommitment for PolyLDE {
         ret
     }
 
-    fn leaf_hash(&self, index: usize) -> Hash {
+    fn leaf_hash_with<H: Hasher>(&self, index: usize) -> Hash {
         if self.0.len() == 1 {
             // For a single element, return its hash.
             self.0[0][index].hash()
         } else {
             // Concatenate the element hashes and hash the result.
-            let mut hasher = MaskedKeccak::new();
+            let mut hasher = H::default();
             for value in &self.0 {
                 hasher.update(value[index].hash().as_bytes());
             }
@@ -94,7 +110,7 @@ struct FriLeaves {
     layer:      MmapVec<FieldElement>,
 }
 
-type FriTree = Tree<FriLeaves>;
+type FriTree<H = MaskedKeccak> = Tree<FriLeaves, H>;
 
 // Merkle tree for FRI layers with coset size
 impl VectorCommitment for FriLeaves {
@@ -117,13 +133,13 @@ impl VectorCommitment for FriLeaves {
         internal_leaf
     }
 
-    fn leaf_hash(&self, index: usize) -> Hash {
+    fn leaf_hash_with<H: Hasher>(&self, index: usize) -> Hash {
         if self.coset_size == 1 {
             // For a single element, return its hash.
             self.layer[index].hash()
         } else {
             // Concatenate the element hashes and hash the result.
-            let mut hasher = MaskedKeccak::new();
+            let mut hasher = H::default();
             for j in 0..self.coset_size {
                 hasher.update(self.layer[(index * self.coset_size + j)].hash().as_bytes());
             }
@@ -146,6 +162,13 @@ impl VectorCommitment for FriLeaves {
 ///
 /// A `ProverChannel`.
 ///
+/// ## Determinism
+///
+/// The proof is a deterministic function of the constraints and the trace.
+/// Proving the same claim twice gives byte-identical proofs, independent of
+/// the number of threads used. All randomness comes from the Fiat-Shamir
+/// channel and the proof of work always uses the smallest valid nonce.
+///
 /// ## Proof construction
 ///
 /// A new `ProverChannel` is initialized with the public input.
@@ -340,15 +363,135 @@ impl VectorCommitment for FriLeaves {
 ///
 /// with merkle proofs to that layer. This process is repeated for all FRI layer
 /// commitments.
+pub fn prove(constraints: &Constraints, trace: &TraceTable) -> Result<Proof> {
+    prove_channel::<Keccak256, MaskedKeccak>(
+        constraints,
+        trace,
+        ProverChannel::default(),
+        &mut |_, _, _| (),
+        None,
+    )
+    .map(|(proof, _)| proof)
+}
+
+/// Produce a Stark proof hashing with `H` instead of Keccak.
+///
+/// See [`prove`]. `H` is used for the Fiat-Shamir channel and for the trace,
+/// constraint and FRI layer Merkle trees. The proof can only be checked with
+/// [`verify_with_hasher`] using the same `H`.
+pub fn prove_with_hasher<H: Hasher>(
+    constraints: &Constraints,
+    trace: &TraceTable,
+) -> Result<Proof> {
+    prove_channel::<H, H>(
+        constraints,
+        trace,
+        ProverChannel::<H>::with_hasher(),
+        &mut |_, _, _| (),
+        None,
+    )
+    .map(|(proof, _)| proof)
+}
+
+/// Produce a Stark proof and record the Fiat-Shamir transcript.
+///
+/// See [`prove`]. The transcript can be compared with the one from
+/// `verify_with_transcript` to find the first place where prover and verifier
+/// diverge.
+pub fn prove_with_transcript(
+    constraints: &Constraints,
+    trace: &TraceTable,
+) -> Result<(Proof, Vec<TranscriptEvent>)> {
+    prove_channel::<Keccak256, MaskedKeccak>(
+        constraints,
+        trace,
+        ProverChannel::default().with_trace(),
+        &mut |_, _, _| (),
+        None,
+    )
+}
+
+// False positives on the Latex math.
+#[allow(clippy::doc_markdown)]
+/// The intermediate polynomials passed to the sink of
+/// [`prove_with_polynomials`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum PolynomialKind {
+    /// Interpolated trace column
+    Trace,
+    /// Part of the combined constraint polynomial
+    ///
+    /// The combined constraint polynomial $C(X)$ is split in parts $C_i$ such
+    /// that $C(X) = \sum_i X^i C_i(X^n)$ where $n$ is the number of parts.
+    Constraint,
+}
+
+/// Produce a Stark proof and pass the intermediate polynomials to `sink`.
+///
+/// See [`prove`]. The sink is called with the kind of polynomial, its index
+/// and the polynomial. This makes it possible to validate the proof against
+/// an independent implementation.
+pub fn prove_with_polynomials(
+    constraints: &Constraints,
+    trace: &TraceTable,
+    sink: &mut dyn FnMut(PolynomialKind, usize, &DensePolynomial),
+) -> Result<Proof> {
+    prove_channel::<Keccak256, MaskedKeccak>(
+        constraints,
+        trace,
+        ProverChannel::default(),
+        sink,
+        None,
+    )
+    .map(|(proof, _)| proof)
+}
+
+/// Produce a Stark proof and write it to `writer` section by section.
+///
+/// See [`prove`]. The trace commitment, constraint commitment, out of domain
+/// values, FRI layers, proof of work and decommitments are written as soon as
+/// they are produced, so the proof is never held in memory as a whole. The
+/// bytes are the same as those of the [`Proof`] returned by [`prove`] and can
+/// be checked with [`verify_reader`](crate::verify_reader).
+///
+/// Unlike [`prove`], the proof is not verified before returning.
+#[cfg(feature = "std")]
+pub fn prove_streaming(
+    constraints: &Constraints,
+    trace: &TraceTable,
+    writer: &mut impl std::io::Write,
+) -> Result<()> {
+    let mut flush = |bytes: Vec<u8>| writer.write_all(&bytes).map_err(|e| Error::Io(e.kind()));
+    let _ = prove_channel::<Keccak256, MaskedKeccak>(
+        constraints,
+        trace,
+        ProverChannel::default(),
+        &mut |_, _, _| (),
+        Some(&mut flush),
+    )?;
+    writer.flush().map_err(|e| Error::Io(e.kind()))
+}
+
 // TODO: Simplify
 #[allow(clippy::cognitive_complexity)]
 // TODO: Split up
 #[allow(clippy::too_many_lines)]
-pub fn prove(constraints: &Constraints, trace: &TraceTable) -> Result<Proof> {
+//
+// The channel hashes with `C` and the Merkle trees with `H`.
+fn prove_channel<C: Hasher, H: Hasher>(
+    constraints: &Constraints,
+    trace: &TraceTable,
+    mut proof: ProverChannel<C>,
+    sink: &mut dyn FnMut(PolynomialKind, usize, &DensePolynomial),
+    mut flush: Option<&mut dyn FnMut(Vec<u8>) -> Result<()>>,
+) -> Result<(Proof, Vec<TranscriptEvent>)> {
     // This hack allows us to avoid changing the interface to mut for the
     // claim polynomials but is ugly and should be removed.
     let original_constraints = constraints.clone();
     let mut constraints = constraints.clone();
+    constraints.validate_size()?;
+    constraints.validate_degrees(constraints.blowup)?;
     // TODO: Verify input
     //  * Constraint trace length matches trace table length
     //  * Fri layout is less than trace length * blowup
@@ -368,8 +511,16 @@ pub fn prove(constraints: &Constraints, trace: &TraceTable) -> Result<Proof> {
     );
     info!("{} constraints", constraints.len(),);
 
+    // Hand the proof written so far to `flush`, if streaming.
+    let mut end_section = |proof: &mut ProverChannel<C>| {
+        match &mut flush {
+            Some(flush) => flush(proof.take_proof()),
+            None => Ok(()),
+        }
+    };
+
     info!("Initialize channel with claim.");
-    let mut proof = ProverChannel::default();
+    proof.set_domain_separation(constraints.domain_separation);
     proof.initialize(constraints.channel_seed());
 
     // 1. Trace commitment.
@@ -385,6 +536,9 @@ pub fn prove(constraints: &Constraints, trace: &TraceTable) -> Result<Proof> {
             .map(DensePolynomial::degree)
             .collect::<Vec<_>>()
     );
+    for (i, polynomial) in trace_polynomials.iter().enumerate() {
+        sink(PolynomialKind::Trace, i, polynomial);
+    }
     let trace_lde = PolyLDE(
         trace_polynomials
             .iter()
@@ -395,8 +549,10 @@ pub fn prove(constraints: &Constraints, trace: &TraceTable) -> Result<Proof> {
     // Construct a merkle tree over the LDE trace
     // and write the root to the channel.
     info!("Construct a merkle tree over the LDE trace and write the root to the channel.");
-    let (commitment, tree) = trace_lde.commit()?;
+    let (commitment, tree) = trace_lde.commit_with::<H>()?;
+    proof.tag(ChannelTag::TraceRoot);
     proof.write(&commitment);
+    end_section(&mut proof)?;
     trace!("END Trace commitment");
 
     // 2. Constraint commitment
@@ -421,6 +577,10 @@ pub fn prove(constraints: &Constraints, trace: &TraceTable) -> Result<Proof> {
             .collect::<Vec<_>>()
     );
 
+    for (i, polynomial) in constraint_polynomials.iter().enumerate() {
+        sink(PolynomialKind::Constraint, i, polynomial);
+    }
+
     // OPT: It may be faster to compute the constraint LDE from the trace LDE,
     // instead of using an FFT.
     info!("Compute the low degree extension of constraint polynomials.");
@@ -433,8 +593,10 @@ pub fn prove(constraints: &Constraints, trace: &TraceTable) -> Result<Proof> {
     // Construct a merkle tree over the LDE combined constraints
     // and write the root to the channel.
     info!("Compute the merkle tree over the LDE constraint polynomials.");
-    let (commitment, c_tree) = constraint_lde.commit()?;
+    let (commitment, c_tree) = constraint_lde.commit_with::<H>()?;
+    proof.tag(ChannelTag::ConstraintRoot);
     proof.write(&commitment);
+    end_section(&mut proof)?;
     trace!("END Constraint commitment");
 
     // 3. Out of domain sampling
@@ -446,6 +608,7 @@ pub fn prove(constraints: &Constraints, trace: &TraceTable) -> Result<Proof> {
         &constraints.trace_arguments(),
         &constraint_polynomials,
     );
+    end_section(&mut proof)?;
     trace!("END Out of domain sampling");
     info!("Oods poly degree: {}", oods_polynomial.degree());
 
@@ -454,21 +617,19 @@ pub fn prove(constraints: &Constraints, trace: &TraceTable) -> Result<Proof> {
     trace!("BEGIN FRI commitment");
     let first_fri_layer = oods_polynomial.low_degree_extension(constraints.blowup);
     info!("Fri layers.");
-    let fri_trees = perform_fri_layering(
+    let fri_trees = perform_fri_layering::<C, H>(
         first_fri_layer,
         &mut proof,
         &constraints.fri_layout,
         constraints.blowup,
     )?;
+    end_section(&mut proof)?;
     trace!("END FRI commitment");
 
     // 5. Proof of work
     info!("Proof of work.");
-    let pow_seed: proof_of_work::ChallengeSeed = proof.get_random();
-    let pow_challenge = pow_seed.with_difficulty(constraints.pow_bits);
-    let pow_response = pow_challenge.solve();
-    debug_assert!(pow_challenge.verify(pow_response));
-    proof.write(pow_response);
+    let _ = proof.grind_pow(constraints.pow_bits);
+    end_section(&mut proof)?;
 
     // 6. Query decommitments
     //
@@ -500,27 +661,34 @@ pub fn prove(constraints: &Constraints, trace: &TraceTable) -> Result<Proof> {
     // Decommit the FRI layer values
     info!("Decommit the FRI layer values.");
     decommit_fri_layers_and_trees(fri_trees.as_slice(), query_indices.as_slice(), &mut proof)?;
+    end_section(&mut proof)?;
 
     // Verify proof
-    info!("Verify proof.");
     // TODO: Rename channel / transcript object
+    let transcript = proof.transcript().to_vec();
     let proof = Proof::from_bytes(proof.proof);
-    verify(&original_constraints, &proof)?;
+    if flush.is_none() {
+        info!("Verify proof.");
+        verify_with_hashers::<C, H>(&original_constraints, &proof)?;
+    }
 
     trace!("END Stark proof");
-    Ok(proof)
+    Ok((proof, transcript))
 }
 
 // Constructs a trace table on a coset domain of `size`.
+//
+// The table is column-major because constraint evaluation reads each column at
+// several row offsets.
 fn extract_trace_coset(trace_lde: &PolyLDE, size: usize) -> TraceTable {
     let trace_lde: &[MmapVec<FieldElement>] = &trace_lde.0;
     let lde_size = trace_lde[0].len();
-    let mut trace_coset = TraceTable::new(size, trace_lde.len());
+    let mut trace_coset = TraceTable::with_layout(size, trace_lde.len(), Layout::ColumnMajor);
     trace!("BEGIN Extract Trace Coset");
-    for i in 0..trace_coset.num_rows() {
-        let index = i * lde_size / size;
-        let index = permute_index(lde_size, index);
-        for (j, lde) in trace_lde.iter().enumerate() {
+    for (j, lde) in trace_lde.iter().enumerate() {
+        for i in 0..trace_coset.num_rows() {
+            let index = i * lde_size / size;
+            let index = permute_index(lde_size, index);
             trace_coset[(i, j)] = lde[index].clone();
         }
     }
@@ -528,7 +696,7 @@ fn extract_trace_coset(trace_lde: &PolyLDE, size: usize) -> TraceTable {
     trace_coset
 }
 
-fn get_indices(num: usize, bits: u32, proof: &mut ProverChannel) -> Vec<usize> {
+fn get_indices<C: Hasher>(num: usize, bits: u32, proof: &mut ProverChannel<C>) -> Vec<usize> {
     let mut query_indices = Vec::with_capacity(num + 3);
     while query_indices.len() < num {
         let val: U256 = proof.get_random();
@@ -600,18 +768,19 @@ fn get_constraint_polynomials(
     let mut result: MmapVec<FieldElement> = MmapVec::with_capacity(coset_size);
     result.resize(coset_size, FieldElement::zero());
     let values = &mut result;
-    values
-        .par_chunks_mut(CHUNK_SIZE)
-        .enumerate()
-        .for_each(|(mut i, chunk)| {
-            i *= CHUNK_SIZE;
-            let mut dag = dag.clone();
-            dag.init(i);
-            for value in chunk {
-                *value = dag.next(&trace_coset);
-                i += 1;
-            }
-        });
+    #[cfg(feature = "std")]
+    let chunks = values.par_chunks_mut(CHUNK_SIZE);
+    #[cfg(not(feature = "std"))]
+    let chunks = values.chunks_mut(CHUNK_SIZE);
+    chunks.enumerate().for_each(|(mut i, chunk)| {
+        i *= CHUNK_SIZE;
+        let mut dag = dag.clone();
+        dag.init(i);
+        for value in chunk {
+            *value = dag.next(&trace_coset);
+            i += 1;
+        }
+    });
     trace!("END Evaluate");
 
     info!("Convert from values to coefficients");
@@ -649,8 +818,8 @@ fn get_constraint_polynomials(
     result
 }
 
-fn oods_combine(
-    proof: &mut ProverChannel,
+fn oods_combine<C: Hasher>(
+    proof: &mut ProverChannel<C>,
     trace_polynomials: &[DensePolynomial],
     trace_arguments: &[(usize, isize)],
     constraint_polynomials: &[DensePolynomial],
@@ -663,6 +832,7 @@ fn oods_combine(
 
     // Write point evaluations to proof
     // OPT: Parallelization
+    proof.tag(ChannelTag::OodsValues);
     for (column, offset) in trace_arguments {
         proof
             .write(&trace_polynomials[*column].evaluate(&(&oods_point * &g.pow(*offset).unwrap())));
@@ -704,17 +874,17 @@ fn oods_combine(
 }
 
 // TODO: remove this and refactor ProverChannel.
-fn get_coefficients(proof: &mut ProverChannel, n: usize) -> Vec<FieldElement> {
+fn get_coefficients<C: Hasher>(proof: &mut ProverChannel<C>, n: usize) -> Vec<FieldElement> {
     (0..n).map(|_| proof.get_random()).collect()
 }
 
-fn perform_fri_layering(
+fn perform_fri_layering<C: Hasher, H: Hasher>(
     first_layer: MmapVec<FieldElement>,
-    proof: &mut ProverChannel,
+    proof: &mut ProverChannel<C>,
     fri_layout: &[usize],
     blowup: usize,
-) -> Result<Vec<FriTree>> {
-    let mut fri_trees: Vec<FriTree> = Vec::with_capacity(fri_layout.len());
+) -> Result<Vec<FriTree<H>>> {
+    let mut fri_trees: Vec<FriTree<H>> = Vec::with_capacity(fri_layout.len());
 
     // Compute 1/x for the fri layer. We only compute the even coordinates.
     // OPT: Can these be efficiently computed on the fly?
@@ -744,81 +914,18 @@ fn perform_fri_layering(
         // FRI layout values are small.
         #[allow(clippy::cast_possible_truncation)]
         let coset_size = 2_usize.pow(n_reductions as u32);
-        let tree = FriTree::from_leaves(FriLeaves { coset_size, layer })?;
+        let tree = FriTree::<H>::with_hasher(FriLeaves { coset_size, layer }, 1)?;
         fri_trees.push(tree);
         let tree = fri_trees.last().unwrap();
         let layer = &tree.leaves().layer;
 
         // Write commitment and pull coefficient
+        proof.tag(ChannelTag::FriLayer);
         proof.write(tree.commitment());
         let coefficient = proof.get_random();
 
-        // Fold layer up to three times
-        // TODO: Capture the pattern in a macro and DRY.
-        // OPT: Parallelization
-        // OPT: The structure in x_inv should allow faster methods,
-        // like in a radix-4 and radix-8 fft.
-        let layer = layer.iter();
-        match n_reductions {
-            1 => {
-                next_layer.extend(
-                    layer
-                        .tuples()
-                        .zip(x_inv.iter())
-                        .map(|((p0, p1), x_inv)| (p0 + p1) + &coefficient * x_inv * (p0 - p1)),
-                )
-            }
-            2 => {
-                let coefficient_2 = coefficient.square();
-                next_layer.extend(
-                    layer
-                        .tuples()
-                        .zip(x_inv.iter())
-                        .map(|((p0, p1), x_inv)| (p0 + p1) + &coefficient * x_inv * (p0 - p1))
-                        .tuples()
-                        .zip(x_inv.iter())
-                        .map(|((p0, p1), x_inv)| (&p0 + &p1) + &coefficient_2 * x_inv * (p0 - p1)),
-                )
-            }
-            3 => {
-                let coefficient_2 = coefficient.square();
-                let coefficient_4 = coefficient_2.square();
-                next_layer.extend(
-                    layer
-                        .tuples()
-                        .zip(x_inv.iter())
-                        .map(|((p0, p1), x_inv)| (p0 + p1) + &coefficient * x_inv * (p0 - p1))
-                        .tuples()
-                        .zip(x_inv.iter())
-                        .map(|((p0, p1), x_inv)| (&p0 + &p1) + &coefficient_2 * x_inv * (p0 - p1))
-                        .tuples()
-                        .zip(x_inv.iter())
-                        .map(|((p0, p1), x_inv)| (&p0 + &p1) + &coefficient_4 * x_inv * (p0 - p1)),
-                )
-            }
-            // TODO: Is there a use case for 4 layer folds?
-            4 => {
-                let coefficient_2 = coefficient.square();
-                let coefficient_4 = coefficient_2.square();
-                let coefficient_8 = coefficient_4.square();
-                next_layer.extend(
-                    layer
-                        .tuples()
-                        .zip(x_inv.iter())
-                        .map(|((p0, p1), x_inv)| (p0 + p1) + &coefficient * x_inv * (p0 - p1))
-                        .tuples()
-                        .zip(x_inv.iter())
-                        .map(|((p0, p1), x_inv)| (&p0 + &p1) + &coefficient_2 * x_inv * (p0 - p1))
-                        .tuples()
-                        .zip(x_inv.iter())
-                        .map(|((p0, p1), x_inv)| (&p0 + &p1) + &coefficient_4 * x_inv * (p0 - p1))
-                        .tuples()
-                        .zip(x_inv.iter())
-                        .map(|((p0, p1), x_inv)| (&p0 + &p1) + &coefficient_8 * x_inv * (p0 - p1)),
-                )
-            }
-            _ => unimplemented!(),
-        };
+        // Fold layer
+        fri::fold_layer_into(&mut next_layer, layer, &x_inv, &coefficient, coset_size);
     }
 
     // Write the final layer coefficients
@@ -827,15 +934,16 @@ fn perform_fri_layering(
     permute(points);
     points.ifft();
     permute(points);
+    proof.tag(ChannelTag::FriLayer);
     proof.write(&*points);
 
     Ok(fri_trees)
 }
 
-fn decommit_fri_layers_and_trees(
-    fri_trees: &[FriTree],
+fn decommit_fri_layers_and_trees<C: Hasher, H: Hasher>(
+    fri_trees: &[FriTree<H>],
     query_indices: &[usize],
-    proof: &mut ProverChannel,
+    proof: &mut ProverChannel<C>,
 ) -> Result<()> {
     let mut previous_indices: Vec<usize> = query_indices.to_vec();
 
@@ -867,7 +975,13 @@ fn decommit_fri_layers_and_trees(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{traits::tests::Recurrance, verify, Provable, Verifiable};
+    use crate::{
+        channel::{Replayable, VerifierChannel},
+        traits::tests::Recurrance,
+        verifier::verify_with_transcript,
+        verify, verify_with_hasher, Provable, Verifiable,
+    };
+    use proptest::prelude::*;
     use tiny_keccak::{Hasher, Sha3};
     use zkp_macros_decl::{field_element, hex, u256h};
     use zkp_primefield::{fft::permute_index, geometric_series::geometric_series};
@@ -943,6 +1057,340 @@ mod tests {
         )
     }
 
+    #[test]
+    fn transcripts_match() {
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let witness = recurrance.witness();
+        let claim = recurrance.claim();
+        let mut constraints = claim.constraints();
+        constraints.num_queries = 20;
+        constraints.fri_layout = vec![3, 2];
+
+        let (proof, prover_transcript) =
+            prove_with_transcript(&constraints, &claim.trace(&witness)).unwrap();
+        let (result, verifier_transcript) = verify_with_transcript(&constraints, &proof);
+        assert_eq!(result, Ok(()));
+        assert!(!prover_transcript.is_empty());
+        assert_eq!(prover_transcript, verifier_transcript);
+    }
+
+    #[test]
+    fn deterministic_proofs() {
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let witness = recurrance.witness();
+        let claim = recurrance.claim();
+        let mut constraints = claim.constraints();
+        constraints.pow_bits = 8;
+        constraints.num_queries = 20;
+        constraints.fri_layout = vec![3, 2];
+        let trace = claim.trace(&witness);
+
+        let prove_with_threads = |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap()
+                .install(|| prove(&constraints, &trace).unwrap())
+        };
+        let proof = prove_with_threads(1);
+        assert_eq!(proof.as_bytes(), prove_with_threads(1).as_bytes());
+        assert_eq!(proof.as_bytes(), prove_with_threads(4).as_bytes());
+    }
+
+    #[test]
+    fn streaming_proof() {
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let witness = recurrance.witness();
+        let claim = recurrance.claim();
+        let mut constraints = claim.constraints();
+        constraints.num_queries = 20;
+        constraints.fri_layout = vec![3, 2];
+        let trace = claim.trace(&witness);
+
+        let mut buffer = Vec::new();
+        prove_streaming(&constraints, &trace, &mut buffer).unwrap();
+        assert_eq!(buffer, prove(&constraints, &trace).unwrap().as_bytes());
+        assert_eq!(
+            crate::verify_reader(&constraints, &mut buffer.as_slice()),
+            Ok(())
+        );
+        assert!(crate::verify_reader(&constraints, &mut &buffer[..100]).is_err());
+
+        // The proof is read as far as needed, up to the first failing check
+        let mut tampered = buffer.clone();
+        tampered[0] ^= 1;
+        let mut reader = tampered.as_slice();
+        assert!(crate::verify_reader(&constraints, &mut reader).is_err());
+        assert!(!reader.is_empty());
+
+        buffer.push(0);
+        assert_eq!(
+            crate::verify_reader(&constraints, &mut buffer.as_slice()),
+            Err(crate::VerifierError::ProofTooLong)
+        );
+    }
+
+    #[test]
+    fn domain_separation() {
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let witness = recurrance.witness();
+        let claim = recurrance.claim();
+        let mut constraints = claim.constraints();
+        constraints.num_queries = 20;
+        constraints.domain_separation = true;
+
+        let (proof, prover_transcript) =
+            prove_with_transcript(&constraints, &claim.trace(&witness)).unwrap();
+        let (result, verifier_transcript) = verify_with_transcript(&constraints, &proof);
+        assert_eq!(result, Ok(()));
+        assert_eq!(prover_transcript, verifier_transcript);
+
+        // The tags are part of the transcript
+        constraints.domain_separation = false;
+        assert!(verify(&constraints, &proof).is_err());
+    }
+
+    #[derive(Clone)]
+    struct Sha3Hasher(Sha3);
+
+    impl Default for Sha3Hasher {
+        fn default() -> Self {
+            Self(Sha3::v256())
+        }
+    }
+
+    impl zkp_hash::Hasher for Sha3Hasher {
+        fn update(&mut self, input: &[u8]) {
+            self.0.update(input)
+        }
+
+        fn hash(self) -> Hash {
+            let mut result = [0; 32];
+            self.0.finalize(&mut result);
+            Hash::new(result)
+        }
+    }
+
+    #[test]
+    fn custom_hasher() {
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let witness = recurrance.witness();
+        let claim = recurrance.claim();
+        let mut constraints = claim.constraints();
+        constraints.num_queries = 20;
+        let trace = claim.trace(&witness);
+
+        let proof = prove_with_hasher::<Sha3Hasher>(&constraints, &trace).unwrap();
+        assert_eq!(
+            verify_with_hasher::<Sha3Hasher>(&constraints, &proof),
+            Ok(())
+        );
+        assert_ne!(proof, prove(&constraints, &trace).unwrap());
+        assert!(verify(&constraints, &proof).is_err());
+
+        // The trace commitment is a tree hashed with the same hasher, including
+        // the leaves, which combine the values of both columns.
+        let trace_lde = PolyLDE(
+            trace
+                .interpolate()
+                .iter()
+                .map(|p| p.low_degree_extension(constraints.blowup))
+                .collect::<Vec<_>>(),
+        );
+        let trace_root = &proof.as_bytes()[..32];
+        let (commitment, _) = trace_lde.clone().commit_with::<Sha3Hasher>().unwrap();
+        assert_eq!(commitment.hash().as_bytes(), trace_root);
+        let (commitment, _) = trace_lde.commit().unwrap();
+        assert_ne!(commitment.hash().as_bytes(), trace_root);
+    }
+
+    #[test]
+    fn degree_exceeds_blowup() {
+        let recurrance = Recurrance {
+            index:         100,
+            initial_value: field_element!("cafebabe"),
+            exponent:      5,
+        };
+        let witness = recurrance.witness();
+        let claim = recurrance.claim();
+        let mut constraints = claim.constraints();
+        assert_eq!(constraints.validate_degrees(8), Ok(()));
+
+        constraints.blowup = 4;
+        assert_eq!(
+            prove(&constraints, &claim.trace(&witness)),
+            Err(Error::InvalidConstraints(
+                ConstraintError::DegreeExceedsBlowup {
+                    constraint_index: 0,
+                    degree:           5,
+                    blowup:           4,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn expression_too_large() {
+        let recurrance = Recurrance {
+            index:         100,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let witness = recurrance.witness();
+        let claim = recurrance.claim();
+        let mut constraints = claim.constraints();
+        let nodes = constraints.expressions()[0].count_nodes();
+        constraints.max_expression_nodes = nodes - 1;
+        let result = prove(&constraints, &claim.trace(&witness));
+        assert_eq!(
+            result,
+            Err(Error::InvalidConstraints(
+                ConstraintError::ExpressionTooLarge {
+                    constraint_index: 0,
+                    nodes,
+                    maximum: nodes - 1,
+                }
+            ))
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                "Constraint 0 has {} nodes, but at most {} are allowed",
+                nodes,
+                nodes - 1
+            )
+        );
+    }
+
+    #[test]
+    fn polynomials_match_oods_values() {
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let witness = recurrance.witness();
+        let claim = recurrance.claim();
+        let constraints = claim.constraints();
+
+        let mut trace_polynomials = Vec::new();
+        let mut constraint_polynomials = Vec::new();
+        let proof = prove_with_polynomials(
+            &constraints,
+            &claim.trace(&witness),
+            &mut |kind, index, polynomial| {
+                let list = match kind {
+                    PolynomialKind::Trace => &mut trace_polynomials,
+                    PolynomialKind::Constraint => &mut constraint_polynomials,
+                };
+                assert_eq!(list.len(), index);
+                list.push(polynomial.clone());
+            },
+        )
+        .unwrap();
+        assert_eq!(trace_polynomials.len(), 2);
+        assert_eq!(
+            constraint_polynomials.len(),
+            constraints.degree().next_power_of_two()
+        );
+
+        // Replay the proof up to the out of domain values
+        let mut channel = VerifierChannel::new(proof.as_bytes().to_vec());
+        channel.initialize(constraints.channel_seed());
+        let _: Hash = channel.replay().unwrap();
+        let _ = channel.get_coefficients(2 * constraints.len());
+        let _: Hash = channel.replay().unwrap();
+        let oods_point: FieldElement = channel.get_random();
+        let g = FieldElement::root(constraints.trace_nrows()).unwrap();
+        for (column, offset) in constraints.trace_arguments() {
+            let value: FieldElement = channel.replay().unwrap();
+            let x = &oods_point * g.pow(offset).unwrap();
+            assert_eq!(trace_polynomials[column].evaluate(&x), value);
+        }
+        let x = oods_point.pow(constraint_polynomials.len());
+        for polynomial in &constraint_polynomials {
+            let value: FieldElement = channel.replay().unwrap();
+            assert_eq!(polynomial.evaluate(&x), value);
+        }
+    }
+
+    #[test]
+    fn oods_check_matches_proof() {
+        let recurrance = Recurrance {
+            index:         1000,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let witness = recurrance.witness();
+        let claim = recurrance.claim();
+        let constraints = claim.constraints();
+
+        let mut trace_polynomials = Vec::new();
+        let mut constraint_polynomials = Vec::new();
+        let proof = prove_with_polynomials(
+            &constraints,
+            &claim.trace(&witness),
+            &mut |kind, _, polynomial| {
+                match kind {
+                    PolynomialKind::Trace => trace_polynomials.push(polynomial.clone()),
+                    PolynomialKind::Constraint => constraint_polynomials.push(polynomial.clone()),
+                }
+            },
+        )
+        .unwrap();
+
+        let mut channel = VerifierChannel::new(proof.as_bytes().to_vec());
+        channel.initialize(constraints.channel_seed());
+        let _: Hash = channel.replay().unwrap();
+        let coefficients = channel.get_coefficients(2 * constraints.len());
+        let _: Hash = channel.replay().unwrap();
+        let oods_point: FieldElement = channel.get_random();
+
+        let result = constraints.oods_check(
+            &trace_polynomials,
+            &constraint_polynomials,
+            &coefficients,
+            &oods_point,
+        );
+        assert!(result.is_consistent());
+        for value in result.trace_values.iter().chain(&result.constraint_values) {
+            let written: FieldElement = channel.replay().unwrap();
+            assert_eq!(value, &written);
+        }
+
+        // Different coefficients no longer match the committed constraints
+        let mut wrong_coefficients = coefficients;
+        wrong_coefficients[0] += FieldElement::one();
+        assert!(!constraints
+            .oods_check(
+                &trace_polynomials,
+                &constraint_polynomials,
+                &wrong_coefficients,
+                &oods_point,
+            )
+            .is_consistent());
+    }
+
     #[test]
     fn fib_test_4096() {
         let recurrance = Recurrance {
@@ -1041,7 +1489,7 @@ mod tests {
         );
 
         let mut proof = ProverChannel::default();
-        proof.initialize(&claim.seed());
+        proof.initialize(&claim.public_input_bytes());
         // Checks that the channel is inited properly
         assert_eq!(
             proof.coin.digest,
@@ -1112,7 +1560,7 @@ mod tests {
             field_element!("03c6b730c58b55f44bbf3cb7ea82b2e6a0a8b23558e908b5466dfe42e821ee96")
         );
 
-        let fri_trees = perform_fri_layering(
+        let fri_trees = perform_fri_layering::<_, MaskedKeccak>(
             CO.low_degree_extension(constraints.blowup),
             &mut proof,
             &constraints.fri_layout,
@@ -1136,14 +1584,9 @@ mod tests {
             "3c6cecef72873e7d73933e73279d36ca77c5a0c7497311eba735722549238334"
         );
 
-        let pow_seed: proof_of_work::ChallengeSeed = proof.get_random();
-        let pow_challenge = pow_seed.with_difficulty(constraints.pow_bits);
-        let pow_response = pow_challenge.solve();
-        debug_assert!(pow_challenge.verify(pow_response));
         // Checks that the pow function is working [may also fail if the previous steps
         // have perturbed the channel's random]
-        assert_eq!(pow_response.nonce(), 281);
-        proof.write(pow_response);
+        assert_eq!(proof.grind_pow(constraints.pow_bits), 281);
 
         let query_indices = get_indices(
             constraints.num_queries,
@@ -1185,4 +1628,31 @@ mod tests {
             "fcf1924f84656e5068ab9cbd44ae084b235bb990eefc0fd0183c77d5645e830e"
         );
     }
+
+    proptest!(
+        #[test]
+        fn fri_decommitment_size(log_size: usize, log_coset: usize, indices: Vec<usize>) {
+            let log_coset = log_coset % 4;
+            let coset_size = 1_usize << log_coset;
+            let size = coset_size << (log_size % 6);
+            let mut layer = MmapVec::with_capacity(size);
+            for i in 0..size {
+                layer.push(FieldElement::from(i));
+            }
+            let trees = vec![FriTree::from_leaves(FriLeaves { coset_size, layer }).unwrap()];
+            let mut indices: Vec<_> = indices.iter().map(|i| i % size).collect();
+            indices.sort_unstable();
+            indices.dedup();
+
+            let mut proof = ProverChannel::default();
+            decommit_fri_layers_and_trees(&trees, &indices, &mut proof).unwrap();
+            prop_assert_eq!(
+                proof.proof.len(),
+                32 * trees[0]
+                    .commitment()
+                    .decommitment_size(&indices, log_coset)
+                    .unwrap()
+            );
+        }
+    );
 }
@@ -1,15 +1,18 @@
-use crate::polynomial::DensePolynomial;
+use crate::polynomial::{DensePolynomial, SparsePolynomial};
 #[cfg(feature = "std")]
 use std::{cmp::Ordering, collections::hash_map::DefaultHasher};
 use std::{
     collections::BTreeSet,
+    fmt,
     hash::{Hash, Hasher},
     iter::Sum,
     ops::{Add, Div, Mul, Sub},
     prelude::v1::*,
 };
+use tiny_keccak::{Hasher as _, Keccak};
+use zkp_hash::Hash as Digest;
 use zkp_macros_decl::field_element;
-use zkp_primefield::{FieldElement, Inv, One, Pow, Zero};
+use zkp_primefield::{invert_batch, FieldElement, Inv, One, Pow, Zero};
 use zkp_u256::U256;
 
 // TODO: Rename to algebraic expression
@@ -23,6 +26,9 @@ pub enum RationalExpression {
     // TODO - Make this a struct with internally named members
     // the members are (index, degree bound, expression, name)
     ClaimPolynomial(usize, usize, Box<RationalExpression>, Option<&'static str>),
+    /// Periodic column with the given coefficients and number of repetitions,
+    /// see [`SparsePolynomial::periodic`].
+    Periodic(Vec<FieldElement>, usize),
     Add(Box<RationalExpression>, Box<RationalExpression>),
     Neg(Box<RationalExpression>),
     Mul(Box<RationalExpression>, Box<RationalExpression>),
@@ -66,6 +72,89 @@ impl RationalExpression {
         f(e)
     }
 
+    /// Compute a value bottom up over the expression.
+    ///
+    /// The function is called once for each node with the node itself and
+    /// the values computed for its direct subexpressions, in the order they
+    /// appear in the variant. Leaves receive an empty slice.
+    pub fn fold<T>(&self, f: &mut impl FnMut(&Self, &[T]) -> T) -> T {
+        use RationalExpression::*;
+        let children = match self {
+            X | Constant(_) | Trace(..) | Periodic(..) => vec![],
+            Polynomial(_, a) | ClaimPolynomial(_, _, a, _) | Neg(a) | Inv(a) | Exp(a, _) => {
+                vec![a.fold(f)]
+            }
+            Add(a, b) | Mul(a, b) => vec![a.fold(f), b.fold(f)],
+        };
+        f(self, &children)
+    }
+
+    /// Keccak hash of the expression tree.
+    ///
+    /// Each node is hashed as a tag byte, its own data and the digests of its
+    /// subexpressions. Unlike the `Hash` implementation this is independent of
+    /// the platform, so it can be used to pin a set of constraints.
+    pub fn digest(&self) -> Digest {
+        use RationalExpression::*;
+        self.fold(&mut |node, children: &[Digest]| {
+            let mut keccak = Keccak::v256();
+            match node {
+                X => keccak.update(&[0]),
+                Constant(c) => {
+                    keccak.update(&[1]);
+                    keccak.update(&c.to_uint().to_bytes_be());
+                }
+                &Trace(i, j) => {
+                    keccak.update(&[2]);
+                    keccak.update(&(i as u64).to_be_bytes());
+                    keccak.update(&(j as i64).to_be_bytes());
+                }
+                Polynomial(p, _) => {
+                    keccak.update(&[3]);
+                    keccak.update(&(p.coefficients().len() as u64).to_be_bytes());
+                    for coefficient in p.coefficients() {
+                        keccak.update(&coefficient.to_uint().to_bytes_be());
+                    }
+                }
+                &ClaimPolynomial(i, n, ..) => {
+                    keccak.update(&[4]);
+                    keccak.update(&(i as u64).to_be_bytes());
+                    keccak.update(&(n as u64).to_be_bytes());
+                }
+                Periodic(coefficients, repetitions) => {
+                    keccak.update(&[10]);
+                    keccak.update(&(*repetitions as u64).to_be_bytes());
+                    keccak.update(&(coefficients.len() as u64).to_be_bytes());
+                    for coefficient in coefficients {
+                        keccak.update(&coefficient.to_uint().to_bytes_be());
+                    }
+                }
+                Add(..) => keccak.update(&[5]),
+                Neg(_) => keccak.update(&[6]),
+                Mul(..) => keccak.update(&[7]),
+                Inv(_) => keccak.update(&[8]),
+                &Exp(_, e) => {
+                    keccak.update(&[9]);
+                    keccak.update(&(e as u64).to_be_bytes());
+                }
+            }
+            for child in children {
+                keccak.update(child.as_bytes());
+            }
+            let mut digest = [0_u8; 32];
+            keccak.finalize(&mut digest);
+            Digest::new(digest)
+        })
+    }
+
+    /// Number of nodes in the expression tree.
+    ///
+    /// Shared subexpressions are counted every time they occur, this is
+    /// proportional to the cost of evaluating the expression.
+    pub fn count_nodes(&self) -> usize {
+        self.fold(&mut |_, children: &[usize]| 1 + children.iter().sum::<usize>())
+    }
+
     pub fn substitute_claim(&self, claim_polynomials: &[DensePolynomial]) -> Self {
         use RationalExpression::*;
         let f = |x| {
@@ -83,6 +172,48 @@ impl RationalExpression {
         };
         self.map(&f)
     }
+
+    /// Replace all `Periodic` nodes by the equivalent `Polynomial` in
+    /// `X^repetitions`.
+    ///
+    /// This is for consumers that only handle dense polynomials, like the
+    /// Solidity verifier generator.
+    pub fn expand_periodic(&self) -> Self {
+        use RationalExpression::*;
+        self.map(&|x| {
+            match x {
+                Periodic(coefficients, repetitions) => {
+                    Self::periodic_as_polynomial(&coefficients, repetitions)
+                }
+                _ => x,
+            }
+        })
+    }
+
+    pub(crate) fn periodic_as_polynomial(
+        coefficients: &[FieldElement],
+        repetitions: usize,
+    ) -> Self {
+        Self::Polynomial(
+            SparsePolynomial::periodic(coefficients, 1).to_dense(),
+            Box::new(Self::X.pow(repetitions)),
+        )
+    }
+
+    /// Evaluate a `Periodic` node with Horner's rule in `x^repetitions`.
+    fn evaluate_periodic(
+        coefficients: &[FieldElement],
+        repetitions: usize,
+        x: &FieldElement,
+    ) -> FieldElement {
+        let x = x.pow(repetitions);
+        coefficients
+            .iter()
+            .rev()
+            .fold(FieldElement::zero(), |sum, coefficient| {
+                sum * &x + coefficient
+            })
+    }
 }
 
 impl From<i32> for RationalExpression {
@@ -139,6 +270,97 @@ impl<T: Into<RationalExpression>> Div<T> for RationalExpression {
     }
 }
 
+impl RationalExpression {
+    /// Binding strength of the outermost operator, used to decide where
+    /// `Display` needs parentheses.
+    fn precedence(&self) -> u8 {
+        use RationalExpression::*;
+        match self {
+            Add(..) => 1,
+            Mul(..) => 2,
+            Neg(_) => 3,
+            Exp(..) => 4,
+            _ => 5,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, precedence: u8) -> fmt::Result {
+        if self.precedence() < precedence {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+/// Human readable infix form that can be parsed back with `str::parse`.
+///
+/// Parentheses are only written where precedence or left associativity
+/// requires them. Polynomial, claim polynomial and periodic nodes are written
+/// for inspection only and can not be parsed back.
+impl fmt::Display for RationalExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use RationalExpression::*;
+        match self {
+            X => write!(f, "x"),
+            Constant(value) => {
+                let value = value.to_uint();
+                if value.limb(1) == 0 && value.limb(2) == 0 && value.limb(3) == 0 {
+                    write!(f, "{}", value.limb(0))
+                } else {
+                    write!(
+                        f,
+                        "0x{:016x}{:016x}{:016x}{:016x}",
+                        value.limb(3),
+                        value.limb(2),
+                        value.limb(1),
+                        value.limb(0)
+                    )
+                }
+            }
+            Trace(i, j) => write!(f, "trace({}, {})", i, j),
+            Polynomial(_, a) => write!(f, "polynomial({})", a),
+            ClaimPolynomial(i, _, a, _) => write!(f, "claim_polynomial_{}({})", i, a),
+            Periodic(_, repetitions) => write!(f, "periodic(x^{})", repetitions),
+            Add(a, b) => {
+                a.fmt_operand(f, 1)?;
+                match &**b {
+                    Neg(b) => {
+                        write!(f, " - ")?;
+                        b.fmt_operand(f, 2)
+                    }
+                    b => {
+                        write!(f, " + ")?;
+                        b.fmt_operand(f, 2)
+                    }
+                }
+            }
+            Neg(a) => {
+                write!(f, "-")?;
+                a.fmt_operand(f, 3)
+            }
+            Mul(a, b) => {
+                a.fmt_operand(f, 2)?;
+                match &**b {
+                    Inv(b) => {
+                        write!(f, " / ")?;
+                        b.fmt_operand(f, 3)
+                    }
+                    b => {
+                        write!(f, " * ")?;
+                        b.fmt_operand(f, 3)
+                    }
+                }
+            }
+            Inv(a) => write!(f, "inv({})", a),
+            Exp(a, e) => {
+                a.fmt_operand(f, 5)?;
+                write!(f, "^{}", e)
+            }
+        }
+    }
+}
+
 impl Sum<RationalExpression> for RationalExpression {
     fn sum<I>(mut iter: I) -> Self
     where
@@ -177,6 +399,10 @@ impl RationalExpression {
                 let (n, d) = a.degree_impl(x_degree, trace_degree);
                 (degree_bound * n, degree_bound * d)
             }
+            Periodic(coefficients, repetitions) => {
+                let periodic = SparsePolynomial::periodic(coefficients, *repetitions);
+                (periodic.degree() * x_degree, 0)
+            }
             Add(a, b) => {
                 let (a_numerator, a_denominator) = a.degree_impl(x_degree, trace_degree);
                 let (b_numerator, b_denominator) = b.degree_impl(x_degree, trace_degree);
@@ -225,6 +451,9 @@ impl RationalExpression {
                 }
             }
             ClaimPolynomial(..) => panic!("ClaimPolynomial should be substituted by Polynomial"),
+            Periodic(coefficients, repetitions) => {
+                (Self::evaluate_periodic(coefficients, *repetitions, x), true)
+            }
             Add(a, b) => {
                 let (res_a, a_ok) = a.check(x, trace);
                 let (res_b, b_ok) = b.check(x, trace);
@@ -304,6 +533,9 @@ impl RationalExpression {
                 p.evaluate(&inner)
             }
             ClaimPolynomial(..) => panic!("ClaimPolynomial should be substituted by Polynomial"),
+            Periodic(coefficients, repetitions) => {
+                Self::evaluate_periodic(coefficients, *repetitions, x)
+            }
             Add(a, b) => a.evaluate(x, trace) + b.evaluate(x, trace),
             Neg(a) => -&a.evaluate(x, trace),
             Mul(a, b) => a.evaluate(x, trace) * b.evaluate(x, trace),
@@ -312,6 +544,87 @@ impl RationalExpression {
         }
     }
 
+    /// Evaluate the expression on a batch of points.
+    ///
+    /// Inversions are deferred by evaluating each point as a fraction. All the
+    /// resulting denominators are then inverted in a single `invert_batch`
+    /// call. The `trace` function receives the evaluation point as its
+    /// third argument.
+    pub fn evaluate_batch(
+        &self,
+        xs: &[FieldElement],
+        trace: &dyn Fn(usize, isize, &FieldElement) -> FieldElement,
+    ) -> Vec<FieldElement> {
+        let (numerators, denominators): (Vec<_>, Vec<_>) = xs
+            .iter()
+            .map(|x| self.evaluate_fraction(x, &|i, j| trace(i, j, x)))
+            .unzip();
+        numerators
+            .into_iter()
+            .zip(invert_batch(&denominators))
+            .map(|(numerator, denominator_inv)| numerator * denominator_inv)
+            .collect()
+    }
+
+    /// Evaluate the expression as a (numerator, denominator) pair.
+    fn evaluate_fraction(
+        &self,
+        x: &FieldElement,
+        trace: &dyn Fn(usize, isize) -> FieldElement,
+    ) -> (FieldElement, FieldElement) {
+        use RationalExpression::*;
+        match self {
+            X => (x.clone(), FieldElement::one()),
+            Constant(c) => (c.clone(), FieldElement::one()),
+            &Trace(i, j) => (trace(i, j), FieldElement::one()),
+            Polynomial(p, a) => {
+                // Homogeneous Horner: p(n / d) = (sum_i c_i n^i d^(k - i)) / d^k
+                let (num, den) = a.evaluate_fraction(x, trace);
+                let mut coefficients = p.coefficients().iter().rev();
+                let mut numerator = coefficients
+                    .next()
+                    .cloned()
+                    .unwrap_or_else(FieldElement::zero);
+                let mut denominator = FieldElement::one();
+                for coefficient in coefficients {
+                    denominator *= &den;
+                    numerator *= &num;
+                    numerator += coefficient * &denominator;
+                }
+                (numerator, denominator)
+            }
+            ClaimPolynomial(..) => panic!("ClaimPolynomial should be substituted by Polynomial"),
+            Periodic(coefficients, repetitions) => {
+                (
+                    Self::evaluate_periodic(coefficients, *repetitions, x),
+                    FieldElement::one(),
+                )
+            }
+            Add(a, b) => {
+                let (an, ad) = a.evaluate_fraction(x, trace);
+                let (bn, bd) = b.evaluate_fraction(x, trace);
+                (an * &bd + bn * &ad, ad * bd)
+            }
+            Neg(a) => {
+                let (n, d) = a.evaluate_fraction(x, trace);
+                (-&n, d)
+            }
+            Mul(a, b) => {
+                let (an, ad) = a.evaluate_fraction(x, trace);
+                let (bn, bd) = b.evaluate_fraction(x, trace);
+                (an * bn, ad * bd)
+            }
+            Inv(a) => {
+                let (n, d) = a.evaluate_fraction(x, trace);
+                (d, n)
+            }
+            Exp(a, e) => {
+                let (num, den) = a.evaluate_fraction(x, trace);
+                (num.pow(*e), den.pow(*e))
+            }
+        }
+    }
+
     pub fn trace_arguments(&self) -> BTreeSet<(usize, isize)> {
         let mut arguments = BTreeSet::new();
         self.trace_arguments_impl(&mut arguments);
@@ -324,7 +637,7 @@ impl RationalExpression {
             &Trace(i, j) => {
                 let _ = s.insert((i, j));
             }
-            X | Constant(_) => (),
+            X | Constant(_) | Periodic(..) => (),
             Polynomial(_, a) | Exp(a, _) | Neg(a) | Inv(a) => a.trace_arguments_impl(s),
             Add(a, b) | Mul(a, b) => {
                 a.trace_arguments_impl(s);
@@ -388,6 +701,11 @@ impl Hash for RationalExpression {
                 n.hash(state);
                 a.hash(state);
             }
+            Periodic(coefficients, repetitions) => {
+                "periodic".hash(state);
+                coefficients.hash(state);
+                repetitions.hash(state);
+            }
         }
     }
 }
@@ -412,3 +730,116 @@ impl Ord for RationalExpression {
         get_hash(self).cmp(&get_hash(other))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn fold_counts_nodes() {
+        use RationalExpression::*;
+        let expression = (Trace(0, 1) * X - 3).pow(2) / (X - 1);
+        let mut count =
+            |_: &RationalExpression, children: &[usize]| 1 + children.iter().sum::<usize>();
+        // Mul(Exp(Add(Mul(Trace, X), Neg(Constant))), Inv(Add(X, Neg(Constant))))
+        assert_eq!(expression.fold(&mut count), 13);
+        assert_eq!(X.fold(&mut count), 1);
+    }
+
+    #[test]
+    fn count_nodes_nested() {
+        use RationalExpression::*;
+        let mut expression = Trace(0, 0);
+        for _ in 0..16 {
+            expression = expression.clone() + expression;
+        }
+        assert_eq!(expression.count_nodes(), (1 << 17) - 1);
+    }
+
+    #[test]
+    fn map_increments_constants() {
+        use RationalExpression::*;
+        let expression = Trace(0, 0) * 2 + X.pow(3) - 5;
+        let incremented = expression.map(&|node| {
+            match node {
+                Constant(c) => Constant(c + FieldElement::one()),
+                other => other,
+            }
+        });
+        assert_eq!(incremented, Trace(0, 0) * 3 + X.pow(3) - 6);
+    }
+
+    #[test]
+    fn display_minimal_parentheses() {
+        use RationalExpression::*;
+        assert_eq!(Trace(0, 1).to_string(), "trace(0, 1)");
+        assert_eq!(
+            ((Trace(0, 0) - X) / (X - 1)).to_string(),
+            "(trace(0, 0) - x) / (x - 1)"
+        );
+        assert_eq!(
+            (Trace(0, 1) * X - 3).pow(2).to_string(),
+            "(trace(0, 1) * x - 3)^2"
+        );
+        assert_eq!((X + 1 + X * 2).to_string(), "x + 1 + x * 2");
+        assert_eq!((X - (X - 1)).to_string(), "x - (x - 1)");
+        assert_eq!((X / (X * 2)).to_string(), "x / (x * 2)");
+        assert_eq!(X.pow(2).neg().to_string(), "-x^2");
+        assert_eq!(X.neg().pow(2).to_string(), "(-x)^2");
+        assert_eq!(X.pow(2).pow(3).to_string(), "(x^2)^3");
+    }
+
+    #[test]
+    fn periodic_matches_sparse_polynomial() {
+        use RationalExpression::*;
+        let coefficients: Vec<_> = (3..7).map(FieldElement::from).collect();
+        let periodic = SparsePolynomial::periodic(&coefficients, 8);
+        let constraint = Trace(0, 0) * Periodic(coefficients, 8) - X;
+        let reference = |x: &FieldElement, value: &FieldElement| value * periodic.evaluate(x) - x;
+        assert_eq!(constraint.degree(31), (31 + 24, 0));
+        assert_eq!(constraint.trace_degree(), (1, 0));
+        let root = FieldElement::root(32).unwrap();
+        for i in 0..32_usize {
+            let x = root.pow(i);
+            let value = FieldElement::from(i);
+            assert_eq!(constraint.evaluate(&x, &|_, _| value.clone()), reference(&x, &value));
+            assert_eq!(
+                constraint.expand_periodic().evaluate(&x, &|_, _| value.clone()),
+                reference(&x, &value)
+            );
+        }
+    }
+
+    proptest!(
+        #[test]
+        fn evaluate_batch_matches_evaluate(
+            xs: Vec<FieldElement>,
+            a: FieldElement,
+            b: FieldElement,
+        ) {
+            use RationalExpression::*;
+            prop_assume!(!xs.iter().any(|x| x.is_zero() || *x == a));
+            let polynomial =
+                DensePolynomial::new(&[b.clone(), a.clone(), FieldElement::one(), b.clone()]);
+            let expressions = vec![
+                X,
+                Constant(a.clone()),
+                Trace(0, 1) * X - &b,
+                X.inv() + Trace(1, -1).pow(3),
+                (X.pow(4) - &a) / (X - &a),
+                Polynomial(polynomial, Box::new(X.inv() + 1)) * Trace(0, 0),
+            ];
+            let trace = |i: usize, j: isize, x: &FieldElement| {
+                x * FieldElement::from(i) + FieldElement::from(j)
+            };
+            for expression in &expressions {
+                let expected = xs
+                    .iter()
+                    .map(|x| expression.evaluate(x, &|i, j| trace(i, j, x)))
+                    .collect::<Vec<_>>();
+                prop_assert_eq!(expression.evaluate_batch(&xs, &trace), expected);
+            }
+        }
+    );
+}
@@ -1,12 +1,14 @@
 use crate::{
-    channel::{RandomGenerator, Replayable, VerifierChannel},
+    channel::{ChannelTag, RandomGenerator, Replayable, VerifierChannel},
     constraints::Constraints,
-    proof_of_work, Proof,
+    proof_of_work,
+    verifier::Error,
+    Proof,
 };
 use hex::encode;
 use std::{collections::BTreeMap, prelude::v1::*};
 use zkp_hash::Hash;
-use zkp_merkle_tree::{Commitment, Error as MerkleError};
+use zkp_merkle_tree::Commitment;
 use zkp_primefield::FieldElement;
 use zkp_u256::U256;
 
@@ -61,7 +63,7 @@ pub fn proof_serialize(
     constraints: &Constraints,
     proof: &Proof,
     result_string: &mut String,
-) -> Result<(), MerkleError> {
+) -> Result<(), Error> {
     let proof = proof.as_bytes();
     let trace_length = constraints.trace_nrows();
     let trace_cols = constraints.trace_ncolumns();
@@ -69,11 +71,13 @@ pub fn proof_serialize(
 
     let mut channel = VerifierChannel::new(proof.to_vec());
     // TODO - Add method to seralize public input
+    channel.set_domain_separation(constraints.domain_separation);
     channel.initialize(constraints.channel_seed());
 
     // Get the low degree root commitment, and constraint root commitment
     // TODO: Make it work as channel.read()
-    let low_degree_extension_root: Hash = channel.replay();
+    channel.tag(ChannelTag::TraceRoot);
+    let low_degree_extension_root: Hash = channel.replay()?;
     result_string.push_str(&format!(
         "\"trace_commitment\": {}, \n",
         low_degree_extension_root.sol_encode()
@@ -81,7 +85,8 @@ pub fn proof_serialize(
     let lde_commitment = Commitment::from_size_hash(eval_domain_size, &low_degree_extension_root)?;
     let _ = channel.get_coefficients(2 * constraints.len());
 
-    let constraint_evaluated_root: Hash = channel.replay();
+    channel.tag(ChannelTag::ConstraintRoot);
+    let constraint_evaluated_root: Hash = channel.replay()?;
     result_string.push_str(&format!(
         "\"constraint_commitment\": {}, \n",
         constraint_evaluated_root.sol_encode()
@@ -97,7 +102,8 @@ pub fn proof_serialize(
     parseable_constraints.substitute();
 
     let trace_arguments = parseable_constraints.trace_arguments();
-    let trace_values: Vec<FieldElement> = channel.replay_many(trace_arguments.len());
+    channel.tag(ChannelTag::OodsValues);
+    let trace_values: Vec<FieldElement> = channel.replay_many(trace_arguments.len())?;
     result_string.push_str(&format!(
         "\"trace_oods_values\": {}, \n",
         trace_values.sol_encode()
@@ -109,7 +115,7 @@ pub fn proof_serialize(
 
     let constraints_trace_degree = constraints.degree().next_power_of_two();
     let claimed_constraint_values: Vec<FieldElement> =
-        channel.replay_many(constraints_trace_degree);
+        channel.replay_many(constraints_trace_degree)?;
     result_string.push_str(&format!(
         "\"constraint_oods_values\": {}, \n",
         claimed_constraint_values.sol_encode()
@@ -124,7 +130,8 @@ pub fn proof_serialize(
     // Get fri roots and eval points from the channel random
     for &num_folds in &constraints.fri_layout {
         fri_size >>= num_folds;
-        fri_commitments.push(Commitment::from_size_hash(fri_size, &channel.replay())?);
+        channel.tag(ChannelTag::FriLayer);
+        fri_commitments.push(Commitment::from_size_hash(fri_size, &channel.replay()?)?);
         eval_points.push(channel.get_random());
     }
     result_string.push_str(&format!(
@@ -133,14 +140,16 @@ pub fn proof_serialize(
     ));
 
     // Gets the last layer coeffiencts
-    let last_layer_coefficients = channel.replay_fri_layer(fri_size / constraints.blowup);
+    channel.tag(ChannelTag::FriLayer);
+    let last_layer_coefficients = channel.replay_fri_layer(fri_size / constraints.blowup)?;
     result_string.push_str(&format!(
         "\"last_layer_coefficients\": {}, \n",
         last_layer_coefficients.sol_encode()
     ));
 
     // Gets the proof of work from the proof.
-    let pow_response: proof_of_work::Response = channel.replay();
+    channel.tag(ChannelTag::Pow);
+    let pow_response: proof_of_work::Response = channel.replay()?;
     result_string.push_str(&format!(
         "\"pow_nonce\": \"0x{}\",",
         encode(pow_response.nonce().to_be_bytes())
@@ -156,8 +165,8 @@ pub fn proof_serialize(
     // Get values and check decommitment of low degree extension
     let lde_values: Vec<(usize, Vec<FieldElement>)> = queries
         .iter()
-        .map(|&index| (index, channel.replay_fri_layer(trace_cols)))
-        .collect();
+        .map(|&index| Ok((index, channel.replay_fri_layer(trace_cols)?)))
+        .collect::<Result<_, Error>>()?;
     let flattened_trace_values: Vec<FieldElement> =
         lde_values.iter().flat_map(|data| data.1.clone()).collect();
     result_string.push_str(&format!(
@@ -166,7 +175,7 @@ pub fn proof_serialize(
     ));
 
     let lde_proof_length = lde_commitment.proof_size(&queries)?;
-    let lde_hashes: Vec<Hash> = channel.replay_many(lde_proof_length);
+    let lde_hashes: Vec<Hash> = channel.replay_many(lde_proof_length)?;
     result_string.push_str(&format!(
         "\"trace_decommitment\": {}, \n",
         lde_hashes.sol_encode()
@@ -177,7 +186,7 @@ pub fn proof_serialize(
     for query_index in &queries {
         constraint_values.push((
             *query_index,
-            channel.replay_fri_layer(constraints_trace_degree),
+            channel.replay_fri_layer(constraints_trace_degree)?,
         ));
     }
     let flattened_constraint_values: Vec<FieldElement> = constraint_values
@@ -189,7 +198,7 @@ pub fn proof_serialize(
         flattened_constraint_values.sol_encode()
     ));
     let constraint_proof_length = constraint_commitment.proof_size(&queries)?;
-    let constraint_hashes: Vec<Hash> = channel.replay_many(constraint_proof_length);
+    let constraint_hashes: Vec<Hash> = channel.replay_many(constraint_proof_length)?;
     result_string.push_str(&format!(
         "\"constraint_decommitment\": {}, \n",
         constraint_hashes.sol_encode()
@@ -216,7 +225,7 @@ pub fn proof_serialize(
             for j in 0..coset_sizes[k] {
                 let n = i * coset_sizes[k] + j;
                 if previous_indices.binary_search(&n).is_err() {
-                    let held: FieldElement = channel.replay();
+                    let held: FieldElement = channel.replay()?;
                     proof_values.push(held.clone());
                 }
             }
@@ -224,7 +233,7 @@ pub fn proof_serialize(
         fri_values.push(proof_values);
 
         let merkle_proof_length = commitment.proof_size(&fri_indices)?;
-        let merkle_hashes: Vec<Hash> = channel.replay_many(merkle_proof_length);
+        let merkle_hashes: Vec<Hash> = channel.replay_many(merkle_proof_length)?;
         fri_decommitments.push(merkle_hashes.clone());
 
         previous_indices = fri_indices.clone();
@@ -197,7 +197,7 @@ impl RationalExpression {
         use RationalExpression::*;
 
         match self {
-            X | Constant(..) => BTreeMap::new(),
+            X | Constant(..) | Periodic(..) => BTreeMap::new(),
             Trace(..) => [(self.clone(), true)].iter().cloned().collect(),
             Add(a, b) | Mul(a, b) => {
                 let mut first = a.trace_search();
@@ -215,7 +215,7 @@ impl RationalExpression {
         use RationalExpression::*;
 
         match self {
-            X | Constant(_) | Trace(..) => BTreeMap::new(),
+            X | Constant(_) | Trace(..) | Periodic(..) => BTreeMap::new(),
             Add(a, b) | Mul(a, b) => {
                 let mut first = a.inv_search();
                 first.extend(b.inv_search());
@@ -232,7 +232,7 @@ impl RationalExpression {
 
         match self {
             X | Constant(_) | Trace(..) | ClaimPolynomial(..) => BTreeMap::new(),
-            Polynomial(..) => [(self.clone(), true)].iter().cloned().collect(),
+            Polynomial(..) | Periodic(..) => [(self.clone(), true)].iter().cloned().collect(),
             Add(a, b) | Mul(a, b) => {
                 let mut first = a.periodic_search();
                 first.extend(b.periodic_search());
@@ -248,7 +248,7 @@ impl RationalExpression {
 
         match self {
             ClaimPolynomial(..) => once(self).cloned().collect(),
-            X | Constant(_) | Trace(..) | Polynomial(..) => BTreeSet::new(),
+            X | Constant(_) | Trace(..) | Polynomial(..) | Periodic(..) => BTreeSet::new(),
             Add(a, b) | Mul(a, b) => {
                 let mut first = a.claim_polynomial_search();
                 first.extend(b.claim_polynomial_search());
@@ -302,7 +302,13 @@ pub fn generate(
     let blowup = constraints.blowup;
     let n_cols = constraints.trace_ncolumns();
     let trace_len = constraints.trace_nrows();
-    let constraint_expressions = constraints.expressions();
+    // Periodic columns are generated from their dense polynomials.
+    let constraint_expressions: Vec<_> = constraints
+        .expressions()
+        .iter()
+        .map(RationalExpression::expand_periodic)
+        .collect();
+    let constraint_expressions = constraint_expressions.as_slice();
 
     let mut traces = BTreeMap::new();
     let mut inverses = BTreeMap::new();
@@ -1,23 +1,75 @@
-use crate::polynomial::DensePolynomial;
+use crate::{constraints::Constraints, polynomial::DensePolynomial};
 use log::trace;
 use std::{
-    ops::{Index, IndexMut},
+    convert::TryInto,
+    fmt,
+    ops::{Index, IndexMut, Range},
     prelude::v1::*,
 };
+use zkp_hash::{Hash, Hashable, Hasher};
+use zkp_merkle_tree::VectorCommitment;
 use zkp_mmap_vec::MmapVec;
-use zkp_primefield::{fft::permute, Fft, FieldElement, Root, Zero};
+use zkp_primefield::{fft::permute, Fft, FieldElement, Pow, Root, Zero};
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    NoColumns,
+    NoRows,
+    RaggedColumns {
+        column:   usize,
+        length:   usize,
+        expected: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+        match *self {
+            NoColumns => write!(f, "Trace table must have at least one column"),
+            NoRows => write!(f, "Trace table must have at least one row"),
+            RaggedColumns {
+                column,
+                length,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "Column {} has length {}, but expected length {}",
+                    column, length, expected
+                )
+            }
+        }
+    }
+}
+
+/// Order in which the values of a [`TraceTable`] are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// Rows are contiguous. Rows can be borrowed as slices.
+    RowMajor,
+    /// Columns are contiguous. This is faster for reading a column at
+    /// different row offsets, as constraint evaluation does.
+    ColumnMajor,
+}
+
+#[derive(Clone)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct TraceTable {
     trace_length: usize,
     num_columns:  usize,
+    layout:       Layout,
     values:       MmapVec<FieldElement>,
 }
 
 impl TraceTable {
     /// Constructs a zero-initialized trace table of the given size.
     pub fn new(trace_length: usize, num_columns: usize) -> Self {
+        Self::with_layout(trace_length, num_columns, Layout::RowMajor)
+    }
+
+    /// Constructs a zero-initialized trace table with the given layout.
+    pub fn with_layout(trace_length: usize, num_columns: usize, layout: Layout) -> Self {
         let mut values: MmapVec<FieldElement> = MmapVec::with_capacity(trace_length * num_columns);
         for _ in 0..(trace_length * num_columns) {
             values.push(FieldElement::zero());
@@ -25,10 +77,148 @@ impl TraceTable {
         Self {
             trace_length,
             num_columns,
+            layout,
+            values,
+        }
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Copy of the table with the other storage layout.
+    ///
+    /// The values are the same, only the order in which they are stored
+    /// changes. Transposing twice gives back the original storage.
+    pub fn transpose(&self) -> Self {
+        let layout = match self.layout {
+            Layout::RowMajor => Layout::ColumnMajor,
+            Layout::ColumnMajor => Layout::RowMajor,
+        };
+        let mut values = MmapVec::with_capacity(self.values.len());
+        match layout {
+            Layout::RowMajor => {
+                for i in 0..self.trace_length {
+                    for j in 0..self.num_columns {
+                        values.push(self[(i, j)].clone());
+                    }
+                }
+            }
+            Layout::ColumnMajor => {
+                for j in 0..self.num_columns {
+                    values.extend(self.iter_column(j).cloned());
+                }
+            }
+        }
+        Self {
+            trace_length: self.trace_length,
+            num_columns: self.num_columns,
+            layout,
             values,
         }
     }
 
+    /// Position of row `i`, column `j` in `values`.
+    fn offset(&self, i: usize, j: usize) -> usize {
+        match self.layout {
+            Layout::RowMajor => i * self.num_columns + j,
+            Layout::ColumnMajor => j * self.trace_length + i,
+        }
+    }
+
+    /// Constructs a trace table from a list of columns.
+    ///
+    /// All columns must have the same, non-zero, length.
+    pub fn from_columns(columns: Vec<Vec<FieldElement>>) -> Result<Self, Error> {
+        let trace_length = columns.first().ok_or(Error::NoColumns)?.len();
+        if trace_length == 0 {
+            return Err(Error::NoRows);
+        }
+        for (column, values) in columns.iter().enumerate() {
+            if values.len() != trace_length {
+                return Err(Error::RaggedColumns {
+                    column,
+                    length: values.len(),
+                    expected: trace_length,
+                });
+            }
+        }
+        let num_columns = columns.len();
+        let mut values = MmapVec::with_capacity(trace_length * num_columns);
+        let mut columns: Vec<_> = columns.into_iter().map(Vec::into_iter).collect();
+        for _ in 0..trace_length {
+            for column in &mut columns {
+                values.push(column.next().unwrap());
+            }
+        }
+        Ok(Self {
+            trace_length,
+            num_columns,
+            layout: Layout::RowMajor,
+            values,
+        })
+    }
+
+    /// Append a column and return its index.
+    ///
+    /// The column must have exactly one value for every row. This copies the
+    /// whole table.
+    pub fn add_column(&mut self, column: Vec<FieldElement>) -> Result<usize, Error> {
+        if column.len() != self.trace_length {
+            return Err(Error::RaggedColumns {
+                column:   self.num_columns,
+                length:   column.len(),
+                expected: self.trace_length,
+            });
+        }
+        let num_columns = self.num_columns + 1;
+        let mut values = MmapVec::with_capacity(self.trace_length * num_columns);
+        match self.layout {
+            Layout::RowMajor => {
+                for (row, value) in self.values.chunks_exact(self.num_columns).zip(column) {
+                    values.extend_from_slice(row);
+                    values.push(value);
+                }
+            }
+            Layout::ColumnMajor => {
+                values.extend_from_slice(&self.values);
+                values.extend(column);
+            }
+        }
+        self.values = values;
+        self.num_columns = num_columns;
+        Ok(num_columns - 1)
+    }
+
+    /// Remove the `j`-th column, shifting the later columns one to the left.
+    ///
+    /// Constraints are not tracked by the table, so any `Trace` expression
+    /// referring to column `j` or above needs to be updated by the caller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `j` is out of range or if it is the only column.
+    pub fn remove_column(&mut self, j: usize) {
+        assert!(j < self.num_columns, "Column index out of range");
+        assert!(self.num_columns > 1, "Trace table must keep a column");
+        let num_columns = self.num_columns - 1;
+        let mut values = MmapVec::with_capacity(self.trace_length * num_columns);
+        match self.layout {
+            Layout::RowMajor => {
+                for row in self.values.chunks_exact(self.num_columns) {
+                    values.extend_from_slice(&row[..j]);
+                    values.extend_from_slice(&row[j + 1..]);
+                }
+            }
+            Layout::ColumnMajor => {
+                values.extend_from_slice(&self.values[..j * self.trace_length]);
+                values.extend_from_slice(&self.values[(j + 1) * self.trace_length..]);
+            }
+        }
+        self.values = values;
+        self.num_columns = num_columns;
+    }
+
     pub fn num_rows(&self) -> usize {
         self.trace_length
     }
@@ -42,20 +232,39 @@ impl TraceTable {
     }
 
     pub fn iter_row(&self, i: usize) -> impl Iterator<Item = &FieldElement> {
-        // Delegate to Index<usize> which returns a row slice.
-        self[i].iter()
+        assert!(i < self.trace_length);
+        let (start, stride) = match self.layout {
+            Layout::RowMajor => (i * self.num_columns, 1),
+            Layout::ColumnMajor => (i, self.trace_length),
+        };
+        self.values[start..]
+            .iter()
+            .step_by(stride)
+            .take(self.num_columns)
     }
 
     pub fn iter_column(&self, j: usize) -> impl Iterator<Item = &FieldElement> {
-        self.values[j..].iter().step_by(self.num_columns)
+        assert!(j < self.num_columns);
+        let (start, stride) = match self.layout {
+            Layout::RowMajor => (j, self.num_columns),
+            Layout::ColumnMajor => (j * self.trace_length, 1),
+        };
+        self.values[start..]
+            .iter()
+            .step_by(stride)
+            .take(self.trace_length)
+    }
+
+    /// Iterate over the columns, each as an iterator over its values.
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &FieldElement>> {
+        (0..self.num_columns).map(move |j| self.iter_column(j))
     }
 
     /// Extract the j-th column as a vector
     ///
     /// It allocates a potentially large new vector. Where possible, use
     /// the index accessors or the column iterator instead. It is unfortunately
-    /// not possible to get a slice of a column (since the representation is
-    /// row first.)
+    /// not possible to get a slice of a column in row-major layout.
     // TODO: Use strides
     pub fn column_to_mmapvec(&self, j: usize) -> MmapVec<FieldElement> {
         let mut result: MmapVec<FieldElement> = MmapVec::with_capacity(self.trace_length);
@@ -65,6 +274,115 @@ impl TraceTable {
         result
     }
 
+    /// Compare only the first `meaningful_rows` rows of two tables.
+    ///
+    /// Tables that differ in padding, or even in length, compare equal as long
+    /// as they have the same columns and agree on the meaningful rows. If
+    /// either table has fewer rows this returns `false`.
+    pub fn equals_up_to(&self, other: &Self, meaningful_rows: usize) -> bool {
+        if self.num_columns != other.num_columns
+            || meaningful_rows > self.trace_length
+            || meaningful_rows > other.trace_length
+        {
+            return false;
+        }
+        if self.layout == Layout::RowMajor && other.layout == Layout::RowMajor {
+            let end = meaningful_rows * self.num_columns;
+            return self.values[..end] == other.values[..end];
+        }
+        (0..meaningful_rows).all(|i| self.iter_row(i).eq(other.iter_row(i)))
+    }
+
+    /// Whether both tables have the same trace commitment.
+    ///
+    /// The commitment covers every row, including padding, so tables that only
+    /// agree on the meaningful rows (see
+    /// [`equals_up_to`](TraceTable::equals_up_to)) are not commitment-equal.
+    /// The storage layout is not committed to. This agrees with `==`, but
+    /// compares the row hashes that end up in the Merkle tree.
+    pub fn commitment_eq(&self, other: &Self) -> bool {
+        self.trace_length == other.trace_length
+            && self.num_columns == other.num_columns
+            && (0..self.trace_length).all(|i| self.leaf_hash(i) == other.leaf_hash(i))
+    }
+
+    /// Whether row `i` is outside the support of the constraints.
+    ///
+    /// A constraint is enforced on a row if it does not hold there for a
+    /// generic trace, that is if its denominator does not cancel. A row is
+    /// padding if no constraint reads it from a row where it is enforced.
+    /// Values on padding rows are unconstrained, but they are still committed
+    /// to. The same caveats on complex rational expressions as in
+    /// [`check_constraints`](crate::check_constraints) apply.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range or the constraints are for a trace of a
+    /// different length.
+    pub fn is_padding_row(&self, i: usize, constraints: &Constraints) -> bool {
+        assert!(i < self.trace_length);
+        assert_eq!(constraints.trace_nrows(), self.trace_length);
+        let num_rows = self.trace_length;
+        let signed_rows: isize = num_rows.try_into().unwrap();
+        let row_at =
+            |offset: isize| -> usize { offset.rem_euclid(signed_rows).try_into().unwrap() };
+        // Distinct powers of the multiplicative generator for every cell.
+        let generic = |column: usize, offset: isize| {
+            FieldElement::generator().pow(1 + column * num_rows + row_at(offset))
+        };
+        let generator = self.generator();
+        let i: isize = i.try_into().unwrap();
+        constraints.expressions().iter().all(|expression| {
+            expression.trace_arguments().into_iter().all(|(_, offset)| {
+                let x = generator.pow(row_at(i - offset));
+                expression.check(&x, &generic).1
+            })
+        })
+    }
+
+    /// Borrow a rectangular block of the table.
+    ///
+    /// Indices into the view are relative to the start of the ranges.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ranges are out of bounds.
+    pub fn view(&self, rows: Range<usize>, columns: Range<usize>) -> TraceView<'_> {
+        self.check_block(&rows, &columns);
+        TraceView {
+            table: self,
+            rows,
+            columns,
+        }
+    }
+
+    /// Mutably borrow a rectangular block of the table.
+    ///
+    /// See [`view`](TraceTable::view).
+    pub fn view_mut(&mut self, rows: Range<usize>, columns: Range<usize>) -> TraceViewMut<'_> {
+        self.check_block(&rows, &columns);
+        TraceViewMut {
+            table: self,
+            rows,
+            columns,
+        }
+    }
+
+    fn check_block(&self, rows: &Range<usize>, columns: &Range<usize>) {
+        assert!(
+            rows.start <= rows.end && rows.end <= self.trace_length,
+            "Row range {:?} out of bounds for trace table with {} rows",
+            rows,
+            self.trace_length
+        );
+        assert!(
+            columns.start <= columns.end && columns.end <= self.num_columns,
+            "Column range {:?} out of bounds for trace table with {} columns",
+            columns,
+            self.num_columns
+        );
+    }
+
     pub fn interpolate(&self) -> Vec<DensePolynomial> {
         trace!("BEGIN Interpolate");
         let result = (0..self.num_columns())
@@ -90,6 +408,24 @@ impl TraceTable {
     }
 }
 
+/// Tables are equal if they have the same dimensions and values, regardless of
+/// layout or spare capacity.
+///
+/// Padding rows are included. Use [`TraceTable::equals_up_to`] to compare only
+/// the meaningful rows.
+impl PartialEq for TraceTable {
+    fn eq(&self, other: &Self) -> bool {
+        if self.trace_length != other.trace_length || self.num_columns != other.num_columns {
+            return false;
+        }
+        if self.layout == other.layout {
+            self.values == other.values
+        } else {
+            self.values == other.transpose().values
+        }
+    }
+}
+
 /// Returns a field
 impl Index<(usize, usize)> for TraceTable {
     type Output = FieldElement;
@@ -97,7 +433,7 @@ impl Index<(usize, usize)> for TraceTable {
     fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
         assert!(i < self.trace_length);
         assert!(j < self.num_columns);
-        &self.values[i * self.num_columns + j]
+        &self.values[self.offset(i, j)]
     }
 }
 
@@ -106,24 +442,381 @@ impl IndexMut<(usize, usize)> for TraceTable {
     fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
         assert!(i < self.trace_length);
         assert!(j < self.num_columns);
-        &mut self.values[i * self.num_columns + j]
+        let offset = self.offset(i, j);
+        &mut self.values[offset]
     }
 }
 
 /// Returns a row as a slice
+///
+/// # Panics
+///
+/// Panics if the table is not in row-major layout.
 impl Index<usize> for TraceTable {
     type Output = [FieldElement];
 
     fn index(&self, i: usize) -> &[FieldElement] {
+        assert_eq!(
+            self.layout,
+            Layout::RowMajor,
+            "Row slices require row-major layout"
+        );
         assert!(i < self.trace_length);
         &self.values[i * self.num_columns..(i + 1) * self.num_columns]
     }
 }
 
 /// Returns a mutable row as a slice
+///
+/// # Panics
+///
+/// Panics if the table is not in row-major layout.
 impl IndexMut<usize> for TraceTable {
     fn index_mut(&mut self, i: usize) -> &mut [FieldElement] {
+        assert_eq!(
+            self.layout,
+            Layout::RowMajor,
+            "Row slices require row-major layout"
+        );
         assert!(i < self.trace_length);
         &mut self.values[i * self.num_columns..(i + 1) * self.num_columns]
     }
 }
+
+/// Commit to the rows of the trace.
+///
+/// Each leaf is a row. It is hashed like the rows of the prover's trace
+/// commitment: a single value by its own hash, wider rows by the hash of the
+/// concatenated column hashes.
+impl VectorCommitment for TraceTable {
+    type Leaf = Vec<FieldElement>;
+
+    fn len(&self) -> usize {
+        self.num_rows()
+    }
+
+    fn leaf(&self, index: usize) -> Self::Leaf {
+        self.iter_row(index).cloned().collect()
+    }
+
+    fn leaf_hash_with<H: Hasher>(&self, index: usize) -> Hash {
+        match self.layout {
+            Layout::RowMajor => (&self[index]).hash_with::<H>(),
+            Layout::ColumnMajor => (&self.leaf(index)[..]).hash_with::<H>(),
+        }
+    }
+}
+
+/// A rectangular block of a [`TraceTable`].
+#[derive(Clone)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct TraceView<'a> {
+    table:   &'a TraceTable,
+    rows:    Range<usize>,
+    columns: Range<usize>,
+}
+
+/// A mutable rectangular block of a [`TraceTable`].
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct TraceViewMut<'a> {
+    table:   &'a mut TraceTable,
+    rows:    Range<usize>,
+    columns: Range<usize>,
+}
+
+impl TraceView<'_> {
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+}
+
+impl TraceViewMut<'_> {
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+}
+
+impl Index<(usize, usize)> for TraceView<'_> {
+    type Output = FieldElement;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        assert!(i < self.rows.len());
+        assert!(j < self.columns.len());
+        &self.table[(self.rows.start + i, self.columns.start + j)]
+    }
+}
+
+impl Index<(usize, usize)> for TraceViewMut<'_> {
+    type Output = FieldElement;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        assert!(i < self.rows.len());
+        assert!(j < self.columns.len());
+        &self.table[(self.rows.start + i, self.columns.start + j)]
+    }
+}
+
+impl IndexMut<(usize, usize)> for TraceViewMut<'_> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        assert!(i < self.rows.len());
+        assert!(j < self.columns.len());
+        &mut self.table[(self.rows.start + i, self.columns.start + j)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RationalExpression;
+    use zkp_merkle_tree::Tree;
+
+    #[test]
+    fn commit_rows() {
+        let mut trace = TraceTable::new(8, 3);
+        for i in 0..8 {
+            for j in 0..3 {
+                trace[(i, j)] = FieldElement::from(10 * i + j);
+            }
+        }
+        let row_1 = trace[1].to_vec();
+        let row_5 = trace[5].to_vec();
+        let tree = Tree::from_leaves(trace).unwrap();
+        let proof = tree.open(&[1, 5]).unwrap();
+        proof
+            .verify(&[(1, row_1.clone()), (5, row_5.clone())])
+            .unwrap();
+        assert!(proof.verify(&[(1, row_5), (5, row_1)]).is_err());
+    }
+
+    #[test]
+    fn transpose_involution() {
+        let mut trace = TraceTable::new(8, 3);
+        for i in 0..8 {
+            for j in 0..3 {
+                trace[(i, j)] = FieldElement::from(10 * i + j);
+            }
+        }
+        let transposed = trace.transpose();
+        assert_eq!(transposed.layout(), Layout::ColumnMajor);
+        for i in 0..8 {
+            for j in 0..3 {
+                assert_eq!(transposed[(i, j)], trace[(i, j)]);
+            }
+            assert!(transposed.iter_row(i).eq(trace.iter_row(i)));
+        }
+        for j in 0..3 {
+            assert!(transposed.iter_column(j).eq(trace.iter_column(j)));
+        }
+        assert_eq!(transposed, trace);
+        assert!(transposed.equals_up_to(&trace, 8));
+        assert_eq!(transposed.leaf_hash(5), trace.leaf_hash(5));
+
+        let original = transposed.transpose();
+        assert_eq!(original.layout(), Layout::RowMajor);
+        assert_eq!(original.values, trace.values);
+    }
+
+    #[test]
+    fn column_major_columns() {
+        let mut trace = TraceTable::with_layout(4, 2, Layout::ColumnMajor);
+        trace[(1, 1)] = FieldElement::from(5);
+        let column = (0..4).map(FieldElement::from).collect::<Vec<_>>();
+        assert_eq!(trace.add_column(column.clone()), Ok(2));
+        assert!(trace.iter_column(2).eq(column.iter()));
+        trace.remove_column(0);
+        assert_eq!(trace.num_columns(), 2);
+        assert_eq!(trace[(1, 0)], FieldElement::from(5));
+        assert!(trace.iter_column(1).eq(column.iter()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Row slices require row-major layout")]
+    fn column_major_row_slice() {
+        let trace = TraceTable::with_layout(4, 2, Layout::ColumnMajor);
+        let _ = &trace[0];
+    }
+
+    #[test]
+    fn equals_up_to_ignores_padding() {
+        let mut zero_padded = TraceTable::new(8, 2);
+        let mut repeat_padded = TraceTable::new(16, 2);
+        for i in 0..5 {
+            zero_padded[(i, 0)] = FieldElement::from(i);
+            zero_padded[(i, 1)] = FieldElement::from(i * i);
+        }
+        for i in 0..16 {
+            let row = std::cmp::min(i, 4);
+            repeat_padded[(i, 0)] = FieldElement::from(row);
+            repeat_padded[(i, 1)] = FieldElement::from(row * row);
+        }
+        assert!(zero_padded.equals_up_to(&repeat_padded, 5));
+        assert!(repeat_padded.equals_up_to(&zero_padded, 5));
+        assert!(!zero_padded.equals_up_to(&repeat_padded, 6));
+        assert!(!zero_padded.equals_up_to(&repeat_padded, 9));
+        assert!(!zero_padded.equals_up_to(&TraceTable::new(8, 3), 0));
+    }
+
+    #[test]
+    fn view_mut_writes_to_table() {
+        let mut table = TraceTable::new(8, 4);
+        {
+            let mut view = table.view_mut(2..6, 1..3);
+            assert_eq!(view.num_rows(), 4);
+            assert_eq!(view.num_columns(), 2);
+            view[(0, 0)] = FieldElement::from(5);
+            view[(3, 1)] = FieldElement::from(7);
+        }
+        assert_eq!(table[(2, 1)], FieldElement::from(5));
+        assert_eq!(table[(5, 2)], FieldElement::from(7));
+        let view = table.view(5..8, 2..4);
+        assert_eq!(view[(0, 0)], FieldElement::from(7));
+        assert_eq!(view[(2, 1)], FieldElement::zero());
+    }
+
+    #[test]
+    #[should_panic(expected = "Row range 4..9 out of bounds for trace table with 8 rows")]
+    fn view_rows_out_of_range() {
+        let table = TraceTable::new(8, 4);
+        let _ = table.view(4..9, 0..4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Column range 3..5 out of bounds for trace table with 4 columns")]
+    fn view_columns_out_of_range() {
+        let mut table = TraceTable::new(8, 4);
+        let _ = table.view_mut(0..8, 3..5);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: i < self.rows.len()")]
+    fn view_index_out_of_range() {
+        let table = TraceTable::new(8, 4);
+        let view = table.view(0..2, 0..2);
+        let _ = &view[(2, 0)];
+    }
+
+    #[test]
+    fn from_columns_round_trip() {
+        let columns: Vec<Vec<FieldElement>> = (0..3)
+            .map(|j| (0..8).map(|i| FieldElement::from(10 * j + i)).collect())
+            .collect();
+        let table = TraceTable::from_columns(columns.clone()).unwrap();
+        assert_eq!(table.num_rows(), 8);
+        assert_eq!(table.num_columns(), 3);
+        assert_eq!(table[(5, 2)], FieldElement::from(25));
+        let round_trip: Vec<Vec<FieldElement>> = table
+            .columns()
+            .map(|column| column.cloned().collect())
+            .collect();
+        assert_eq!(round_trip, columns);
+    }
+
+    #[test]
+    fn from_columns_invalid() {
+        assert_eq!(TraceTable::from_columns(vec![]), Err(Error::NoColumns));
+        assert_eq!(
+            TraceTable::from_columns(vec![vec![], vec![]]),
+            Err(Error::NoRows)
+        );
+        let error = TraceTable::from_columns(vec![
+            vec![FieldElement::zero(); 4],
+            vec![FieldElement::zero(); 4],
+            vec![FieldElement::zero(); 3],
+        ])
+        .unwrap_err();
+        assert_eq!(error, Error::RaggedColumns {
+            column:   2,
+            length:   3,
+            expected: 4,
+        });
+        assert_eq!(
+            error.to_string(),
+            "Column 2 has length 3, but expected length 4"
+        );
+    }
+
+    #[test]
+    fn add_remove_column() {
+        let column = |offset: usize| -> Vec<FieldElement> {
+            (0..4).map(|i| FieldElement::from(offset + i)).collect()
+        };
+        let mut trace = TraceTable::from_columns(vec![column(0), column(10)]).unwrap();
+        assert_eq!(trace.add_column(column(20)), Ok(2));
+        assert_eq!(trace.num_columns(), 3);
+        assert_eq!(
+            trace,
+            TraceTable::from_columns(vec![column(0), column(10), column(20)]).unwrap()
+        );
+        assert_eq!(
+            trace.iter_column(2).cloned().collect::<Vec<_>>(),
+            column(20)
+        );
+
+        trace.remove_column(1);
+        assert_eq!(
+            trace,
+            TraceTable::from_columns(vec![column(0), column(20)]).unwrap()
+        );
+    }
+
+    #[test]
+    fn add_column_length() {
+        let mut trace = TraceTable::new(4, 2);
+        assert_eq!(
+            trace.add_column(vec![FieldElement::zero(); 3]),
+            Err(Error::RaggedColumns {
+                column:   2,
+                length:   3,
+                expected: 4,
+            })
+        );
+        assert_eq!(trace, TraceTable::new(4, 2));
+    }
+
+    #[test]
+    fn commitment_eq_includes_padding() {
+        let mut trace = TraceTable::new(8, 2);
+        for i in 0..5 {
+            trace[(i, 0)] = FieldElement::from(i);
+            trace[(i, 1)] = FieldElement::from(i * i);
+        }
+        let transposed = trace.transpose();
+        assert!(trace.commitment_eq(&transposed));
+        assert_eq!(trace, transposed);
+
+        let mut padded = trace.clone();
+        padded[(7, 1)] = FieldElement::from(42);
+        assert!(trace.equals_up_to(&padded, 5));
+        assert!(!trace.commitment_eq(&padded));
+        assert_ne!(trace, padded);
+
+        let mut wider = trace.clone();
+        let _ = wider.add_column(vec![FieldElement::zero(); 8]).unwrap();
+        assert!(!trace.commitment_eq(&wider));
+    }
+
+    #[test]
+    fn padding_rows_from_constraints() {
+        use RationalExpression::*;
+        let g = Constant(FieldElement::root(8).unwrap());
+        let on_row = |index| (X - g.pow(index)).inv();
+        let constraints = Constraints::from_expressions((8, 1), vec![], vec![
+            (Trace(0, 0) - 1) * on_row(0),
+            (Trace(0, 1) - Trace(0, 0) * 2) * on_row(1),
+        ])
+        .unwrap();
+        let trace = TraceTable::new(8, 1);
+        let padding: Vec<_> = (0..8)
+            .filter(|&i| trace.is_padding_row(i, &constraints))
+            .collect();
+        assert_eq!(padding, vec![3, 4, 5, 6, 7]);
+    }
+}
@@ -3,10 +3,25 @@ use crate::constraint_check::{check_constraints, check_specific_constraint};
 #[cfg(feature = "prover")]
 use crate::{prove, ProverError, TraceTable};
 use crate::{verify, Constraints, Proof, VerifierError};
+use std::prelude::v1::*;
 
 pub trait Verifiable {
     fn constraints(&self) -> Constraints;
 
+    /// The serialized public input.
+    ///
+    /// This is the seed of the proof channel, so prover and verifier need to
+    /// agree on it byte for byte. Implementations should pass it to
+    /// `Constraints::from_expressions` so there is a single definition.
+    fn public_input_bytes(&self) -> Vec<u8>;
+
+    /// Verify a proof of this claim.
+    ///
+    /// The proof does not contain the public input, so there is no separate
+    /// check that it matches this claim. The public input seeds the channel
+    /// instead: a proof of a different claim draws different challenges and
+    /// fails like any other invalid proof, with the [`VerifierError`] of the
+    /// first check that does not hold.
     fn verify(&self, proof: &Proof) -> Result<(), VerifierError> {
         let constraints = self.constraints();
         verify(&constraints, proof)
@@ -96,8 +111,8 @@ pub(crate) mod tests {
         }
     }
 
-    impl Claim {
-        pub(crate) fn seed(&self) -> Vec<u8> {
+    impl Verifiable for Claim {
+        fn public_input_bytes(&self) -> Vec<u8> {
             let mut seed = self.index.to_be_bytes().to_vec();
             seed.extend_from_slice(&self.value.as_montgomery().to_bytes_be());
             // For backwards compatibility, don't include exponent in seed when it's 1.
@@ -106,9 +121,7 @@ pub(crate) mod tests {
             }
             seed
         }
-    }
 
-    impl Verifiable for Claim {
         fn constraints(&self) -> Constraints {
             use RationalExpression::*;
 
@@ -120,7 +133,7 @@ pub(crate) mod tests {
             let every_row = || (X - g.pow(trace_length - 1)) / (X.pow(trace_length) - 1);
 
             // Constraints
-            Constraints::from_expressions((trace_length, 2), self.seed(), vec![
+            Constraints::from_expressions((trace_length, 2), self.public_input_bytes(), vec![
                 (Trace(0, 1) - Trace(1, 0).pow(self.exponent)) * every_row(),
                 (Trace(1, 1) - Trace(0, 0) - Trace(1, 0)) * every_row(),
                 (Trace(0, 0) - 1) * on_row(trace_length),
@@ -203,7 +216,17 @@ pub(crate) mod tests {
     }
 
     impl Claim2 {
-        fn seed(&self) -> Vec<u8> {
+        fn trace_length(&self) -> usize {
+            (self.index + 1).next_power_of_two()
+        }
+
+        fn claim_polynomials(&self) -> Vec<DensePolynomial> {
+            vec![DensePolynomial::new(&[self.value.clone()])]
+        }
+    }
+
+    impl Verifiable for Claim2 {
+        fn public_input_bytes(&self) -> Vec<u8> {
             let mut seed = self.index.to_be_bytes().to_vec();
             for coefficient in &self.coefficients {
                 seed.extend_from_slice(&coefficient.as_montgomery().to_bytes_be());
@@ -216,16 +239,6 @@ pub(crate) mod tests {
             seed
         }
 
-        fn trace_length(&self) -> usize {
-            (self.index + 1).next_power_of_two()
-        }
-
-        fn claim_polynomials(&self) -> Vec<DensePolynomial> {
-            vec![DensePolynomial::new(&[self.value.clone()])]
-        }
-    }
-
-    impl Verifiable for Claim2 {
         fn constraints(&self) -> Constraints {
             use RationalExpression::*;
 
@@ -261,7 +274,8 @@ pub(crate) mod tests {
                 .map(|c| c.substitute_claim(&claim_polynomials))
                 .collect();
 
-            Constraints::from_expressions((trace_length, 1), self.seed(), constraints).unwrap()
+            Constraints::from_expressions((trace_length, 1), self.public_input_bytes(), constraints)
+                .unwrap()
         }
     }
 
@@ -329,4 +343,59 @@ pub(crate) mod tests {
                 .boxed()
         }
     }
+
+    proptest!(
+        #[test]
+        fn public_input_is_channel_seed(recurrance: Recurrance, recurrance2: Recurrance2) {
+            let claim = recurrance.claim();
+            let constraints = claim.constraints();
+            prop_assert_eq!(claim.public_input_bytes(), constraints.channel_seed());
+            let claim = recurrance2.claim();
+            let constraints = claim.constraints();
+            prop_assert_eq!(claim.public_input_bytes(), constraints.channel_seed());
+        }
+    );
+
+    #[test]
+    fn prover_and_verifier_seed() {
+        let recurrance = Recurrance {
+            index:         10,
+            initial_value: FieldElement::from(3),
+            exponent:      1,
+        };
+        let claim = recurrance.claim();
+        let (proof, transcript) =
+            crate::prove_with_transcript(&claim.constraints(), &claim.trace(&recurrance.witness()))
+                .unwrap();
+        assert_eq!(
+            transcript.first(),
+            Some(&crate::TranscriptEvent::Seed(claim.public_input_bytes()))
+        );
+        let (result, transcript) = crate::verify_with_transcript(&claim.constraints(), &proof);
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            transcript.first(),
+            Some(&crate::TranscriptEvent::Seed(claim.public_input_bytes()))
+        );
+    }
+
+    #[test]
+    fn verify_rejects_other_claim() {
+        let recurrance = Recurrance {
+            index:         10,
+            initial_value: FieldElement::from(3),
+            exponent:      1,
+        };
+        let claim = recurrance.claim();
+        let proof = claim.prove(&recurrance.witness()).unwrap();
+        assert_eq!(claim.verify(&proof), Ok(()));
+
+        // There is no dedicated error for a claim mismatch. The other claim
+        // seeds the channel differently and the proof fails an ordinary check.
+        let other = Claim {
+            value: claim.value.clone() + FieldElement::one(),
+            ..claim
+        };
+        assert!(other.verify(&proof).is_err());
+    }
 }
@@ -1,14 +1,18 @@
 use crate::{
-    channel::{RandomGenerator, Replayable, VerifierChannel},
+    channel::{
+        ChannelTag, Error as ChannelError, ProofSource, RandomGenerator, Replayable,
+        TranscriptEvent, VerifierChannel,
+    },
     constraints::Constraints,
     polynomial::DensePolynomial,
-    proof_of_work, Proof,
+    rational_expression::RationalExpression,
+    Proof,
 };
 use log::trace;
 #[cfg(feature = "std")]
 use std::error;
 use std::{collections::BTreeMap, fmt, prelude::v1::*};
-use zkp_hash::Hash;
+use zkp_hash::{Hash, Hasher, Keccak256, MaskedKeccak};
 use zkp_merkle_tree::{Commitment, Error as MerkleError, Proof as MerkleProof};
 use zkp_primefield::{
     fft, geometric_series::root_series, FieldElement, One, Pow, Root, SquareInline, Zero,
@@ -17,21 +21,30 @@ use zkp_u256::U256;
 
 type Result<T> = std::result::Result<T, Error>;
 
-// TODO - We could parametrize root unavailable with the size asked for and fri
-// error with which layer failed.
+// TODO - We could parametrize root unavailable with the size asked for.
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[allow(variant_size_differences)]
 pub enum Error {
     RootUnavailable,
     InvalidPoW,
     InvalidLDECommitment,
     InvalidConstraintCommitment,
-    InvalidFriCommitment,
+    InvalidFriCommitment {
+        layer: usize,
+    },
     HashMapFailure,
     ProofTooLong,
     OodsCalculationFailure,
     OodsMismatch,
     FriCalculationFailure,
+    OpeningInconsistency {
+        query: usize,
+    },
     Merkle(MerkleError),
+    Channel(ChannelError),
+    ConstraintDigestMismatch,
+    #[cfg(feature = "std")]
+    Io(std::io::ErrorKind),
 }
 
 impl fmt::Display for Error {
@@ -42,7 +55,9 @@ impl fmt::Display for Error {
             InvalidPoW => write!(f, "The suggested proof of work failed to verify"),
             InvalidLDECommitment => write!(f, "The LDE merkle proof is incorrect"),
             InvalidConstraintCommitment => write!(f, "The constraint merkle proof is incorrect"),
-            InvalidFriCommitment => write!(f, "A FRI layer commitment is incorrect"),
+            InvalidFriCommitment { layer } => {
+                write!(f, "The commitment of FRI layer {} is incorrect", layer)
+            }
             HashMapFailure => {
                 write!(
                     f,
@@ -63,8 +78,19 @@ impl fmt::Display for Error {
                 )
             }
             OodsMismatch => write!(f, "Calculated oods value doesn't match the committed one"),
+            OpeningInconsistency { query } => {
+                write!(
+                    f,
+                    "The trace and constraint openings disagree at query {}",
+                    query
+                )
+            }
             // This is a wrapper, so defer to the underlying types' implementation of `fmt`.
             Merkle(ref e) => std::fmt::Display::fmt(e, f),
+            Channel(ref e) => fmt::Display::fmt(e, f),
+            ConstraintDigestMismatch => write!(f, "The constraints do not match the pinned digest"),
+            #[cfg(feature = "std")]
+            Io(kind) => write!(f, "Reading the proof failed: {:?}", kind),
         }
     }
 }
@@ -75,6 +101,18 @@ impl From<MerkleError> for Error {
     }
 }
 
+impl From<ChannelError> for Error {
+    fn from(err: ChannelError) -> Self {
+        #[cfg(feature = "std")]
+        {
+            if let ChannelError::Io(kind) = err {
+                return Self::Io(kind);
+            }
+        }
+        Self::Channel(err)
+    }
+}
+
 // False positives on the Latex math.
 #[allow(clippy::doc_markdown)]
 /// # Stark verify
@@ -173,26 +211,99 @@ impl From<MerkleError> for Error {
 /// * Evaluate the final layer
 ///
 /// <!-- TODO: ellaborate FRI verification -->
+pub fn verify(constraints: &Constraints, proof: &Proof) -> Result<()> {
+    verify_with_hashers::<Keccak256, MaskedKeccak>(constraints, proof)
+}
+
+/// Verify a Stark proof hashing with `H` instead of Keccak.
+///
+/// See [`verify`]. This checks proofs from
+/// [`prove_with_hasher`](crate::prove_with_hasher) with the same `H`, which
+/// is used for the Fiat-Shamir channel and all Merkle trees.
+pub fn verify_with_hasher<H: Hasher>(constraints: &Constraints, proof: &Proof) -> Result<()> {
+    verify_with_hashers::<H, H>(constraints, proof)
+}
+
+/// Verify a Stark proof whose channel hashes with `C` and whose Merkle trees
+/// hash with `H`.
+pub(crate) fn verify_with_hashers<C: Hasher, H: Hasher>(
+    constraints: &Constraints,
+    proof: &Proof,
+) -> Result<()> {
+    let mut channel = VerifierChannel::<C>::with_hasher(proof.as_bytes().to_vec());
+    verify_channel::<C, H, _>(constraints, &mut channel)
+}
+
+/// Verify a Stark proof against constraints pinned by their digest.
+///
+/// The constraints are still needed to verify the proof, but they are only
+/// used if [`Constraints::digest`] matches `digest`. This allows taking the
+/// constraints from an untrusted source when verifying many proofs of the
+/// same statement.
+pub fn verify_with_digest(constraints: &Constraints, proof: &Proof, digest: &Hash) -> Result<()> {
+    if constraints.digest() != *digest {
+        return Err(Error::ConstraintDigestMismatch);
+    }
+    verify(constraints, proof)
+}
+
+/// Verify a Stark proof read from `reader`.
+///
+/// See [`verify`]. Checks a proof written by
+/// [`prove_streaming`](crate::prove_streaming), pulling each commitment and
+/// decommitment from `reader` when the verifier gets to it, so the proof is
+/// never held in memory as a whole. Verification stops at the first failing
+/// check, otherwise `reader` has to end right after the proof.
+///
+/// Reads are small and not buffered, so readers like files should be wrapped
+/// in a [`BufReader`](std::io::BufReader).
+#[cfg(feature = "std")]
+pub fn verify_reader(constraints: &Constraints, reader: &mut impl std::io::Read) -> Result<()> {
+    let mut channel = VerifierChannel::<Keccak256, _>::from_reader(reader);
+    verify_channel::<_, MaskedKeccak, _>(constraints, &mut channel)
+}
+
+/// Verify a Stark proof and record the Fiat-Shamir transcript.
+///
+/// See [`verify`]. The transcript is returned regardless of the outcome and
+/// can be compared with the one from `prove_with_transcript` to find the
+/// first place where prover and verifier diverge.
+pub fn verify_with_transcript(
+    constraints: &Constraints,
+    proof: &Proof,
+) -> (Result<()>, Vec<TranscriptEvent>) {
+    let mut channel = VerifierChannel::new(proof.as_bytes().to_vec()).with_trace();
+    let result = verify_channel::<_, MaskedKeccak, _>(constraints, &mut channel);
+    (result, channel.transcript().to_vec())
+}
+
 // TODO: Refactor into smaller function
 #[allow(clippy::too_many_lines)]
-pub fn verify(constraints: &Constraints, proof: &Proof) -> Result<()> {
+//
+// The channel hashes with `C` and the Merkle trees with `H`, the proof is
+// read from `S`.
+fn verify_channel<C: Hasher, H: Hasher, S: ProofSource>(
+    constraints: &Constraints,
+    channel: &mut VerifierChannel<C, S>,
+) -> Result<()> {
     trace!("BEGIN Verify");
-    let proof = proof.as_bytes();
     let trace_length = constraints.trace_nrows();
     let trace_cols = constraints.trace_ncolumns();
     let eval_domain_size = trace_length * constraints.blowup;
     let eval_x = root_series(eval_domain_size).collect::<Vec<_>>();
 
-    let mut channel = VerifierChannel::new(proof.to_vec());
+    channel.set_domain_separation(constraints.domain_separation);
     channel.initialize(constraints.channel_seed());
 
     // Get the low degree root commitment, and constraint root commitment
     // TODO: Make it work as channel.read()
-    let low_degree_extension_root: Hash = channel.replay();
+    channel.tag(ChannelTag::TraceRoot);
+    let low_degree_extension_root: Hash = channel.replay()?;
     let lde_commitment = Commitment::from_size_hash(eval_domain_size, &low_degree_extension_root)?;
     let constraint_coefficients = channel.get_coefficients(2 * constraints.len());
 
-    let constraint_evaluated_root: Hash = channel.replay();
+    channel.tag(ChannelTag::ConstraintRoot);
+    let constraint_evaluated_root: Hash = channel.replay()?;
     let constraint_commitment =
         Commitment::from_size_hash(eval_domain_size, &constraint_evaluated_root)?;
 
@@ -203,7 +314,8 @@ pub fn verify(constraints: &Constraints, proof: &Proof) -> Result<()> {
     let mut parseable_constraints = constraints.clone();
     parseable_constraints.substitute();
     let trace_arguments = parseable_constraints.trace_arguments();
-    let trace_values: Vec<FieldElement> = channel.replay_many(trace_arguments.len());
+    channel.tag(ChannelTag::OodsValues);
+    let trace_values: Vec<FieldElement> = channel.replay_many(trace_arguments.len())?;
     let claimed_trace_map: BTreeMap<(usize, isize), FieldElement> = trace_arguments
         .into_iter()
         .zip(trace_values.iter().cloned())
@@ -211,7 +323,7 @@ pub fn verify(constraints: &Constraints, proof: &Proof) -> Result<()> {
 
     let constraints_trace_degree = constraints.degree().next_power_of_two();
     let claimed_constraint_values: Vec<FieldElement> =
-        channel.replay_many(constraints_trace_degree);
+        channel.replay_many(constraints_trace_degree)?;
 
     let oods_coefficients =
         channel.get_coefficients(claimed_trace_map.len() + claimed_constraint_values.len());
@@ -222,57 +334,77 @@ pub fn verify(constraints: &Constraints, proof: &Proof) -> Result<()> {
     // Get fri roots and eval points from the channel random
     for &num_folds in &constraints.fri_layout {
         fri_size >>= num_folds;
-        fri_commitments.push(Commitment::from_size_hash(fri_size, &channel.replay())?);
+        channel.tag(ChannelTag::FriLayer);
+        fri_commitments.push(Commitment::from_size_hash(fri_size, &channel.replay()?)?);
         eval_points.push(channel.get_random());
     }
     // Gets the last layer coeffiencts
-    let last_layer_coefficients = channel.replay_fri_layer(fri_size / constraints.blowup);
+    channel.tag(ChannelTag::FriLayer);
+    let last_layer_coefficients = channel.replay_fri_layer(fri_size / constraints.blowup)?;
 
     // Gets the proof of work from the proof.
-    let pow_seed: proof_of_work::ChallengeSeed = channel.get_random();
-    let pow_challenge = pow_seed.with_difficulty(constraints.pow_bits);
-    let pow_response: proof_of_work::Response = channel.replay();
-    if !pow_challenge.verify(pow_response) {
-        return Err(Error::InvalidPoW);
-    }
+    let _ = channel.verify_pow(constraints.pow_bits).map_err(|error| {
+        match error {
+            ChannelError::InvalidProofOfWork { .. } => Error::InvalidPoW,
+            error => error.into(),
+        }
+    })?;
 
     // Gets queries from channel
     let queries = get_indices(
         constraints.num_queries,
         eval_domain_size.trailing_zeros(),
-        &mut channel,
+        channel,
     );
 
-    // Get values and check decommitment of low degree extension
+    // Get values and check decommitment of low degree extension. Values are
+    // read one element at a time, the same way the prover writes them.
     let lde_values: Vec<(usize, Vec<FieldElement>)> = queries
         .iter()
-        .map(|&index| (index, channel.replay_fri_layer(trace_cols)))
-        .collect();
+        .map(|&index| Ok((index, channel.replay_many(trace_cols)?)))
+        .collect::<Result<_>>()?;
     let lde_proof_length = lde_commitment.proof_size(&queries)?;
-    let lde_hashes: Vec<Hash> = channel.replay_many(lde_proof_length);
+    let lde_hashes: Vec<Hash> = channel.replay_many(lde_proof_length)?;
     let lde_proof = MerkleProof::from_hashes(&lde_commitment, &queries, &lde_hashes)?;
     // Note - we could express this a merkle error instead but this adds specificity
-    if lde_proof.verify(&lde_values).is_err() {
+    if lde_proof.verify_with::<H, _>(&lde_values).is_err() {
         return Err(Error::InvalidLDECommitment);
     }
 
     // Gets the values and checks the constraint decommitment
     let mut constraint_values = Vec::with_capacity(queries.len());
     for query_index in &queries {
-        constraint_values.push((
-            *query_index,
-            channel.replay_fri_layer(constraints_trace_degree),
-        ));
+        constraint_values.push((*query_index, channel.replay_many(constraints_trace_degree)?));
     }
     let constraint_proof_length = constraint_commitment.proof_size(&queries)?;
-    let constraint_hashes: Vec<Hash> = channel.replay_many(constraint_proof_length);
+    let constraint_hashes: Vec<Hash> = channel.replay_many(constraint_proof_length)?;
     let constraint_proof =
         MerkleProof::from_hashes(&constraint_commitment, &queries, &constraint_hashes)?;
     // Note - we could express this a merkle error instead but this adds specificity
-    if constraint_proof.verify(&constraint_values).is_err() {
+    if constraint_proof
+        .verify_with::<H, _>(&constraint_values)
+        .is_err()
+    {
         return Err(Error::InvalidConstraintCommitment);
     }
 
+    // Cross-check the trace openings against the constraint openings. This is
+    // only possible if the constraint polynomial is not split up.
+    if constraints_trace_degree == 1 {
+        let trace_arguments = claimed_trace_map.keys().copied().collect::<Vec<_>>();
+        check_opening_consistency(
+            &constraints
+                .combine(&constraint_coefficients)
+                .substitute_claim(&constraints.claim_polynomials),
+            &trace_arguments,
+            constraints.blowup,
+            &queries,
+            &lde_values,
+            &constraint_values,
+            &eval_x,
+        )?;
+    }
+
     let coset_sizes = constraints
         .fri_layout
         .iter()
@@ -318,7 +450,7 @@ pub fn verify(constraints: &Constraints, proof: &Proof) -> Result<()> {
                         )?);
                     }
                 } else {
-                    coset.push(channel.replay());
+                    coset.push(channel.read_field()?);
                 }
             }
             fri_layer_values.push((*i, coset));
@@ -340,7 +472,7 @@ pub fn verify(constraints: &Constraints, proof: &Proof) -> Result<()> {
         }
 
         let merkle_proof_length = commitment.proof_size(&fri_indices)?;
-        let merkle_hashes: Vec<Hash> = channel.replay_many(merkle_proof_length);
+        let merkle_hashes: Vec<Hash> = channel.replay_many(merkle_proof_length)?;
         let merkle_proof = MerkleProof::from_hashes(commitment, &fri_indices, &merkle_hashes)?;
         fri_folds = layer_folds;
 
@@ -350,8 +482,8 @@ pub fn verify(constraints: &Constraints, proof: &Proof) -> Result<()> {
         len /= coset_sizes[k];
 
         // Note - we could express this a merkle error instead but this adds specificity
-        if merkle_proof.verify(&fri_layer_values).is_err() {
-            return Err(Error::InvalidFriCommitment);
+        if merkle_proof.verify_with::<H, _>(&fri_layer_values).is_err() {
+            return Err(Error::InvalidFriCommitment { layer: k });
         };
 
         previous_indices = fri_indices.clone();
@@ -362,7 +494,7 @@ pub fn verify(constraints: &Constraints, proof: &Proof) -> Result<()> {
                 .collect();
         }
     }
-    if !channel.at_end() {
+    if !channel.at_end()? {
         return Err(Error::ProofTooLong);
     }
 
@@ -395,7 +527,7 @@ pub fn verify(constraints: &Constraints, proof: &Proof) -> Result<()> {
     Ok(())
 }
 
-fn oods_value_from_trace_values(
+pub(crate) fn oods_value_from_trace_values(
     constraints: &Constraints,
     coefficients: &[FieldElement],
     trace_values: &BTreeMap<(usize, isize), FieldElement>,
@@ -408,7 +540,7 @@ fn oods_value_from_trace_values(
         .evaluate(oods_point, &trace)
 }
 
-fn oods_value_from_constraint_values(
+pub(crate) fn oods_value_from_constraint_values(
     constraint_values: &[FieldElement],
     oods_point: &FieldElement,
 ) -> FieldElement {
@@ -421,9 +553,59 @@ fn oods_value_from_constraint_values(
     result
 }
 
+/// Recompute the combined constraint from the opened trace values and compare
+/// it to the opened constraint polynomial value.
+///
+/// Only the query rows are opened, so the check is done for those queries
+/// where all the rows referenced by the constraints happen to be opened as
+/// well. It requires the constraint polynomial to be unsplit, i.e. of trace
+/// degree one.
+// Offsets and domain sizes are small.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_possible_wrap)]
+#[allow(clippy::cast_sign_loss)]
+fn check_opening_consistency(
+    combined_constraints: &RationalExpression,
+    trace_arguments: &[(usize, isize)],
+    blowup: usize,
+    queries: &[usize],
+    lde_values: &[(usize, Vec<FieldElement>)],
+    constraint_values: &[(usize, Vec<FieldElement>)],
+    eval_x: &[FieldElement],
+) -> Result<()> {
+    let eval_domain_size = eval_x.len();
+    for (&query, (_, constraint_value)) in queries.iter().zip(constraint_values) {
+        let natural_index = fft::permute_index(eval_domain_size, query);
+        let rows = trace_arguments
+            .iter()
+            .map(|&(column, offset)| {
+                let neighbor = (natural_index as isize + offset * blowup as isize)
+                    .rem_euclid(eval_domain_size as isize) as usize;
+                let neighbor = fft::permute_index(eval_domain_size, neighbor);
+                queries
+                    .binary_search(&neighbor)
+                    .ok()
+                    .map(|k| ((column, offset), lde_values[k].1[column].clone()))
+            })
+            .collect::<Option<BTreeMap<_, _>>>();
+        if let Some(rows) = rows {
+            let x = &eval_x[natural_index] * FieldElement::generator();
+            let expected = combined_constraints.evaluate(&x, &|i, j| rows[&(i, j)].clone());
+            if constraint_value.first() != Some(&expected) {
+                return Err(Error::OpeningInconsistency { query });
+            }
+        }
+    }
+    Ok(())
+}
+
 // TODO: Clean up
 #[allow(clippy::cast_possible_truncation)]
-fn get_indices(num: usize, bits: u32, proof: &mut VerifierChannel) -> Vec<usize> {
+fn get_indices<C: Hasher, S: ProofSource>(
+    num: usize,
+    bits: u32,
+    proof: &mut VerifierChannel<C, S>,
+) -> Vec<usize> {
     let mut query_indices = Vec::with_capacity(num + 3);
     while query_indices.len() < num {
         let val: U256 = proof.get_random();
@@ -440,7 +622,7 @@ fn get_indices(num: usize, bits: u32, proof: &mut VerifierChannel) -> Vec<usize>
     query_indices
 }
 
-fn fri_fold(
+pub(crate) fn fri_fold(
     coset: &[FieldElement],
     eval_point: &FieldElement,
     mut step: usize,
@@ -530,9 +712,178 @@ mod tests {
     use crate::{
         prove,
         traits::tests::{Recurrance, Recurrance2},
-        Provable, Verifiable,
+        ProofParams, Provable, Verifiable,
     };
     use proptest::prelude::*;
+    use zkp_macros_decl::field_element;
+
+    #[test]
+    fn pinned_digest() {
+        let recurrance = Recurrance {
+            index:         30,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let claim = recurrance.claim();
+        let mut constraints = claim.constraints();
+        constraints.num_queries = 20;
+        let proof = prove(&constraints, &claim.trace(&recurrance.witness())).unwrap();
+        let digest = constraints.digest();
+        assert_eq!(verify_with_digest(&constraints, &proof, &digest), Ok(()));
+
+        let other = Recurrance {
+            index: 31,
+            ..recurrance
+        };
+        let mut other_constraints = other.claim().constraints();
+        other_constraints.num_queries = 20;
+        assert_eq!(
+            verify_with_digest(&other_constraints, &proof, &digest),
+            Err(Error::ConstraintDigestMismatch)
+        );
+    }
+
+    #[test]
+    fn opening_inconsistency() {
+        let recurrance = Recurrance {
+            index:         30,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let claim = recurrance.claim();
+        let constraints = claim.constraints();
+        let trace_polynomials = claim.trace(&recurrance.witness()).interpolate();
+        let trace_generator = FieldElement::root(constraints.trace_nrows()).unwrap();
+        let eval_domain_size = constraints.trace_nrows() * constraints.blowup;
+        let eval_x = root_series(eval_domain_size).collect::<Vec<_>>();
+        let coefficients = (1..=2 * constraints.len())
+            .map(FieldElement::from)
+            .collect::<Vec<_>>();
+        let combined_constraints = constraints.combine(&coefficients);
+
+        // Open a row together with the next row of the trace.
+        let mut queries = vec![
+            fft::permute_index(eval_domain_size, 5),
+            fft::permute_index(eval_domain_size, 5 + constraints.blowup),
+        ];
+        queries.sort_unstable();
+        let points = queries
+            .iter()
+            .map(|&query| {
+                &eval_x[fft::permute_index(eval_domain_size, query)] * FieldElement::generator()
+            })
+            .collect::<Vec<_>>();
+        let lde_values = queries
+            .iter()
+            .zip(&points)
+            .map(|(&query, x)| {
+                (
+                    query,
+                    trace_polynomials.iter().map(|p| p.evaluate(x)).collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut constraint_values = queries
+            .iter()
+            .zip(&points)
+            .map(|(&query, x)| {
+                let trace = |i: usize, j: isize| {
+                    trace_polynomials[i].evaluate(&(x * trace_generator.pow(j).unwrap()))
+                };
+                (query, vec![combined_constraints.evaluate(x, &trace)])
+            })
+            .collect::<Vec<_>>();
+
+        let check = |constraint_values: &[(usize, Vec<FieldElement>)]| {
+            check_opening_consistency(
+                &combined_constraints,
+                &constraints.trace_arguments(),
+                constraints.blowup,
+                &queries,
+                &lde_values,
+                constraint_values,
+                &eval_x,
+            )
+        };
+        assert_eq!(check(&constraint_values), Ok(()));
+        constraint_values[0].1[0] += FieldElement::one();
+        assert_eq!(
+            check(&constraint_values),
+            Err(Error::OpeningInconsistency { query: queries[0] })
+        );
+    }
+
+    /// Proof with proof of work and the offset of the nonce.
+    fn tamper_target() -> (Constraints, Proof, usize) {
+        let recurrance = Recurrance {
+            index:         30,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let claim = recurrance.claim();
+        let mut constraints = claim.constraints();
+        constraints.pow_bits = 8;
+        let proof = prove(&constraints, &claim.trace(&recurrance.witness())).unwrap();
+
+        // Commitments, out of domain values, FRI commitments and the last layer
+        let fri_size = (constraints.trace_nrows() * constraints.blowup)
+            >> constraints.fri_layout.iter().sum::<usize>();
+        let nonce_offset = 32
+            * (2 + constraints.trace_arguments().len()
+                + constraints.degree().next_power_of_two()
+                + constraints.fri_layout.len()
+                + fri_size / constraints.blowup);
+        (constraints, proof, nonce_offset)
+    }
+
+    fn tamper(proof: &Proof, offset: usize) -> Proof {
+        let mut bytes = proof.as_bytes().to_vec();
+        bytes[offset] ^= 1;
+        Proof::from_bytes(bytes)
+    }
+
+    #[test]
+    fn tampered_sections() {
+        let (constraints, proof, nonce_offset) = tamper_target();
+        assert_eq!(verify(&constraints, &proof), Ok(()));
+        assert_eq!(
+            verify(&constraints, &tamper(&proof, nonce_offset + 7)),
+            Err(Error::InvalidPoW)
+        );
+        // The trace values of the first query follow the nonce.
+        assert_eq!(
+            verify(&constraints, &tamper(&proof, nonce_offset + 8)),
+            Err(Error::InvalidLDECommitment)
+        );
+        // The proof ends with the decommitment of the last FRI layer.
+        assert_eq!(
+            verify(&constraints, &tamper(&proof, proof.as_bytes().len() - 1)),
+            Err(Error::InvalidFriCommitment {
+                layer: constraints.fri_layout.len() - 1,
+            })
+        );
+    }
+
+    #[test]
+    fn truncated_proof() {
+        let recurrance = Recurrance {
+            index:         30,
+            initial_value: field_element!("cafebabe"),
+            exponent:      1,
+        };
+        let claim = recurrance.claim();
+        let constraints = claim.constraints();
+        let proof = prove(&constraints, &claim.trace(&recurrance.witness())).unwrap();
+        let mut bytes = proof.as_bytes().to_vec();
+        let _ = bytes.pop();
+        assert_eq!(
+            verify(&constraints, &Proof::from_bytes(bytes)),
+            Err(Error::Channel(ChannelError::UnexpectedEof {
+                requested: 32,
+                remaining: 31,
+            }))
+        );
+    }
 
     proptest!(
         #[test]
@@ -540,7 +891,11 @@ mod tests {
             let public = r.claim();
             let private = r.witness();
 
-            let constraints = public.constraints();
+            let mut constraints = public.constraints();
+            let log_trace_nrows = constraints.trace_nrows().trailing_zeros() as usize;
+            constraints
+                .set_params(ProofParams::fast_insecure(log_trace_nrows))
+                .unwrap();
             let trace = public.trace(&private);
 
             prop_assert!(verify(&constraints, &prove(&constraints, &trace).unwrap()).is_ok());
@@ -0,0 +1,40 @@
+// Proves a small Fibonacci claim using only `core` and `alloc`. CI runs this
+// with `--no-default-features --features prover`, so `zkp-stark` is built
+// without its `std` feature.
+#![cfg(feature = "prover")]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec;
+use zkp_primefield::{FieldElement, One, Root};
+use zkp_stark::{prove, verify, Constraints, RationalExpression, TraceTable};
+
+#[test]
+fn prove_fibonacci() {
+    use RationalExpression::*;
+
+    let trace_length = 8;
+    let mut trace = TraceTable::new(trace_length, 2);
+    trace[(0, 0)] = FieldElement::one();
+    trace[(0, 1)] = FieldElement::from(3);
+    for i in 0..(trace_length - 1) {
+        trace[(i + 1, 0)] = trace[(i, 1)].clone();
+        trace[(i + 1, 1)] = &trace[(i, 0)] + &trace[(i, 1)];
+    }
+    let value = trace[(5, 0)].clone();
+
+    let g = Constant(FieldElement::root(trace_length).unwrap());
+    let on_row = |index| (X - g.pow(index)).inv();
+    let every_row = || (X - g.pow(trace_length - 1)) / (X.pow(trace_length) - 1);
+    let constraints = Constraints::from_expressions((trace_length, 2), b"no_std".to_vec(), vec![
+        (Trace(0, 1) - Trace(1, 0)) * every_row(),
+        (Trace(1, 1) - Trace(0, 0) - Trace(1, 0)) * every_row(),
+        (Trace(0, 0) - 1) * on_row(0),
+        (Trace(0, 0) - &value) * on_row(5),
+    ])
+    .unwrap();
+
+    let proof = prove(&constraints, &trace).unwrap();
+    assert_eq!(verify(&constraints, &proof), Ok(()));
+}